@@ -0,0 +1,353 @@
+//! グラフアルゴリズムの実行と結果の表示
+
+pub mod player;
+
+use itertools::Itertools;
+
+use crate::{
+    components::Colors,
+    graph::{algorithms, Graph},
+    view_state::GraphViewState,
+};
+
+/// 連結成分などを塗り分ける色
+const PALETTE: [Colors; 11] = [
+    Colors::Red,
+    Colors::Blue,
+    Colors::Green,
+    Colors::Yellow,
+    Colors::Violet,
+    Colors::Orange,
+    Colors::Cyan,
+    Colors::Pink,
+    Colors::Brown,
+    Colors::Indigo,
+    Colors::Gray,
+];
+
+/// 始点の色
+const SOURCE_COLOR: Colors = Colors::Orange;
+/// 訪問済みの頂点・探索木の辺の色
+const VISITED_COLOR: Colors = Colors::Cyan;
+/// 閉路や矛盾した辺など，問題箇所の色
+const PROBLEM_COLOR: Colors = Colors::Red;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlgorithmKind {
+    #[default]
+    Bfs,
+    Dfs,
+    ShortestPaths,
+    ConnectedComponents,
+    StronglyConnectedComponents,
+    TopologicalSort,
+    Bipartite,
+    Cycle,
+}
+
+impl AlgorithmKind {
+    pub const ALL: [Self; 8] = [
+        Self::Bfs,
+        Self::Dfs,
+        Self::ShortestPaths,
+        Self::ConnectedComponents,
+        Self::StronglyConnectedComponents,
+        Self::TopologicalSort,
+        Self::Bipartite,
+        Self::Cycle,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bfs => "BFS",
+            Self::Dfs => "DFS",
+            Self::ShortestPaths => "Shortest Paths",
+            Self::ConnectedComponents => "Connected Components",
+            Self::StronglyConnectedComponents => "Strongly Connected Components",
+            Self::TopologicalSort => "Topological Sort",
+            Self::Bipartite => "Bipartite Check",
+            Self::Cycle => "Cycle Detection",
+        }
+    }
+
+    /// 始点を指定する必要があるか
+    pub fn needs_source(self) -> bool {
+        matches!(self, Self::Bfs | Self::Dfs | Self::ShortestPaths)
+    }
+
+    /// 実行過程を再生できるか
+    pub fn is_traceable(self) -> bool {
+        matches!(self, Self::Bfs | Self::Dfs | Self::ShortestPaths)
+    }
+
+    /// 一方向の辺があるグラフでのみ実行できるか
+    pub fn requires_directed(self) -> bool {
+        matches!(
+            self,
+            Self::StronglyConnectedComponents | Self::TopologicalSort
+        )
+    }
+}
+
+/// アルゴリズムの実行結果（頂点・辺に重ねて表示する色とラベル）
+#[derive(Debug, Clone, Default)]
+pub struct AlgorithmResult {
+    pub vertex_colors: Vec<Option<Colors>>,
+    pub vertex_labels: Vec<Option<String>>,
+    pub edge_colors: Vec<Option<Colors>>,
+    pub summary: String,
+}
+
+impl AlgorithmResult {
+    fn new(graph: &Graph) -> Self {
+        Self {
+            vertex_colors: vec![None; graph.vertices.len()],
+            vertex_labels: vec![None; graph.vertices.len()],
+            edge_colors: vec![None; graph.edges.len()],
+            summary: String::new(),
+        }
+    }
+
+    /// 結果をビュー状態に重ねて表示する
+    pub fn paint(&self, view: &mut GraphViewState) {
+        view.clear_highlight();
+        for (vertex, color) in view.vertices.iter_mut().zip(&self.vertex_colors) {
+            vertex.highlight = *color;
+        }
+        for (vertex, label) in view.vertices.iter_mut().zip(&self.vertex_labels) {
+            vertex.highlight_label = label.clone();
+        }
+        for (edge, color) in view.edges.iter_mut().zip(&self.edge_colors) {
+            edge.highlight = *color;
+        }
+    }
+}
+
+/// アルゴリズムを実行する
+/// 負の重みの辺があればエラーを返す．頂点番号はユーザーの番号付けで表示する
+fn check_non_negative_weights(graph: &Graph, zero_indexed: bool) -> anyhow::Result<()> {
+    let display = |v: usize| if zero_indexed { v } else { v + 1 };
+    match algorithms::negative_edge(graph) {
+        Some(index) => Err(anyhow::anyhow!(
+            "Negative weight on edge {} -> {}",
+            display(graph.edges[index].from),
+            display(graph.edges[index].to)
+        )),
+        None => Ok(()),
+    }
+}
+
+pub fn run_algorithm(
+    kind: AlgorithmKind,
+    graph: &Graph,
+    source: usize,
+    zero_indexed: bool,
+) -> anyhow::Result<AlgorithmResult> {
+    let display = |v: usize| if zero_indexed { v } else { v + 1 };
+    let mut result = AlgorithmResult::new(graph);
+
+    if kind.needs_source() && graph.vertices.get(source).is_none_or(|v| v.is_deleted) {
+        return Err(anyhow::anyhow!("Vertex {} does not exist", display(source)));
+    }
+    if kind.requires_directed() && !graph.has_directed_edges() {
+        return Err(anyhow::anyhow!("{} requires directed edges", kind.name()));
+    }
+
+    match kind {
+        AlgorithmKind::Bfs | AlgorithmKind::Dfs => {
+            let traversal = if kind == AlgorithmKind::Bfs {
+                algorithms::bfs(graph, source)
+            } else {
+                algorithms::dfs(graph, source)
+            };
+            for (step, &v) in traversal.order.iter().enumerate() {
+                result.vertex_colors[v] = Some(VISITED_COLOR);
+                result.vertex_labels[v] = Some(match kind {
+                    AlgorithmKind::Bfs => traversal.depth[v].unwrap_or(0).to_string(),
+                    _ => step.to_string(),
+                });
+            }
+            for edge in traversal.parent_edge.iter().flatten() {
+                result.edge_colors[*edge] = Some(VISITED_COLOR);
+            }
+            result.vertex_colors[source] = Some(SOURCE_COLOR);
+            result.summary = format!(
+                "Order: {}",
+                traversal.order.iter().map(|&v| display(v)).join(" ")
+            );
+        }
+        AlgorithmKind::ShortestPaths => {
+            check_non_negative_weights(graph, zero_indexed)?;
+            let paths = algorithms::shortest_paths(graph, source)?;
+            for (v, dist) in paths.dist.iter().enumerate() {
+                if graph.vertices[v].is_deleted {
+                    continue;
+                }
+                result.vertex_labels[v] = Some(dist.map_or("∞".to_string(), format_distance));
+                if dist.is_some() {
+                    result.vertex_colors[v] = Some(VISITED_COLOR);
+                }
+            }
+            for edge in paths.parent_edge.iter().flatten() {
+                result.edge_colors[*edge] = Some(VISITED_COLOR);
+            }
+            result.vertex_colors[source] = Some(SOURCE_COLOR);
+            let reachable = paths.dist.iter().flatten().count();
+            result.summary = format!("{reachable} vertices reachable");
+        }
+        AlgorithmKind::ConnectedComponents | AlgorithmKind::StronglyConnectedComponents => {
+            let component = if kind == AlgorithmKind::ConnectedComponents {
+                algorithms::connected_components(graph)
+            } else {
+                algorithms::strongly_connected_components(graph)
+            };
+            paint_groups(&mut result, graph, &component);
+            let count = component.iter().flatten().max().map_or(0, |max| max + 1);
+            result.summary = format!("{count} components");
+        }
+        AlgorithmKind::TopologicalSort => match algorithms::topological_sort(graph) {
+            Some(order) => {
+                for (position, &v) in order.iter().enumerate() {
+                    result.vertex_labels[v] = Some(position.to_string());
+                }
+                result.summary = format!("Order: {}", order.iter().map(|&v| display(v)).join(" "));
+            }
+            None => {
+                if let Some(cycle) = algorithms::find_cycle(graph) {
+                    paint_cycle(&mut result, &cycle);
+                }
+                result.summary = "Not a DAG (cycle highlighted)".to_string();
+            }
+        },
+        AlgorithmKind::Bipartite => match algorithms::bipartition(graph) {
+            Ok(side) => {
+                for (v, side) in side.iter().enumerate() {
+                    result.vertex_colors[v] = side.map(|side| PALETTE[side as usize]);
+                }
+                result.summary = "Bipartite".to_string();
+            }
+            Err(edge) => {
+                let edge_data = &graph.edges[edge];
+                result.edge_colors[edge] = Some(PROBLEM_COLOR);
+                result.vertex_colors[edge_data.from] = Some(PROBLEM_COLOR);
+                result.vertex_colors[edge_data.to] = Some(PROBLEM_COLOR);
+                result.summary = format!(
+                    "Not bipartite: conflict on {} - {}",
+                    display(edge_data.from),
+                    display(edge_data.to)
+                );
+            }
+        },
+        AlgorithmKind::Cycle => match algorithms::find_cycle(graph) {
+            Some(cycle) => {
+                paint_cycle(&mut result, &cycle);
+                result.summary = format!(
+                    "Cycle: {}",
+                    cycle.vertices.iter().map(|&v| display(v)).join(" ")
+                );
+            }
+            None => result.summary = "No cycle".to_string(),
+        },
+    }
+
+    Ok(result)
+}
+
+/// 成分ごとに頂点と成分内の辺を塗り分ける
+fn paint_groups(result: &mut AlgorithmResult, graph: &Graph, group: &[Option<usize>]) {
+    for (v, group) in group.iter().enumerate() {
+        result.vertex_colors[v] = group.map(|group| PALETTE[group % PALETTE.len()]);
+    }
+    for (index, edge) in graph.edges.iter().enumerate() {
+        let (Some(from), Some(to)) = (group.get(edge.from), group.get(edge.to)) else {
+            continue;
+        };
+        if from.is_some() && from == to {
+            result.edge_colors[index] = result.vertex_colors[edge.from];
+        }
+    }
+}
+
+fn paint_cycle(result: &mut AlgorithmResult, cycle: &algorithms::Cycle) {
+    for &v in &cycle.vertices {
+        result.vertex_colors[v] = Some(PROBLEM_COLOR);
+    }
+    for &edge in &cycle.edges {
+        result.edge_colors[edge] = Some(PROBLEM_COLOR);
+    }
+}
+
+/// 距離を表示用の文字列にする（整数の場合は小数点以下を省く）
+fn format_distance(dist: f64) -> String {
+    if dist.fract() == 0.0 && dist.abs() < 1e15 {
+        format!("{}", dist as i64)
+    } else {
+        format!("{dist}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{player::TracePlayer, run_algorithm, AlgorithmKind};
+    use crate::{
+        components::Colors,
+        graph::{EdgeDirection, EdgeWeight, Graph},
+        view_state::GraphViewState,
+    };
+
+    #[test]
+    fn clearing_result_keeps_user_colors() {
+        let graph = Graph::default();
+        let mut view = GraphViewState::new_for_graph(&graph);
+        view.vertices[1].color = Colors::Green;
+
+        let result = run_algorithm(AlgorithmKind::Bfs, &graph, 0, true).unwrap();
+        result.paint(&mut view);
+        let snapshot = view.snapshot(&graph);
+        assert_eq!(snapshot.vertices[1].color, Colors::Cyan);
+        assert_eq!(snapshot.vertices[1].label.as_deref(), Some("1"));
+
+        view.clear_highlight();
+        let snapshot = view.snapshot(&graph);
+        assert_eq!(snapshot.vertices[1].color, Colors::Green);
+        assert_eq!(snapshot.vertices[1].label, None);
+    }
+
+    #[test]
+    fn directed_only_algorithms_reject_undirected_graphs() {
+        let graph = Graph::default();
+        assert!(run_algorithm(AlgorithmKind::TopologicalSort, &graph, 0, true).is_err());
+    }
+
+    #[test]
+    fn directed_only_algorithms_accept_directed_edges_in_undirected_graphs() {
+        let mut graph = Graph::default();
+        assert!(!graph.is_directed);
+        graph.edges[0].direction = EdgeDirection::Directed;
+        assert!(graph.has_directed_edges());
+        assert!(run_algorithm(AlgorithmKind::TopologicalSort, &graph, 0, true).is_ok());
+        assert!(run_algorithm(AlgorithmKind::StronglyConnectedComponents, &graph, 0, true).is_ok());
+    }
+
+    #[test]
+    fn negative_weights_are_reported_in_the_users_indexing() {
+        let mut graph = Graph {
+            is_weighted: true,
+            ..Default::default()
+        };
+        graph.edges[0].weight = Some(EdgeWeight::Int(-1));
+        let (from, to) = (graph.edges[0].from, graph.edges[0].to);
+
+        let err = run_algorithm(AlgorithmKind::ShortestPaths, &graph, 0, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Negative weight on edge {} -> {}", from + 1, to + 1)
+        );
+        let err =
+            TracePlayer::record(AlgorithmKind::ShortestPaths, &graph, 0, 1.0, true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Negative weight on edge {from} -> {to}")
+        );
+    }
+}
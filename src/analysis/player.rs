@@ -0,0 +1,315 @@
+//! アルゴリズムの実行過程の再生
+
+use super::{
+    check_non_negative_weights, format_distance, AlgorithmKind, AlgorithmResult, SOURCE_COLOR,
+    VISITED_COLOR,
+};
+use crate::{
+    components::Colors,
+    graph::{algorithms, algorithms::TraceEvent, Graph},
+};
+
+/// キュー・スタックに入っている頂点の色
+const FRONTIER_COLOR: Colors = Colors::Yellow;
+/// 処理中の頂点・調べている辺の色
+const CURRENT_COLOR: Colors = Colors::Orange;
+
+/// 再生速度の範囲（イベント / 秒）
+pub const SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.25..=30.0;
+
+/// 記録したイベント列を 1 つずつ再生するプレイヤー
+#[derive(Debug, Clone)]
+pub struct TracePlayer {
+    kind: AlgorithmKind,
+    source: usize,
+    events: Vec<TraceEvent>,
+    /// 反映済みのイベント数
+    position: usize,
+    pub is_playing: bool,
+    /// 再生速度（イベント / 秒）
+    pub speed: f32,
+    /// 次のイベントまでの経過時間
+    elapsed: f32,
+    /// 記録時のグラフの各頂点が削除済みかどうかと，各辺の両端・削除済みかどうか
+    topology: (Vec<bool>, Vec<(usize, usize, bool)>),
+    /// 最後に表示へ反映した再生位置
+    painted_position: Option<usize>,
+}
+
+impl TracePlayer {
+    /// アルゴリズムを実行してイベント列を記録する．再生に対応していない場合は`None`．
+    pub fn record(
+        kind: AlgorithmKind,
+        graph: &Graph,
+        source: usize,
+        speed: f32,
+        zero_indexed: bool,
+    ) -> anyhow::Result<Option<Self>> {
+        let mut events = vec![];
+        match kind {
+            AlgorithmKind::Bfs => {
+                algorithms::bfs_traced(graph, source, |event| events.push(event));
+            }
+            AlgorithmKind::Dfs => {
+                algorithms::dfs_traced(graph, source, |event| events.push(event));
+            }
+            AlgorithmKind::ShortestPaths => {
+                check_non_negative_weights(graph, zero_indexed)?;
+                algorithms::dijkstra_traced(graph, source, |event| events.push(event))?;
+            }
+            _ => return Ok(None),
+        }
+
+        Ok(Some(Self {
+            kind,
+            source,
+            events,
+            position: 0,
+            is_playing: true,
+            speed,
+            elapsed: 0.0,
+            topology: (
+                graph.vertices.iter().map(|v| v.is_deleted).collect(),
+                graph
+                    .edges
+                    .iter()
+                    .map(|e| (e.from, e.to, e.is_deleted))
+                    .collect(),
+            ),
+            painted_position: None,
+        }))
+    }
+
+    pub fn kind(&self) -> AlgorithmKind {
+        self.kind
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.events.len()
+    }
+
+    /// 記録後にグラフの頂点・辺が増減したり，辺がつなぎ替えられたりしていないか
+    pub fn matches(&self, graph: &Graph) -> bool {
+        let (vertices, edges) = &self.topology;
+        vertices
+            .iter()
+            .copied()
+            .eq(graph.vertices.iter().map(|v| v.is_deleted))
+            && edges
+                .iter()
+                .copied()
+                .eq(graph.edges.iter().map(|e| (e.from, e.to, e.is_deleted)))
+    }
+
+    /// 経過時間に応じて再生位置を進める．位置が変わった場合`true`を返す．
+    pub fn advance(&mut self, dt: f32) -> bool {
+        if !self.is_playing {
+            return false;
+        }
+        if self.is_finished() {
+            self.is_playing = false;
+            return false;
+        }
+
+        self.elapsed += dt * self.speed;
+        let steps = self.elapsed.floor() as usize;
+        if steps == 0 {
+            return false;
+        }
+        self.elapsed -= steps as f32;
+        self.position = (self.position + steps).min(self.events.len());
+        true
+    }
+
+    pub fn toggle_playing(&mut self) {
+        if self.is_finished() {
+            self.position = 0;
+        }
+        self.is_playing ^= true;
+        self.elapsed = 0.0;
+    }
+
+    pub fn step_forward(&mut self) {
+        self.is_playing = false;
+        self.position = (self.position + 1).min(self.events.len());
+    }
+
+    pub fn step_back(&mut self) {
+        self.is_playing = false;
+        self.position = self.position.saturating_sub(1);
+    }
+
+    pub fn rewind(&mut self) {
+        self.is_playing = false;
+        self.position = 0;
+    }
+
+    /// 現在のイベントの説明
+    pub fn describe_current(&self, graph: &Graph, zero_indexed: bool) -> String {
+        let display = |v: usize| if zero_indexed { v } else { v + 1 };
+        let Some(event) = self.position.checked_sub(1).map(|i| self.events[i]) else {
+            return "Ready".to_string();
+        };
+        match event {
+            TraceEvent::Push(v) => format!("push {}", display(v)),
+            TraceEvent::Pop(v) => format!("pop {}", display(v)),
+            TraceEvent::Visit(v) => format!("visit {}", display(v)),
+            TraceEvent::Examine(edge) => match graph.edges.get(edge) {
+                Some(edge) => format!("examine {} - {}", display(edge.from), display(edge.to)),
+                None => "examine".to_string(),
+            },
+            TraceEvent::Relax { to, dist, .. } => {
+                format!("dist[{}] = {}", display(to), format_distance(dist))
+            }
+        }
+    }
+
+    /// 前回の反映から再生位置が変わった場合，新しい表示を返す
+    pub fn take_frame(&mut self, graph: &Graph) -> Option<AlgorithmResult> {
+        if self.painted_position == Some(self.position) {
+            return None;
+        }
+        self.painted_position = Some(self.position);
+        Some(self.frame(graph))
+    }
+
+    /// 現在の再生位置までのイベントを反映した表示を作る
+    pub fn frame(&self, graph: &Graph) -> AlgorithmResult {
+        let mut result = AlgorithmResult::new(graph);
+        let mut visited = vec![false; graph.vertices.len()];
+        let mut tree_edge = vec![None; graph.vertices.len()];
+        let mut current = None;
+        let mut examined = None;
+
+        for event in &self.events[..self.position] {
+            match *event {
+                TraceEvent::Push(v) => {
+                    if let Some(color) = result.vertex_colors.get_mut(v) {
+                        *color = Some(FRONTIER_COLOR);
+                    }
+                }
+                TraceEvent::Pop(v) => {
+                    current = Some(v);
+                }
+                TraceEvent::Visit(v) => {
+                    if let Some(flag) = visited.get_mut(v) {
+                        *flag = true;
+                    }
+                    current = Some(v);
+                }
+                TraceEvent::Examine(edge) => {
+                    examined = Some(edge);
+                }
+                TraceEvent::Relax { edge, to, dist } => {
+                    if let Some(label) = result.vertex_labels.get_mut(to) {
+                        *label = Some(format_distance(dist));
+                    }
+                    if let Some(tree_edge) = tree_edge.get_mut(to) {
+                        *tree_edge = Some(edge);
+                    }
+                }
+            }
+        }
+
+        for (v, &is_visited) in visited.iter().enumerate() {
+            if is_visited {
+                result.vertex_colors[v] = Some(VISITED_COLOR);
+            }
+        }
+        for edge in tree_edge.iter().flatten() {
+            if let Some(color) = result.edge_colors.get_mut(*edge) {
+                *color = Some(VISITED_COLOR);
+            }
+        }
+        if self.position == 0 {
+            if let Some(color) = result.vertex_colors.get_mut(self.source) {
+                *color = Some(SOURCE_COLOR);
+            }
+        }
+        if self.is_finished() {
+            current = None;
+            examined = None;
+        }
+        if let Some(label) = result.vertex_labels.get_mut(self.source) {
+            *label = Some("0".to_string());
+        }
+        if let Some(color) = current.and_then(|v| result.vertex_colors.get_mut(v)) {
+            *color = Some(CURRENT_COLOR);
+        }
+        if let Some(color) = examined.and_then(|edge| result.edge_colors.get_mut(edge)) {
+            *color = Some(CURRENT_COLOR);
+        }
+
+        result.summary = format!("{} / {}", self.position, self.events.len());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TracePlayer;
+    use crate::{analysis::AlgorithmKind, components::Colors, graph::Graph};
+
+    #[test]
+    fn stepping_back_and_forth_replays_the_same_frame() {
+        let graph = Graph::default();
+        let mut player = TracePlayer::record(AlgorithmKind::Bfs, &graph, 0, 1.0, false)
+            .unwrap()
+            .unwrap();
+        player.rewind();
+
+        player.step_forward();
+        let first = player.frame(&graph);
+        player.step_forward();
+        player.step_back();
+        assert_eq!(player.frame(&graph).vertex_colors, first.vertex_colors);
+    }
+
+    #[test]
+    fn advance_respects_speed_and_finishes() {
+        let graph = Graph::default();
+        let mut player = TracePlayer::record(AlgorithmKind::Dfs, &graph, 0, 2.0, false)
+            .unwrap()
+            .unwrap();
+
+        assert!(!player.advance(0.25));
+        assert!(player.advance(0.25));
+        assert_eq!(player.position(), 1);
+
+        while player.advance(1.0) {}
+        assert!(player.is_finished());
+        assert!(!player.is_playing);
+
+        let frame = player.frame(&graph);
+        assert_eq!(frame.vertex_colors[1], Some(Colors::Cyan));
+        assert_eq!(frame.vertex_labels[1].as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn rewired_or_deleted_edges_invalidate_the_trace() {
+        let mut graph = Graph::default();
+        let player = TracePlayer::record(AlgorithmKind::Bfs, &graph, 0, 1.0, false)
+            .unwrap()
+            .unwrap();
+
+        graph.vertices[0].position += egui::vec2(10.0, 0.0);
+        assert!(player.matches(&graph));
+
+        let to = graph.edges[0].to;
+        graph.edges[0].to = graph.edges[0].from;
+        assert!(!player.matches(&graph));
+        graph.edges[0].to = to;
+        assert!(player.matches(&graph));
+
+        graph.edges[0].is_deleted = true;
+        assert!(!player.matches(&graph));
+    }
+}
@@ -1,16 +1,28 @@
+use std::rc::Rc;
+
 use eframe::egui;
 use serde::{Deserialize, Serialize};
 
+use crate::analysis::{player::TracePlayer, run_algorithm, AlgorithmKind};
 use crate::components::{
     draw_central_panel, draw_clear_all_modal, draw_entity_editor, draw_error_modal, draw_footer,
     draw_inspector_panel, draw_tool_bar, draw_top_panel, Colors, CursorHoverState, InspectorTab,
 };
-use crate::config::{AppConfig, SimulatorKind};
+use crate::config::{
+    AppConfig, SimulateConfig, SimulatorKind, Theme, ThemeKind, VisualizerConfig, VisualizerKind,
+};
 use crate::export::{ExportFormat, ExportService};
-use crate::graph::{simulation_methods, BaseGraph, Simulator};
+use crate::graph::{
+    simulation_methods::{self, MAX_QUADRATIC_MODEL_VERTICES},
+    visualize_methods, BaseGraph, EdgeListOptions, Graph, GraphTextFormat, Simulator, Visualizer,
+};
+use crate::history::EditHistory;
 use crate::math::affine::Affine2D;
 use crate::mode::EditMode;
-use crate::project_io::{export_graph_to_file, import_graph_from_file, ImportedGraph, SaveOptions};
+use crate::project_io::{
+    export_graph_to_file, export_subgraph_to_file, export_subgraph_to_json, import_graph_from_file,
+    GraphFile, ImportedGraph, SaveOptions, GRAPH_FILE_FORMAT,
+};
 use crate::state::{AppState, IoFormat, UiState};
 use crate::update::request_repaint;
 use crate::view_state::GraphViewState;
@@ -23,8 +35,12 @@ pub struct GraphEditorApp {
 }
 
 const UI_STATE_STORAGE_KEY: &str = "graph-editor:ui-state";
+const UI_STATE_VERSION: u32 = 4;
 const GRAPH_STATE_STORAGE_KEY: &str = "graph-editor:graph-state";
 const GRAPH_LAYOUT_SETTLE_STEPS: usize = 120;
+/// 配置直後にまとめて進めるシミュレーションの「頂点数 × ステップ数」の上限．
+/// 大きなグラフでは UI を止めないようにステップ数を減らす
+const GRAPH_LAYOUT_SETTLE_BUDGET: usize = GRAPH_LAYOUT_SETTLE_STEPS * MAX_QUADRATIC_MODEL_VERTICES;
 const AUTO_FIT_DIAMETER_THRESHOLD: usize = 12;
 const EDGE_LENGTH_SHRINK_DIAMETER_THRESHOLD: usize = 10;
 
@@ -36,40 +52,90 @@ struct StoredUiState {
     show_number: bool,
     is_animated: bool,
     is_directed: bool,
+    is_weighted: bool,
+    allows_multi_edges: bool,
     export_format: String,
     ui_font_size: f32,
     vertex_font_size: f32,
     vertex_radius: f32,
     vertex_stroke: f32,
     edge_stroke: f32,
-    edge_length: f32,
+    /// バージョン3までの辺の長さ．バージョン4からは`simulator_config.l`に含める
+    #[serde(skip_serializing, deserialize_with = "deserialize_some")]
+    edge_length: Option<f32>,
     edge_bezier_distance: f32,
+    self_loop_size: f32,
+    /// ユーザーがパレットに追加した色（`#rrggbb`）
+    custom_palette: Vec<String>,
+    theme_kind: ThemeKind,
+    custom_theme: Theme,
+    /// 画像出力に使う配色．`None`は編集中の配色と同じ
+    export_theme_kind: Option<ThemeKind>,
     scale_min: f32,
     scale_max: f32,
     scale_delta: f32,
+    density_threshold: f32,
+    visualizer_kind: VisualizerKind,
+    visualizer_config: VisualizerConfig,
+    simulator_kind: SimulatorKind,
+    simulator_config: SimulateConfig,
 }
 
 impl Default for StoredUiState {
     fn default() -> Self {
         let defaults = AppConfig::default();
         Self {
-            version: 3,
+            version: UI_STATE_VERSION,
             zero_indexed: false,
             show_number: true,
             is_animated: true,
             is_directed: false,
+            is_weighted: false,
+            allows_multi_edges: false,
             export_format: ExportFormat::Png.extension().to_string(),
             ui_font_size: defaults.ui_font_size,
             vertex_font_size: defaults.vertex_font_size,
             vertex_radius: defaults.vertex_radius,
             vertex_stroke: defaults.vertex_stroke,
             edge_stroke: defaults.edge_stroke,
-            edge_length: defaults.simulator_config.l,
+            edge_length: None,
             edge_bezier_distance: defaults.edge_bezier_distance,
+            self_loop_size: defaults.self_loop_size,
+            custom_palette: vec![],
+            theme_kind: defaults.theme_kind,
+            custom_theme: defaults.custom_theme,
+            export_theme_kind: defaults.export_theme_kind,
             scale_min: defaults.scale_min,
             scale_max: defaults.scale_max,
             scale_delta: defaults.scale_delta,
+            density_threshold: defaults.density_threshold,
+            visualizer_kind: defaults.visualizer_kind,
+            visualizer_config: defaults.visualizer_config,
+            simulator_kind: defaults.simulator_kind,
+            simulator_config: defaults.simulator_config,
+        }
+    }
+}
+
+/// 値があれば`Some`として読み込む．eframe の保存形式（RON）では`Option`に`Some(..)`が必要なため，
+/// `Option`でなかった頃に保存された値を読むのに使う．
+fn deserialize_some<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f32>, D::Error> {
+    f32::deserialize(deserializer).map(Some)
+}
+
+impl StoredUiState {
+    /// 古いバージョンで保存された状態を現在の形式に直す．
+    /// 足りない項目は`#[serde(default)]`で既定値になるため，形の変わった項目だけを移す．
+    fn migrate(mut self) -> Self {
+        if self.version < 4 {
+            if let Some(edge_length) = self.edge_length.take() {
+                self.simulator_config.l = edge_length;
+            }
         }
+        self.version = UI_STATE_VERSION;
+        self
     }
 }
 
@@ -85,20 +151,36 @@ impl GraphEditorApp {
             .storage
             .and_then(|storage| eframe::get_value(storage, UI_STATE_STORAGE_KEY))
             .unwrap_or_default();
+        let state = state.migrate();
         app.state.zero_indexed = state.zero_indexed;
         app.state.show_number = state.show_number;
         app.state.is_animated = state.is_animated;
         app.state.graph.is_directed = state.is_directed;
+        app.state.graph.is_weighted = state.is_weighted;
+        app.state.graph.allows_multi_edges = state.allows_multi_edges;
         app.config.ui_font_size = state.ui_font_size;
         app.config.vertex_font_size = state.vertex_font_size;
         app.config.vertex_radius = state.vertex_radius;
         app.config.vertex_stroke = state.vertex_stroke;
         app.config.edge_stroke = state.edge_stroke;
-        app.config.simulator_config.l = state.edge_length;
         app.config.edge_bezier_distance = state.edge_bezier_distance;
+        app.config.self_loop_size = state.self_loop_size;
+        app.config.custom_palette = state
+            .custom_palette
+            .iter()
+            .filter_map(|hex| egui::Color32::from_hex(hex).ok())
+            .collect();
+        app.config.theme_kind = state.theme_kind;
+        app.config.custom_theme = state.custom_theme;
+        app.config.export_theme_kind = state.export_theme_kind;
         app.config.scale_min = state.scale_min;
         app.config.scale_max = state.scale_max;
         app.config.scale_delta = state.scale_delta;
+        app.config.density_threshold = state.density_threshold;
+        app.config.visualizer_kind = state.visualizer_kind;
+        app.config.visualizer_config = state.visualizer_config;
+        app.config.simulator_kind = state.simulator_kind;
+        app.config.simulator_config = state.simulator_config;
         app.state.simulation_edge_length = app.config.simulator_config.l;
         let format = match state.export_format.as_str() {
             "svg" => ExportFormat::Svg,
@@ -128,6 +210,7 @@ impl GraphEditorApp {
         }
         for edge in &mut self.state.graph_view.edges {
             edge.is_pressed = false;
+            edge.is_selected = false;
         }
     }
 
@@ -137,6 +220,270 @@ impl GraphEditorApp {
         }
     }
 
+    /// 画面上の矩形に含まれる頂点と，両端が含まれる辺を選択する
+    pub fn select_in_rect(&mut self, rect: egui::Rect, additive: bool) {
+        if !additive {
+            self.state.graph_view.clear_selection();
+        }
+
+        let graph = &self.state.graph;
+        let inside: Vec<bool> = graph
+            .vertices
+            .iter()
+            .map(|vertex| !vertex.is_deleted && rect.contains(vertex.get_position()))
+            .collect();
+
+        for (view, &is_inside) in self.state.graph_view.vertices.iter_mut().zip(&inside) {
+            view.is_selected |= is_inside;
+        }
+        for (view, edge) in self.state.graph_view.edges.iter_mut().zip(&graph.edges) {
+            let is_inside = inside.get(edge.from).copied().unwrap_or(false)
+                && inside.get(edge.to).copied().unwrap_or(false);
+            view.is_selected |= !edge.is_deleted && is_inside;
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        for view in &mut self.state.graph_view.vertices {
+            view.is_selected = true;
+        }
+        for view in &mut self.state.graph_view.edges {
+            view.is_selected = true;
+        }
+    }
+
+    /// 選択中の頂点に隣接する頂点と，その間の辺を選択に加える
+    pub fn select_neighbors(&mut self) {
+        let selected: Vec<bool> = self
+            .state
+            .graph_view
+            .vertices
+            .iter()
+            .map(|view| view.is_selected)
+            .collect();
+        let is_selected = |id: usize| selected.get(id).copied().unwrap_or(false);
+
+        for (index, edge) in self.state.graph.edges.iter().enumerate() {
+            if edge.is_deleted || !(is_selected(edge.from) || is_selected(edge.to)) {
+                continue;
+            }
+            for id in [edge.from, edge.to] {
+                if let Some(view) = self.state.graph_view.vertices.get_mut(id) {
+                    view.is_selected = true;
+                }
+            }
+            if let Some(view) = self.state.graph_view.edges.get_mut(index) {
+                view.is_selected = true;
+            }
+        }
+    }
+
+    /// 選択中の頂点の固定を切り替える．すべて固定済みなら解除し，そうでなければすべて固定する
+    pub fn toggle_pin_selection(&mut self) {
+        let selected: Vec<usize> = self
+            .state
+            .graph_view
+            .vertices
+            .iter()
+            .enumerate()
+            .filter(|(index, view)| {
+                view.is_selected
+                    && self
+                        .state
+                        .graph
+                        .vertices
+                        .get(*index)
+                        .is_some_and(|v| !v.is_deleted)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if selected.is_empty() {
+            return;
+        }
+
+        let pin = !selected
+            .iter()
+            .all(|&index| self.state.graph.vertices[index].is_pinned);
+        self.record_history();
+        for index in selected {
+            let vertex = &mut self.state.graph.vertices[index];
+            vertex.is_pinned = pin;
+            vertex.velocity = egui::Vec2::ZERO;
+        }
+    }
+
+    /// 選択中の頂点と辺を削除する
+    pub fn delete_selection(&mut self) {
+        if !self.state.graph_view.has_selection() {
+            return;
+        }
+
+        self.record_history();
+        let AppState {
+            graph, graph_view, ..
+        } = &mut self.state;
+        for (vertex, view) in graph.vertices.iter_mut().zip(&mut graph_view.vertices) {
+            if view.is_selected {
+                vertex.is_deleted = true;
+                view.is_selected = false;
+            }
+        }
+        for (edge, view) in graph.edges.iter_mut().zip(&mut graph_view.edges) {
+            if view.is_selected {
+                edge.is_deleted = true;
+                view.is_selected = false;
+            }
+        }
+        self.close_entity_editor();
+    }
+
+    /// 選択中のアルゴリズムを実行し，結果をグラフに重ねて表示する
+    pub fn run_selected_algorithm(&mut self) {
+        match run_algorithm(
+            self.ui.algorithm_kind,
+            &self.state.graph,
+            self.ui.algorithm_source,
+            self.state.zero_indexed,
+        ) {
+            Ok(result) => {
+                self.ui.algorithm_player = None;
+                result.paint(&mut self.state.graph_view);
+                self.ui.algorithm_summary = Some(result.summary);
+            }
+            Err(err) => {
+                self.clear_algorithm_result();
+                self.ui.error_message = Some(err.to_string());
+            }
+        }
+    }
+
+    /// アルゴリズムの結果の表示を消す
+    pub fn clear_algorithm_result(&mut self) {
+        self.state.graph_view.clear_highlight();
+        self.ui.algorithm_summary = None;
+        self.ui.algorithm_player = None;
+    }
+
+    /// 選択中のアルゴリズムの実行過程を記録し，再生を始める
+    pub fn start_algorithm_animation(&mut self) {
+        match TracePlayer::record(
+            self.ui.algorithm_kind,
+            &self.state.graph,
+            self.ui.algorithm_source,
+            self.ui.algorithm_speed,
+            self.state.zero_indexed,
+        ) {
+            Ok(Some(player)) => {
+                self.ui.algorithm_summary = None;
+                self.ui.algorithm_player = Some(player);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                self.clear_algorithm_result();
+                self.ui.error_message = Some(err.to_string());
+            }
+        }
+    }
+
+    /// 再生位置を進め，変化があれば表示に反映する
+    fn update_algorithm_player(&mut self, ctx: &egui::Context) {
+        let Some(player) = &mut self.ui.algorithm_player else {
+            return;
+        };
+        // 頂点や辺が増減したり辺がつなぎ替えられたりした場合は，記録したイベントが使えないため停止する
+        if !player.matches(&self.state.graph) {
+            self.clear_algorithm_result();
+            return;
+        }
+
+        player.advance(ctx.input(|i| i.stable_dt));
+        if let Some(frame) = player.take_frame(&self.state.graph) {
+            frame.paint(&mut self.state.graph_view);
+        }
+    }
+
+    fn selected_vertex_flags(&self) -> Vec<bool> {
+        self.state
+            .graph_view
+            .vertices
+            .iter()
+            .map(|view| view.is_selected)
+            .collect()
+    }
+
+    /// 選択中の頂点が誘導する部分グラフを JSON 形式でクリップボードにコピーする
+    pub fn copy_selection(&mut self, ctx: &egui::Context) {
+        let selected = self.selected_vertex_flags();
+        if !selected.contains(&true) {
+            return;
+        }
+
+        match export_subgraph_to_json(
+            &self.state.graph,
+            &self.state.graph_view,
+            self.state.zero_indexed,
+            &selected,
+        ) {
+            Ok(json) => ctx.copy_text(json),
+            Err(err) => self.ui.error_message = Some(err.to_string()),
+        }
+    }
+
+    /// JSON 形式の部分グラフを，重心が`anchor`（画面座標）に来るように貼り付ける．
+    /// このエディタの形式でないテキストは無視する．
+    pub fn paste_graph_json(&mut self, json: &str, anchor: egui::Pos2) {
+        let Ok(file) = serde_json::from_str::<GraphFile>(json) else {
+            return;
+        };
+        if file.format != GRAPH_FILE_FORMAT {
+            return;
+        }
+        match import_graph_from_file(file) {
+            Ok(imported) => self.insert_subgraph(imported, anchor),
+            Err(err) => self.ui.error_message = Some(format!("Failed to paste graph: {err}")),
+        }
+    }
+
+    /// 選択中の部分グラフを少しずらした位置に複製する
+    pub fn duplicate_selection(&mut self) {
+        let selected = self.selected_vertex_flags();
+        if !selected.contains(&true) {
+            return;
+        }
+
+        let file = export_subgraph_to_file(
+            &self.state.graph,
+            &self.state.graph_view,
+            self.state.zero_indexed,
+            &selected,
+        );
+        match import_graph_from_file(file) {
+            Ok(imported) => {
+                let offset = egui::Vec2::splat(self.config.duplicate_offset);
+                let anchor = centroid(&imported.graph) + offset;
+                self.insert_subgraph(imported, anchor);
+            }
+            Err(err) => self.ui.error_message = Some(err.to_string()),
+        }
+    }
+
+    /// 読み込んだグラフを新しい番号で現在のグラフに追加し，追加した部分を選択する
+    fn insert_subgraph(&mut self, imported: ImportedGraph, anchor: egui::Pos2) {
+        if imported.graph.vertices.is_empty() {
+            return;
+        }
+
+        self.record_history();
+        self.switch_normal_mode();
+
+        let offset = anchor - centroid(&imported.graph);
+        self.state.graph.append(&imported.graph, offset);
+        self.state
+            .graph_view
+            .append(imported.view, self.state.next_z_index);
+        self.state.next_z_index += imported.graph.vertices.len() as u32;
+    }
+
     pub fn switch_normal_mode(&mut self) {
         self.deselect_all_vertices_edges();
         self.state.edit_mode = EditMode::default_normal();
@@ -166,34 +513,79 @@ impl GraphEditorApp {
         self.state.edit_mode = EditMode::default_delete();
     }
 
-    pub fn request_export_image(&mut self, ctx: &egui::Context) {
-        let export_ctx = crate::export::ExportContext {
-            graph: &self.state.graph,
-            view: &self.state.graph_view,
-            config: &self.config,
-            show_number: self.state.show_number,
-            zero_indexed: self.state.zero_indexed,
-        };
-        if let Some(err) = self.export.request_export(ctx, &export_ctx) {
-            self.ui.error_message = Some(err);
+    /// 変更を加える直前の状態を履歴に記録する
+    pub fn record_history(&mut self) {
+        self.state
+            .history
+            .record(&self.state.graph, &self.state.graph_view);
+    }
+
+    pub fn undo(&mut self) {
+        if self
+            .state
+            .history
+            .undo(&mut self.state.graph, &mut self.state.graph_view)
+        {
+            self.after_history_restore();
         }
     }
 
-    pub fn handle_export_events(&mut self, ctx: &egui::Context) {
+    pub fn redo(&mut self) {
+        if self
+            .state
+            .history
+            .redo(&mut self.state.graph, &mut self.state.graph_view)
+        {
+            self.after_history_restore();
+        }
+    }
+
+    fn after_history_restore(&mut self) {
+        self.ui.algorithm_player = None;
+        self.deselect_all_vertices_edges();
+        self.close_entity_editor();
+        if self.state.edit_mode.is_add_edge() {
+            self.state.edit_mode = EditMode::default_add_edge();
+        }
+        self.state.next_z_index = self
+            .state
+            .graph_view
+            .vertices
+            .iter()
+            .map(|vertex| vertex.z_index + 1)
+            .max()
+            .unwrap_or(0);
+    }
+
+    pub fn request_export_image(&mut self) {
         let export_ctx = crate::export::ExportContext {
             graph: &self.state.graph,
             view: &self.state.graph_view,
             config: &self.config,
+            theme: self.config.export_theme(),
             show_number: self.state.show_number,
             zero_indexed: self.state.zero_indexed,
         };
-        if let Some(err) = self.export.handle_events(ctx, &export_ctx) {
+        if let Some(err) = self.export.request_export(&export_ctx) {
             self.ui.error_message = Some(err);
         }
     }
 
     pub fn sync_input_text_from_graph(&mut self) {
-        let encoded = self.state.graph.encode(self.state.zero_indexed);
+        let encoded = match self
+            .state
+            .graph
+            .encode_as(self.ui.text_format, self.state.zero_indexed)
+        {
+            Ok(encoded) => {
+                self.ui.input_encode_error = None;
+                encoded
+            }
+            Err(err) => {
+                self.ui.input_encode_error = Some(err.to_string());
+                String::new()
+            }
+        };
         self.ui.input_text = encoded.clone();
         self.ui.input_synced_text = encoded;
         self.ui.input_is_dirty = false;
@@ -204,6 +596,7 @@ impl GraphEditorApp {
             include_vertex_position: self.ui.save_vertex_position,
             include_vertex_style: self.ui.save_vertex_style,
             include_edge_style: self.ui.save_edge_style,
+            layout_seed: Some(self.config.visualizer_config.seed),
         }
     }
 
@@ -225,9 +618,21 @@ impl GraphEditorApp {
         }
     }
 
+    pub fn sync_dot_text_from_graph(&mut self) {
+        let dot = crate::project_io::dot::export_graph_to_dot(
+            &self.state.graph,
+            &self.state.graph_view,
+            self.state.zero_indexed,
+        );
+        self.ui.dot_text = dot.clone();
+        self.ui.dot_synced_text = dot;
+        self.ui.dot_is_dirty = false;
+    }
+
     pub fn sync_io_texts_from_graph(&mut self) {
         self.sync_input_text_from_graph();
         self.sync_json_text_from_graph();
+        self.sync_dot_text_from_graph();
     }
 
     fn restore_imported_graph(&mut self, imported: ImportedGraph) {
@@ -236,6 +641,9 @@ impl GraphEditorApp {
         self.state.zero_indexed = imported.zero_indexed;
         self.state.next_z_index = self.state.graph.vertices.len() as u32;
         self.state.selected_color = Colors::Default;
+        if let Some(seed) = imported.layout_seed {
+            self.config.visualizer_config.seed = seed;
+        }
         self.switch_normal_mode();
         self.state.simulation_edge_length = self.effective_layout_edge_length();
     }
@@ -270,12 +678,23 @@ impl GraphEditorApp {
     }
 
     fn simulator_with_edge_length(&self, edge_length: f32) -> Box<dyn Simulator> {
+        let mut config = self.config.simulator_config;
+        config.l = edge_length;
         match self.config.simulator_kind {
             SimulatorKind::ForceDirected => {
-                let mut config = self.config.simulator_config;
-                config.l = edge_length;
                 Box::new(simulation_methods::ForceDirectedModel { config })
             }
+            SimulatorKind::BarnesHut => Box::new(simulation_methods::BarnesHutModel { config }),
+            SimulatorKind::KamadaKawai => Box::new(simulation_methods::KamadaKawaiModel {
+                config,
+                distances: Rc::clone(&self.state.graph_distances),
+            }),
+            SimulatorKind::StressMajorization => {
+                Box::new(simulation_methods::StressMajorizationModel {
+                    config,
+                    distances: Rc::clone(&self.state.graph_distances),
+                })
+            }
         }
     }
 
@@ -286,7 +705,8 @@ impl GraphEditorApp {
     fn settle_graph_layout(&mut self, edge_length: f32) {
         self.state.simulation_edge_length = edge_length;
         let simulator = self.simulator_with_edge_length(edge_length);
-        for _ in 0..GRAPH_LAYOUT_SETTLE_STEPS {
+        let n = self.state.graph.vertices.len().max(1);
+        for _ in 0..GRAPH_LAYOUT_SETTLE_STEPS.min(GRAPH_LAYOUT_SETTLE_BUDGET / n) {
             simulator.simulate_step(&mut self.state.graph);
         }
 
@@ -338,27 +758,67 @@ impl GraphEditorApp {
         ]);
     }
 
+    /// 初期配置を求めた直後に，アニメーションを続けるか，力学モデルで配置を整える．
+    /// 木のように形を保つ配置では，力学モデルを止めたままにする．
+    fn finish_generated_layout(&mut self, was_animated: bool, canvas_rect: egui::Rect) {
+        if self.config.visualizer_kind.is_static() {
+            self.state.simulation_edge_length = self.effective_layout_edge_length();
+            self.auto_fit_graph_to_canvas(canvas_rect);
+            self.state.is_animated = false;
+        } else if was_animated {
+            self.state.simulation_edge_length = self.effective_layout_edge_length();
+            self.state.is_animated = true;
+        } else {
+            let edge_length = self.effective_layout_edge_length();
+            self.settle_graph_layout(edge_length);
+            self.auto_fit_graph_to_canvas(canvas_rect);
+            self.state.is_animated = false;
+        }
+    }
+
+    /// 形を保つ配置で現在のグラフを配置し直し，アニメーションを止める
+    fn arrange_statically(&mut self, ctx: &egui::Context, visualizer: &dyn Visualizer) {
+        let canvas_rect = self.ui.canvas_rect.unwrap_or_else(|| ctx.available_rect());
+        self.record_history();
+        self.state.graph.relayout(
+            visualizer,
+            f32::INFINITY,
+            canvas_rect,
+            &mut self.config.visualizer_config.rng(),
+        );
+        self.state.simulation_edge_length = self.effective_layout_edge_length();
+        self.auto_fit_graph_to_canvas(canvas_rect);
+        self.state.is_animated = false;
+        self.sync_io_texts_from_graph();
+    }
+
+    /// 現在のグラフを `root` を根とする木（森）として配置し直す
+    pub fn arrange_as_tree(&mut self, ctx: &egui::Context, root: usize) {
+        self.config.visualizer_config.tree_root = root;
+        self.arrange_statically(ctx, &visualize_methods::Tree { root });
+    }
+
+    /// 現在のグラフを，辺が上から下を向くように層状に配置し直す
+    pub fn arrange_as_dag(&mut self, ctx: &egui::Context) {
+        let sweeps = self.config.visualizer_config.sugiyama_sweeps;
+        self.arrange_statically(ctx, &visualize_methods::Sugiyama { sweeps });
+    }
+
     pub fn rebuild_from_base_graph(&mut self, ctx: &egui::Context, base_graph: BaseGraph) {
         let canvas_rect = self.ui.canvas_rect.unwrap_or_else(|| ctx.available_rect());
         let was_animated = self.state.is_animated;
         let visualizer = self.config.visualizer();
+        self.record_history();
         let new_graph_result = self.state.graph.rebuild_from_basegraph(
             visualizer.as_ref(),
-            self.config.density_threshold,
+            self.config.layout_density_threshold(),
             base_graph,
             canvas_rect,
+            &mut self.config.visualizer_config.rng(),
         );
         match new_graph_result {
             Ok(_) => {
-                if was_animated {
-                    self.state.simulation_edge_length = self.effective_layout_edge_length();
-                    self.state.is_animated = true;
-                } else {
-                    let edge_length = self.effective_layout_edge_length();
-                    self.settle_graph_layout(edge_length);
-                    self.auto_fit_graph_to_canvas(canvas_rect);
-                    self.state.is_animated = false;
-                }
+                self.finish_generated_layout(was_animated, canvas_rect);
                 self.state.graph_view.reset_for_graph(&self.state.graph);
                 self.state.next_z_index = self.state.graph.vertices.len() as u32;
                 self.sync_io_texts_from_graph();
@@ -373,23 +833,19 @@ impl GraphEditorApp {
         let canvas_rect = self.ui.canvas_rect.unwrap_or_else(|| ctx.available_rect());
         let was_animated = self.state.is_animated;
 
+        self.record_history();
         self.state.graph = imported.graph;
         self.state.graph_view = imported.view;
         self.state.zero_indexed = imported.zero_indexed;
         self.state.next_z_index = self.state.graph.vertices.len() as u32;
         self.state.selected_color = Colors::Default;
+        if let Some(seed) = imported.layout_seed {
+            self.config.visualizer_config.seed = seed;
+        }
         self.switch_normal_mode();
 
         if imported.used_generated_positions {
-            if was_animated {
-                self.state.simulation_edge_length = self.effective_layout_edge_length();
-                self.state.is_animated = true;
-            } else {
-                let edge_length = self.effective_layout_edge_length();
-                self.settle_graph_layout(edge_length);
-                self.auto_fit_graph_to_canvas(canvas_rect);
-                self.state.is_animated = false;
-            }
+            self.finish_generated_layout(was_animated, canvas_rect);
         } else {
             self.state.simulation_edge_length = self.effective_layout_edge_length();
             self.state.is_animated = was_animated;
@@ -399,6 +855,15 @@ impl GraphEditorApp {
     }
 }
 
+/// 頂点の位置（画面座標）の重心
+fn centroid(graph: &Graph) -> egui::Pos2 {
+    let n = graph.vertices.len().max(1) as f32;
+    let sum = graph.vertices.iter().fold(egui::Vec2::ZERO, |sum, vertex| {
+        sum + vertex.get_position().to_vec2()
+    });
+    (sum / n).to_pos2()
+}
+
 impl Default for GraphEditorApp {
     fn default() -> Self {
         let config = AppConfig::default();
@@ -407,8 +872,10 @@ impl Default for GraphEditorApp {
             state: AppState {
                 graph_view: GraphViewState::new_for_graph(&graph),
                 graph,
+                history: EditHistory::new(config.history_limit),
                 is_animated: true,
                 simulation_edge_length: config.simulator_config.l,
+                graph_distances: Default::default(),
                 last_mouse_pos: None,
                 next_z_index: 2,
                 edit_mode: EditMode::default_normal(),
@@ -422,11 +889,17 @@ impl Default for GraphEditorApp {
                 input_text: String::new(),
                 input_synced_text: String::new(),
                 io_format: IoFormat::default(),
+                text_format: GraphTextFormat::default(),
+                edge_list_options: EdgeListOptions::default(),
+                input_encode_error: None,
                 json_text: String::new(),
                 json_synced_text: String::new(),
+                dot_text: String::new(),
+                dot_synced_text: String::new(),
                 input_has_focus: false,
                 input_is_dirty: false,
                 json_is_dirty: false,
+                dot_is_dirty: false,
                 save_vertex_position: true,
                 save_vertex_style: true,
                 save_edge_style: true,
@@ -436,6 +909,12 @@ impl Default for GraphEditorApp {
                 inspector_tab: InspectorTab::default(),
                 edit_target: None,
                 edit_window_pos: None,
+                marquee_start: None,
+                algorithm_kind: AlgorithmKind::default(),
+                algorithm_source: 0,
+                algorithm_summary: None,
+                algorithm_player: None,
+                algorithm_speed: 2.0,
             },
             export: ExportService::default(),
             config,
@@ -446,22 +925,39 @@ impl Default for GraphEditorApp {
 impl eframe::App for GraphEditorApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         let state = StoredUiState {
-            version: 3,
+            version: UI_STATE_VERSION,
             zero_indexed: self.state.zero_indexed,
             show_number: self.state.show_number,
             is_animated: self.state.is_animated,
             is_directed: self.state.graph.is_directed,
+            is_weighted: self.state.graph.is_weighted,
+            allows_multi_edges: self.state.graph.allows_multi_edges,
             export_format: self.export.format().extension().to_string(),
             ui_font_size: self.config.ui_font_size,
             vertex_font_size: self.config.vertex_font_size,
             vertex_radius: self.config.vertex_radius,
             vertex_stroke: self.config.vertex_stroke,
             edge_stroke: self.config.edge_stroke,
-            edge_length: self.config.simulator_config.l,
+            edge_length: None,
             edge_bezier_distance: self.config.edge_bezier_distance,
+            self_loop_size: self.config.self_loop_size,
+            custom_palette: self
+                .config
+                .custom_palette
+                .iter()
+                .map(|&rgb| Colors::Custom(rgb).name())
+                .collect(),
+            theme_kind: self.config.theme_kind,
+            custom_theme: self.config.custom_theme,
+            export_theme_kind: self.config.export_theme_kind,
             scale_min: self.config.scale_min,
             scale_max: self.config.scale_max,
             scale_delta: self.config.scale_delta,
+            density_threshold: self.config.density_threshold,
+            visualizer_kind: self.config.visualizer_kind,
+            visualizer_config: self.config.visualizer_config,
+            simulator_kind: self.config.simulator_kind,
+            simulator_config: self.config.simulator_config,
         };
         eframe::set_value(storage, UI_STATE_STORAGE_KEY, &state);
         let graph_state = export_graph_to_file(
@@ -477,6 +973,13 @@ impl eframe::App for GraphEditorApp {
         self.state.graph_view.apply_deletions(&self.state.graph);
         self.state.graph.apply_deletions();
 
+        self.update_algorithm_player(ctx);
+
+        let theme = self.config.theme().egui_theme();
+        if ctx.theme() != theme {
+            ctx.set_theme(theme);
+        }
+
         draw_top_panel(self, ctx);
         draw_footer(self, ctx);
         draw_tool_bar(self, ctx);
@@ -486,9 +989,181 @@ impl eframe::App for GraphEditorApp {
         draw_error_modal(self, ctx);
         draw_clear_all_modal(self, ctx);
 
-        self.handle_export_events(ctx);
-
         // 再描画
         request_repaint(self, ctx);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{GraphEditorApp, StoredUiState, UI_STATE_STORAGE_KEY, UI_STATE_VERSION};
+    use crate::config::{AppConfig, LayoutPreset, SimulatorKind, Theme, ThemeKind, VisualizerKind};
+    use crate::graph::EdgeDirection;
+    use crate::project_io::{export_graph_to_json, import_graph_from_json, SaveOptions};
+
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_string(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    #[test]
+    fn migrates_version_3_ui_state() {
+        let mut storage = MemoryStorage::default();
+        eframe::Storage::set_string(
+            &mut storage,
+            UI_STATE_STORAGE_KEY,
+            "(version: 3, zero_indexed: true, edge_length: 240.0, vertex_radius: 20.0)".to_string(),
+        );
+
+        let state = eframe::get_value::<StoredUiState>(&storage, UI_STATE_STORAGE_KEY)
+            .unwrap()
+            .migrate();
+        assert_eq!(state.version, UI_STATE_VERSION);
+        assert!(state.zero_indexed);
+        assert_eq!(state.vertex_radius, 20.0);
+        assert_eq!(state.simulator_config.l, 240.0);
+        assert_eq!(state.simulator_kind, SimulatorKind::ForceDirected);
+    }
+
+    #[test]
+    fn round_trips_simulation_settings() {
+        let mut state = StoredUiState {
+            density_threshold: 0.5,
+            visualizer_kind: VisualizerKind::Sugiyama,
+            simulator_kind: SimulatorKind::BarnesHut,
+            ..Default::default()
+        };
+        state.simulator_config.c = 1e4;
+        state.simulator_config.l = 90.0;
+        state.visualizer_config.sugiyama_sweeps = 3;
+
+        let mut storage = MemoryStorage::default();
+        eframe::set_value(&mut storage, UI_STATE_STORAGE_KEY, &state);
+        let restored = eframe::get_value::<StoredUiState>(&storage, UI_STATE_STORAGE_KEY)
+            .unwrap()
+            .migrate();
+
+        assert_eq!(restored.density_threshold, 0.5);
+        assert_eq!(restored.visualizer_kind, VisualizerKind::Sugiyama);
+        assert_eq!(restored.simulator_kind, SimulatorKind::BarnesHut);
+        assert_eq!(restored.simulator_config.c, 1e4);
+        assert_eq!(restored.simulator_config.l, 90.0);
+        assert_eq!(restored.visualizer_config.sugiyama_sweeps, 3);
+    }
+
+    #[test]
+    fn tree_friendly_preset_switches_the_simulator_only_on_small_graphs() {
+        let mut config = AppConfig::default();
+        LayoutPreset::TreeFriendly.apply(&mut config, 100_000);
+        assert_eq!(config.visualizer_kind, VisualizerKind::Tree);
+        assert_eq!(config.simulator_kind, SimulatorKind::ForceDirected);
+
+        LayoutPreset::TreeFriendly.apply(&mut config, 10);
+        assert_eq!(config.simulator_kind, SimulatorKind::StressMajorization);
+    }
+
+    #[test]
+    fn applying_json_restores_its_layout_seed() {
+        let mut app = GraphEditorApp::default();
+        app.ui.canvas_rect = Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(720.0, 720.0),
+        ));
+        app.config.visualizer_config.seed = 1;
+        let json = export_graph_to_json(
+            &app.state.graph,
+            &app.state.graph_view,
+            false,
+            SaveOptions {
+                layout_seed: Some(1234),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let imported = import_graph_from_json(&json).unwrap();
+        app.apply_imported_graph(&egui::Context::default(), imported);
+        assert_eq!(app.config.visualizer_config.seed, 1234);
+    }
+
+    #[test]
+    fn pasting_ignores_text_in_other_formats() {
+        let mut app = GraphEditorApp::default();
+        let vertex_count = app.state.graph.vertices.len();
+        for text in ["hello", "3 2\n1 2\n2 3", r#"{"format": "other"}"#] {
+            app.paste_graph_json(text, egui::Pos2::ZERO);
+        }
+        assert_eq!(app.state.graph.vertices.len(), vertex_count);
+        assert!(app.ui.error_message.is_none());
+    }
+
+    #[test]
+    fn pasted_edges_keep_their_direction() {
+        let mut source = GraphEditorApp::default();
+        source.state.graph.is_directed = true;
+        let json = export_graph_to_json(
+            &source.state.graph,
+            &source.state.graph_view,
+            false,
+            SaveOptions::default(),
+        )
+        .unwrap();
+
+        let mut app = GraphEditorApp::default();
+        assert!(!app.state.graph.is_directed);
+        let edge_count = app.state.graph.edges.len();
+        app.paste_graph_json(&json, egui::Pos2::ZERO);
+
+        let pasted = &app.state.graph.edges[edge_count..];
+        assert_eq!(pasted.len(), source.state.graph.edges.len());
+        assert!(pasted
+            .iter()
+            .all(|edge| edge.direction == EdgeDirection::Directed));
+        assert!(app.state.graph.edges[..edge_count]
+            .iter()
+            .all(|edge| edge.direction == EdgeDirection::Inherit));
+    }
+
+    #[test]
+    fn round_trips_theme_settings() {
+        let mut custom_theme = Theme::high_contrast();
+        custom_theme.bg_color = egui::Color32::from_rgb(10, 20, 30);
+        let state = StoredUiState {
+            theme_kind: ThemeKind::Custom,
+            custom_theme,
+            export_theme_kind: Some(ThemeKind::Light),
+            ..Default::default()
+        };
+
+        let mut storage = MemoryStorage::default();
+        eframe::set_value(&mut storage, UI_STATE_STORAGE_KEY, &state);
+        let restored = eframe::get_value::<StoredUiState>(&storage, UI_STATE_STORAGE_KEY).unwrap();
+
+        assert_eq!(restored.theme_kind, ThemeKind::Custom);
+        assert_eq!(restored.custom_theme, custom_theme);
+        assert_eq!(restored.export_theme_kind, Some(ThemeKind::Light));
+
+        // 配色を持たない古い状態は明るい配色になる
+        let mut storage = MemoryStorage::default();
+        eframe::Storage::set_string(
+            &mut storage,
+            UI_STATE_STORAGE_KEY,
+            "(version: 4)".to_string(),
+        );
+        let state = eframe::get_value::<StoredUiState>(&storage, UI_STATE_STORAGE_KEY).unwrap();
+        assert_eq!(state.theme_kind, ThemeKind::Light);
+        assert_eq!(state.export_theme_kind, None);
+    }
+}
@@ -0,0 +1,13 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    if let Err(err) = graph_editor::cli::run(std::env::args().skip(1)) {
+        eprintln!("error: {err:#}");
+        std::process::exit(1);
+    }
+}
+
+// The command-line tool is not available on the web.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,669 @@
+//! ヘッドレスでグラフを変換・描画するコマンドラインツール
+
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::{
+    config::{AppConfig, SimulatorKind, ThemeKind, VisualizerKind},
+    export::{
+        export_png_bytes, export_svg_bytes, export_tikz_bytes, ExportContext, PngOptions,
+        TikzOptions,
+    },
+    graph::{simulation_methods::MAX_QUADRATIC_MODEL_VERTICES, BaseGraph, EdgeListOptions, Graph},
+    project_io::{
+        dot::{export_graph_to_dot, import_graph_from_dot},
+        export_graph_to_json, import_graph_from_json, SaveOptions,
+    },
+    view_state::GraphViewState,
+};
+
+const USAGE: &str = "\
+Usage: graph-editor-cli [OPTIONS] [INPUT]
+
+Reads an edge list, Graph Editor JSON or Graphviz DOT from INPUT (or stdin)
+and writes SVG, PNG, TikZ, JSON, DOT or an edge list to stdout (or --output).
+
+Options:
+  -i, --input-format <FORMAT>   auto | edge-list | json | dot  [default: auto]
+  -f, --format <FORMAT>         svg | png | tikz | json | dot | edge-list
+                                [default: from --output, else svg]
+  -o, --output <FILE>           Write to FILE instead of stdout
+      --zero-indexed            Read and write 0-indexed edge lists  [default: 1-indexed]
+      --directed                Treat an edge list as a directed graph
+      --weighted                Read `u v w` weighted edge lists
+      --detect-indexing         Guess 0- or 1-indexing from the vertex numbers
+      --detect-edge-count       Accept an edge list whose first line is only N
+      --ignore-trailing         Ignore lines after the edges (queries etc.)
+      --layout <LAYOUT>         naive | spectral | hill-climbing | simulated-annealing |
+                                tree | sugiyama
+                                [default: spectral for edge lists, keep positions otherwise]
+      --root <V>                Root vertex for --layout tree  [default: first vertex]
+      --seed <N>                Random seed for the layout  [default: from JSON, else 0]
+      --steps <N>               Run N force-directed simulation steps  [default: 0]
+      --simulator <KIND>        force-directed | barnes-hut | kamada-kawai | stress
+                                [default: force-directed]
+      --size <PX>               Canvas size used for the initial layout  [default: 720]
+      --no-numbers              Do not draw vertex numbers in SVG / PNG
+      --scale <FACTOR>          PNG pixels per SVG pixel  [default: 2]
+      --transparent             Leave the PNG background transparent
+      --theme <THEME>           light | dark | high-contrast for SVG / PNG / TikZ  [default: light]
+      --standalone              Wrap TikZ output in a compilable standalone document
+  -h, --help                    Print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Auto,
+    EdgeList,
+    Json,
+    Dot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Svg,
+    Png,
+    Tikz,
+    Json,
+    Dot,
+    EdgeList,
+}
+
+impl OutputFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "svg" => Some(Self::Svg),
+            "png" => Some(Self::Png),
+            "tex" => Some(Self::Tikz),
+            "json" => Some(Self::Json),
+            "dot" | "gv" => Some(Self::Dot),
+            "txt" => Some(Self::EdgeList),
+            _ => None,
+        }
+    }
+}
+
+/// コマンドライン引数
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub input_format: InputFormat,
+    pub output_format: OutputFormat,
+    pub zero_indexed: bool,
+    pub is_directed: bool,
+    pub is_weighted: bool,
+    pub edge_list_options: EdgeListOptions,
+    pub layout: Option<VisualizerKind>,
+    /// `--layout tree` の根（入力の頂点番号）
+    pub root: Option<usize>,
+    /// 乱択を行う配置の乱数の種
+    pub seed: Option<u64>,
+    pub steps: usize,
+    pub simulator: SimulatorKind,
+    pub size: f32,
+    pub show_number: bool,
+    /// SVG・PNG・TikZ の配色
+    pub theme: ThemeKind,
+    pub png_options: PngOptions,
+    pub tikz_options: TikzOptions,
+    pub show_help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            input: None,
+            output: None,
+            input_format: InputFormat::Auto,
+            output_format: OutputFormat::Svg,
+            zero_indexed: false,
+            is_directed: false,
+            is_weighted: false,
+            edge_list_options: EdgeListOptions::default(),
+            layout: None,
+            root: None,
+            seed: None,
+            steps: 0,
+            simulator: SimulatorKind::ForceDirected,
+            size: 720.0,
+            show_number: true,
+            theme: ThemeKind::Light,
+            png_options: PngOptions::default(),
+            tikz_options: TikzOptions::default(),
+            show_help: false,
+        }
+    }
+}
+
+impl Options {
+    /// 引数（プログラム名を除く）を読み取る
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        let mut output_format = None;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| anyhow::anyhow!("Missing value for {name}"))
+            };
+            match arg.as_str() {
+                "-h" | "--help" => options.show_help = true,
+                "-i" | "--input-format" => {
+                    options.input_format = match value(&arg)?.as_str() {
+                        "auto" => InputFormat::Auto,
+                        "edge-list" => InputFormat::EdgeList,
+                        "json" => InputFormat::Json,
+                        "dot" => InputFormat::Dot,
+                        other => return Err(anyhow::anyhow!("Unknown input format: {other}")),
+                    }
+                }
+                "-f" | "--format" => {
+                    output_format = Some(match value(&arg)?.as_str() {
+                        "svg" => OutputFormat::Svg,
+                        "png" => OutputFormat::Png,
+                        "tikz" => OutputFormat::Tikz,
+                        "json" => OutputFormat::Json,
+                        "dot" => OutputFormat::Dot,
+                        "edge-list" => OutputFormat::EdgeList,
+                        other => return Err(anyhow::anyhow!("Unknown output format: {other}")),
+                    })
+                }
+                "-o" | "--output" => options.output = Some(PathBuf::from(value(&arg)?)),
+                "--zero-indexed" => options.zero_indexed = true,
+                "--directed" => options.is_directed = true,
+                "--weighted" => options.is_weighted = true,
+                "--detect-indexing" => options.edge_list_options.detect_indexing = true,
+                "--detect-edge-count" => options.edge_list_options.detect_edge_count = true,
+                "--ignore-trailing" => options.edge_list_options.ignore_trailing = true,
+                "--layout" => {
+                    options.layout = Some(match value(&arg)?.as_str() {
+                        "naive" => VisualizerKind::Naive,
+                        "spectral" => VisualizerKind::Spectral,
+                        "hill-climbing" => VisualizerKind::HillClimbing,
+                        "simulated-annealing" => VisualizerKind::SimulatedAnnealing,
+                        "tree" => VisualizerKind::Tree,
+                        "sugiyama" => VisualizerKind::Sugiyama,
+                        other => return Err(anyhow::anyhow!("Unknown layout: {other}")),
+                    })
+                }
+                "--root" => {
+                    options.root = Some(
+                        value(&arg)?
+                            .parse()
+                            .context("--root expects a vertex number")?,
+                    )
+                }
+                "--seed" => {
+                    options.seed = Some(
+                        value(&arg)?
+                            .parse()
+                            .context("--seed expects a non-negative integer")?,
+                    )
+                }
+                "--steps" => {
+                    options.steps = value(&arg)?
+                        .parse()
+                        .context("--steps expects a non-negative integer")?
+                }
+                "--simulator" => {
+                    options.simulator = match value(&arg)?.as_str() {
+                        "force-directed" => SimulatorKind::ForceDirected,
+                        "barnes-hut" => SimulatorKind::BarnesHut,
+                        "kamada-kawai" => SimulatorKind::KamadaKawai,
+                        "stress" => SimulatorKind::StressMajorization,
+                        other => return Err(anyhow::anyhow!("Unknown simulator: {other}")),
+                    }
+                }
+                "--size" => {
+                    options.size = value(&arg)?.parse().context("--size expects a number")?
+                }
+                "--no-numbers" => options.show_number = false,
+                "--scale" => {
+                    let scale: f32 = value(&arg)?.parse().context("--scale expects a number")?;
+                    if !PngOptions::SCALE_RANGE.contains(&scale) {
+                        return Err(anyhow::anyhow!(
+                            "--scale must be between {} and {}",
+                            PngOptions::SCALE_RANGE.start(),
+                            PngOptions::SCALE_RANGE.end()
+                        ));
+                    }
+                    options.png_options.scale = scale;
+                }
+                "--theme" => {
+                    options.theme = match value(&arg)?.as_str() {
+                        "light" => ThemeKind::Light,
+                        "dark" => ThemeKind::Dark,
+                        "high-contrast" => ThemeKind::HighContrast,
+                        other => return Err(anyhow::anyhow!("Unknown theme: {other}")),
+                    }
+                }
+                "--transparent" => options.png_options.transparent_background = true,
+                "--standalone" => options.tikz_options.standalone = true,
+                other if other.starts_with('-') && other != "-" => {
+                    return Err(anyhow::anyhow!("Unknown option: {other}"))
+                }
+                _ if options.input.is_some() => {
+                    return Err(anyhow::anyhow!("Unexpected argument: {arg}"))
+                }
+                _ => options.input = (arg != "-").then(|| PathBuf::from(arg)),
+            }
+        }
+
+        options.output_format = output_format
+            .or_else(|| options.output.as_deref().and_then(OutputFormat::from_path))
+            .unwrap_or(OutputFormat::Svg);
+
+        Ok(options)
+    }
+}
+
+/// `#` で始まる行を除いた先頭の文字から入力形式を推測する．
+/// JSON は`{`，DOT はキーワードかコメントで始まる．
+fn detect_input_format(input: &str) -> InputFormat {
+    let first_char = input
+        .lines()
+        .map(str::trim_start)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .and_then(|line| line.chars().next());
+    match first_char {
+        Some('{') => InputFormat::Json,
+        Some(c) if c.is_alphabetic() || c == '/' => InputFormat::Dot,
+        _ => InputFormat::EdgeList,
+    }
+}
+
+/// `--root` で指定した頂点番号を 0 始まりの添字に直す
+fn root_index(options: &Options, zero_indexed: bool, n: usize) -> anyhow::Result<usize> {
+    let Some(root) = options.root else {
+        return Ok(0);
+    };
+    if zero_indexed {
+        Some(root)
+    } else {
+        root.checked_sub(1)
+    }
+    .filter(|&index| index < n)
+    .ok_or_else(|| anyhow::anyhow!("--root {root} is out of range"))
+}
+
+/// 入力を読み込み，配置とシミュレーションを行って指定の形式で出力する
+pub fn convert(input: &str, options: &Options) -> anyhow::Result<Vec<u8>> {
+    let mut config = AppConfig::default();
+    if let Some(layout) = options.layout {
+        config.visualizer_kind = layout;
+    }
+    config.simulator_kind = options.simulator;
+    config.theme_kind = options.theme;
+    let canvas_rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::splat(options.size));
+
+    let input_format = match options.input_format {
+        InputFormat::Auto => detect_input_format(input),
+        format => format,
+    };
+
+    let (mut graph, view, zero_indexed) = if input_format != InputFormat::EdgeList {
+        let imported = if input_format == InputFormat::Json {
+            import_graph_from_json(input)?
+        } else {
+            import_graph_from_dot(input)?
+        };
+        let mut graph = imported.graph;
+        if let Some(seed) = imported.layout_seed {
+            config.visualizer_config.seed = seed;
+        }
+        if let Some(seed) = options.seed {
+            config.visualizer_config.seed = seed;
+        }
+        if options.layout.is_some() {
+            config.visualizer_config.tree_root =
+                root_index(options, imported.zero_indexed, graph.vertices.len())?;
+            graph.relayout(
+                config.visualizer().as_ref(),
+                config.layout_density_threshold(),
+                canvas_rect,
+                &mut config.visualizer_config.rng(),
+            );
+        }
+        (graph, imported.view, imported.zero_indexed)
+    } else {
+        let parsed = BaseGraph::parse_edge_list(
+            input,
+            options.zero_indexed,
+            options.is_weighted,
+            options.edge_list_options,
+        )?;
+        config.visualizer_config.tree_root =
+            root_index(options, parsed.zero_indexed, parsed.graph.n)?;
+        config.visualizer_config.seed = options.seed.unwrap_or_default();
+        let mut graph = Graph {
+            is_directed: options.is_directed,
+            is_weighted: options.is_weighted,
+            ..Default::default()
+        };
+        graph.rebuild_from_basegraph(
+            config.visualizer().as_ref(),
+            config.layout_density_threshold(),
+            parsed.graph,
+            canvas_rect,
+            &mut config.visualizer_config.rng(),
+        )?;
+        let view = GraphViewState::new_for_graph(&graph);
+        (graph, view, parsed.zero_indexed)
+    };
+
+    if options.steps > 0
+        && config.simulator_kind.is_quadratic()
+        && graph.vertices.len() > MAX_QUADRATIC_MODEL_VERTICES
+    {
+        eprintln!(
+            "warning: more than {MAX_QUADRATIC_MODEL_VERTICES} vertices, using barnes-hut instead"
+        );
+    }
+    let simulator = config.simulator();
+    for _ in 0..options.steps {
+        simulator.simulate_step(&mut graph);
+    }
+
+    let export_ctx = ExportContext {
+        graph: &graph,
+        view: &view,
+        config: &config,
+        theme: config.export_theme(),
+        show_number: options.show_number,
+        zero_indexed,
+    };
+    match options.output_format {
+        OutputFormat::Svg => export_svg_bytes(&export_ctx),
+        OutputFormat::Png => export_png_bytes(&export_ctx, options.png_options),
+        OutputFormat::Tikz => export_tikz_bytes(&export_ctx, options.tikz_options),
+        OutputFormat::Json => Ok(export_graph_to_json(
+            &graph,
+            &view,
+            zero_indexed,
+            SaveOptions {
+                layout_seed: Some(config.visualizer_config.seed),
+                ..Default::default()
+            },
+        )?
+        .into_bytes()),
+        OutputFormat::Dot => Ok(export_graph_to_dot(&graph, &view, zero_indexed).into_bytes()),
+        OutputFormat::EdgeList => Ok(graph.encode(zero_indexed).into_bytes()),
+    }
+}
+
+/// コマンドラインツールのエントリーポイント
+pub fn run(args: impl IntoIterator<Item = String>) -> anyhow::Result<()> {
+    let options = Options::parse(args)?;
+    if options.show_help {
+        print!("{USAGE}");
+        return Ok(());
+    }
+
+    let input = match &options.input {
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?,
+        None => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .context("failed to read stdin")?;
+            input
+        }
+    };
+
+    let bytes = convert(&input, &options)?;
+
+    match &options.output {
+        Some(path) => std::fs::write(path, bytes)
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => std::io::stdout()
+            .write_all(&bytes)
+            .context("failed to write stdout")?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert, Options, OutputFormat};
+
+    fn options(args: &[&str]) -> Options {
+        Options::parse(args.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    #[test]
+    fn output_format_follows_output_extension() {
+        assert_eq!(
+            options(&["-o", "graph.json"]).output_format,
+            OutputFormat::Json
+        );
+        assert_eq!(
+            options(&["-o", "graph.json", "-f", "svg"]).output_format,
+            OutputFormat::Svg
+        );
+        assert!(Options::parse(["--bogus".to_string()]).is_err());
+    }
+
+    #[test]
+    fn converts_edge_list_to_edge_list_and_svg() {
+        let input = "3 2\n1 2\n2 3\n";
+
+        let edge_list = convert(input, &options(&["-f", "edge-list", "--steps", "5"])).unwrap();
+        assert_eq!(String::from_utf8(edge_list).unwrap().trim(), input.trim());
+
+        let svg = String::from_utf8(convert(input, &options(&[])).unwrap()).unwrap();
+        assert!(svg.starts_with("<?xml"));
+        assert_eq!(svg.matches("<circle").count(), 6);
+    }
+
+    #[test]
+    fn seeded_layouts_are_reproducible() {
+        let input = "5 4\n1 2\n2 3\n3 4\n4 5\n";
+        let layout = |seed: &str| {
+            String::from_utf8(
+                convert(
+                    input,
+                    &options(&["-f", "json", "--layout", "naive", "--seed", seed]),
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+
+        let json = layout("42");
+        assert_eq!(json, layout("42"));
+        assert_ne!(json, layout("43"));
+        assert!(json.contains("\"layout_seed\": 42"));
+
+        // JSON に記録された種で配置し直す
+        let relayout = convert(&json, &options(&["-f", "json", "--layout", "naive"])).unwrap();
+        assert_eq!(String::from_utf8(relayout).unwrap(), json);
+    }
+
+    #[test]
+    fn json_round_trips_through_the_cli() {
+        let json = convert(
+            "2 1\n0 1 7\n",
+            &options(&["--zero-indexed", "--weighted", "--directed", "-f", "json"]),
+        )
+        .unwrap();
+        let json = String::from_utf8(json).unwrap();
+
+        let edge_list = convert(&json, &options(&["-f", "edge-list"])).unwrap();
+        assert_eq!(String::from_utf8(edge_list).unwrap().trim(), "2 1\n0 1 7");
+    }
+
+    #[test]
+    fn tree_layout_puts_the_root_on_top() {
+        let json = convert(
+            "4 3\n1 2\n1 3\n3 4\n",
+            &options(&["-f", "json", "--layout", "tree", "--root", "3"]),
+        )
+        .unwrap();
+        let imported =
+            crate::project_io::import_graph_from_json(std::str::from_utf8(&json).unwrap()).unwrap();
+        let y: Vec<_> = imported
+            .graph
+            .vertices
+            .iter()
+            .map(|vertex| vertex.position.y)
+            .collect();
+        assert!(y[2] < y[0] && y[2] < y[3]);
+        assert!(y[0] < y[1]);
+
+        assert!(convert("2 1\n1 2\n", &options(&["--layout", "tree", "--root", "3"])).is_err());
+    }
+
+    #[test]
+    fn dot_input_is_detected_and_converted() {
+        let dot = convert("3 2\n1 2\n2 3\n", &options(&["-f", "dot"])).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.starts_with("graph G {"));
+
+        let edge_list = convert(&dot, &options(&["-f", "edge-list"])).unwrap();
+        assert_eq!(
+            String::from_utf8(edge_list).unwrap().trim(),
+            "3 2\n1 2\n2 3"
+        );
+    }
+
+    #[test]
+    fn commented_sample_input_is_read_as_an_edge_list() {
+        let input = "# sample 1\n3\n0 1\n1 2\n2\n0 2\n";
+        let args = [
+            "-f",
+            "edge-list",
+            "--detect-indexing",
+            "--detect-edge-count",
+            "--ignore-trailing",
+        ];
+        let edge_list = convert(input, &options(&args)).unwrap();
+        assert_eq!(
+            String::from_utf8(edge_list).unwrap().trim(),
+            "3 2\n0 1\n1 2"
+        );
+
+        let err = convert(input, &options(&["-f", "edge-list"])).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("line 3, column 3: Excessive input"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn tikz_curves_antiparallel_edges_and_defines_colors() {
+        let dot = "digraph { 1 [fillcolor=red]; 1 -> 2; 2 -> 1; 2 -> 3 [label=\"a_b\"] }";
+
+        let tikz = convert(dot, &options(&["-f", "tikz", "--standalone"])).unwrap();
+        let tikz = String::from_utf8(tikz).unwrap();
+        assert!(tikz.starts_with("\\documentclass"));
+        assert!(tikz.trim_end().ends_with("\\end{document}"));
+        assert!(tikz.contains("\\definecolor{gered}{HTML}{FF4646}"));
+        assert_eq!(tikz.matches(".. controls").count(), 2);
+        assert_eq!(tikz.matches("\\draw[").count(), 3);
+        assert!(tikz.contains("{a\\_b}"));
+
+        let snippet = convert(dot, &options(&["-f", "tikz"])).unwrap();
+        assert!(!String::from_utf8(snippet)
+            .unwrap()
+            .contains("\\documentclass"));
+    }
+
+    #[test]
+    fn self_loops_are_exported_as_loops() {
+        let dot = "digraph { 1 -> 1 [label=\"l\"]; 1 -> 2 }";
+
+        let svg = String::from_utf8(convert(dot, &options(&["-f", "svg"])).unwrap()).unwrap();
+        assert_eq!(svg.matches("<path d=\"M ").count(), 1);
+        assert!(svg.contains(" C "));
+        assert_eq!(svg.matches("<polygon").count(), 2);
+        assert!(svg.contains(">l</text>"));
+
+        let tikz = String::from_utf8(convert(dot, &options(&["-f", "tikz"])).unwrap()).unwrap();
+        assert!(tikz.contains("\\draw[ge edge, draw=geedge, ->] (v0) .. controls"));
+        assert!(tikz.contains(".. (v0);"));
+    }
+
+    #[test]
+    fn parallel_edges_are_kept_and_drawn_as_separate_curves() {
+        let input = "2 3\n1 2\n2 1\n1 2\n";
+
+        let edge_list = convert(input, &options(&["-f", "edge-list"])).unwrap();
+        assert_eq!(String::from_utf8(edge_list).unwrap().trim(), input.trim());
+
+        let json = String::from_utf8(convert(input, &options(&["-f", "json"])).unwrap()).unwrap();
+        assert!(json.contains("\"multigraph\": true"));
+
+        // 3 本のうち 1 本は直線，残りは反対側へ膨らむ曲線になる
+        let svg = String::from_utf8(convert(input, &options(&[])).unwrap()).unwrap();
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert_eq!(svg.matches(" Q ").count(), 2);
+    }
+
+    #[test]
+    fn per_edge_directions_are_exported() {
+        let json = convert("3 2\n1 2\n2 3\n", &options(&["--directed", "-f", "json"])).unwrap();
+        let json = String::from_utf8(json).unwrap().replacen(
+            "\"to\": 2",
+            "\"to\": 2,\n      \"direction\": \"bidirectional\"",
+            1,
+        );
+
+        let edge_list = convert(&json, &options(&["-f", "edge-list"])).unwrap();
+        assert_eq!(
+            String::from_utf8(edge_list).unwrap().trim(),
+            "3 3\n1 2\n2 3\n3 2"
+        );
+
+        // 両向きの辺だけ両端に矢印を付ける
+        let svg = String::from_utf8(convert(&json, &options(&[])).unwrap()).unwrap();
+        assert_eq!(svg.matches("<polygon").count(), 3);
+        let tikz = String::from_utf8(convert(&json, &options(&["-f", "tikz"])).unwrap()).unwrap();
+        assert!(tikz.contains(", ->]"));
+        assert!(tikz.contains(", <->]"));
+    }
+
+    #[test]
+    fn png_is_rendered_from_the_model_at_the_requested_scale() {
+        let json = convert("3 2\n1 2\n2 3\n", &options(&["-f", "json"])).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        let decode = |args: &[&str]| {
+            let bytes = convert(&json, &options(args)).unwrap();
+            image::load_from_memory(&bytes).unwrap().to_rgba8()
+        };
+
+        let small = decode(&["-f", "png", "--scale", "1"]);
+        let large = decode(&["-f", "png", "--scale", "2"]);
+        assert!(large.width().abs_diff(small.width() * 2) <= 1);
+        assert!(large.height().abs_diff(small.height() * 2) <= 1);
+        assert_eq!(small.get_pixel(0, 0)[3], 255);
+
+        let transparent = decode(&["-f", "png", "--transparent"]);
+        assert_eq!(transparent.get_pixel(0, 0)[3], 0);
+    }
+
+    #[test]
+    fn theme_sets_the_export_background() {
+        let input = "3 2\n1 2\n2 3\n";
+        let svg =
+            |args: &[&str]| String::from_utf8(convert(input, &options(args)).unwrap()).unwrap();
+
+        let background = |hex: &str| format!("<rect width=\"100%\" height=\"100%\" fill=\"{hex}\"");
+        assert!(svg(&[]).contains(&background("#E6E6E6")));
+        assert!(svg(&["--theme", "dark"]).contains(&background("#202124")));
+        assert!(svg(&["--theme", "high-contrast"]).contains(&background("#FFFFFF")));
+
+        let png = convert(input, &options(&["-f", "png", "--theme", "dark"])).unwrap();
+        let png = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(png.get_pixel(0, 0).0, [32, 33, 36, 255]);
+        assert!(Options::parse(["--theme".to_string(), "bogus".to_string()]).is_err());
+    }
+}
@@ -4,14 +4,16 @@ use itertools::Itertools;
 
 use super::transition_and_scale::{drag_central_panel, scale_central_panel};
 use crate::{
-    components::{default_vertex_text_color, Colors},
+    components::default_vertex_text_color,
     config::AppConfig,
-    graph::Graph,
+    graph::{EdgeDirection, Graph},
     math::{
         affine::{Affine2D, ApplyAffine},
         bezier::{
             bezier_curve, calc_bezier_control_point, calc_intersection_of_bezier_and_circle,
-            d2_bezier_dt2, d_bezier_dt,
+            cubic_bezier_curve, d2_bezier_dt2, d_bezier_dt, d_cubic_bezier_dt, edge_midpoint,
+            edge_side_point, nested_self_loop_size, parallel_edge_offsets, self_loop_directions,
+            self_loop_points, self_loop_side_point, trim_cubic_bezier_end,
         },
         newton::newton_method,
     },
@@ -24,15 +26,27 @@ use crate::{
 /// メイン領域を描画
 pub fn draw_central_panel(app: &mut GraphEditorApp, ctx: &egui::Context) {
     egui::CentralPanel::default()
-        .frame(egui::Frame::new().fill(app.config.bg_color))
+        .frame(egui::Frame::new().fill(app.config.theme().bg_color))
         .show(ctx, |ui| {
             app.ui.canvas_rect = Some(ui.max_rect());
 
+            // 元に戻す / やり直す
+            handle_history_shortcuts(app, ui);
+
             // モード切替を行う
             change_edit_mode(app, ui);
 
+            // 選択のショートカット処理
+            handle_selection_shortcuts(app, ui);
+
+            // コピー / 貼り付け
+            handle_clipboard_events(app, ui);
+
             // ドラッグを行う
-            drag_central_panel(app, ui);
+            let canvas_response = drag_central_panel(app, ui);
+
+            // 範囲選択を行う
+            update_marquee_selection(app, ui, &canvas_response);
 
             // スケールを行う
             scale_central_panel(app, ui);
@@ -60,13 +74,133 @@ pub fn draw_central_panel(app: &mut GraphEditorApp, ctx: &egui::Context) {
 
             // 頂点の描画
             render_vertices(&snapshot, app, ui, painter);
+
+            // 範囲選択の矩形を描画
+            render_marquee(app, ui, painter);
+        });
+}
+
+/// Undo / Redo のショートカット処理
+fn handle_history_shortcuts(app: &mut GraphEditorApp, ui: &egui::Ui) {
+    // 入力中はテキストエディタ側の Undo を優先する
+    if app.ui.input_has_focus || ui.ctx().wants_keyboard_input() {
+        return;
+    }
+
+    let redo_shortcut = egui::KeyboardShortcut::new(
+        egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+        egui::Key::Z,
+    );
+    let undo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+
+    // Shift 付きを先に判定する
+    if ui.input_mut(|i| i.consume_shortcut(&redo_shortcut)) {
+        app.redo();
+    } else if ui.input_mut(|i| i.consume_shortcut(&undo_shortcut)) {
+        app.undo();
+    }
+}
+
+/// 全選択・選択範囲の削除のショートカット処理
+fn handle_selection_shortcuts(app: &mut GraphEditorApp, ui: &egui::Ui) {
+    if app.ui.input_has_focus
+        || ui.ctx().wants_keyboard_input()
+        || app.state.edit_mode != EditMode::Normal
+    {
+        return;
+    }
+
+    let select_all_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::A);
+    if ui.input_mut(|i| i.consume_shortcut(&select_all_shortcut)) {
+        app.select_all();
+    }
+    let duplicate_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::D);
+    if ui.input_mut(|i| i.consume_shortcut(&duplicate_shortcut)) {
+        app.duplicate_selection();
+    }
+    if ui.input(|i| i.key_pressed(egui::Key::Delete) || i.key_pressed(egui::Key::Backspace)) {
+        app.delete_selection();
+    }
+    if ui.input(|i| i.key_pressed(egui::Key::P)) {
+        app.toggle_pin_selection();
+    }
+}
+
+/// クリップボードのコピー・貼り付けイベントの処理
+fn handle_clipboard_events(app: &mut GraphEditorApp, ui: &egui::Ui) {
+    if app.ui.input_has_focus || ui.ctx().wants_keyboard_input() {
+        return;
+    }
+
+    let (copied, pasted) = ui.input(|i| {
+        let copied = i.events.contains(&egui::Event::Copy);
+        let pasted = i.events.iter().find_map(|event| match event {
+            egui::Event::Paste(text) => Some(text.clone()),
+            _ => None,
         });
+        (copied, pasted)
+    });
+
+    if copied {
+        app.copy_selection(ui.ctx());
+    }
+    if let Some(text) = pasted {
+        // カーソル位置を中心に貼り付ける
+        let anchor = ui
+            .input(|i| i.pointer.hover_pos())
+            .unwrap_or_else(|| ui.max_rect().center());
+        app.paste_graph_json(&text, anchor);
+    }
+}
+
+/// Shift + ドラッグによる範囲選択の処理
+fn update_marquee_selection(
+    app: &mut GraphEditorApp,
+    ui: &egui::Ui,
+    canvas_response: &egui::Response,
+) {
+    if app.state.edit_mode != EditMode::Normal {
+        app.ui.marquee_start = None;
+        return;
+    }
+
+    let modifiers = ui.input(|i| i.modifiers);
+
+    if canvas_response.drag_started_by(egui::PointerButton::Primary) && modifiers.shift {
+        app.ui.marquee_start = ui.input(|i| i.pointer.press_origin());
+    }
+
+    if let Some(start) = app.ui.marquee_start {
+        if canvas_response.drag_stopped() || !canvas_response.dragged() {
+            if let Some(end) = ui.input(|i| i.pointer.latest_pos()) {
+                // Ctrl を押している場合は現在の選択に追加する
+                app.select_in_rect(egui::Rect::from_two_pos(start, end), modifiers.command);
+            }
+            app.ui.marquee_start = None;
+        }
+        return;
+    }
+
+    // 何もない場所をクリックした場合は選択を解除する
+    let is_on_edge = app
+        .state
+        .graph_view
+        .edges
+        .iter()
+        .any(|edge| edge.is_pressed);
+    if canvas_response.clicked() && !modifiers.command && !is_on_edge {
+        app.state.graph_view.clear_selection();
+    }
 }
 
 /// モード切替の処理
 fn change_edit_mode(app: &mut GraphEditorApp, ui: &egui::Ui) {
     // 入力中はモード切替を行わない
-    if app.ui.input_has_focus || ui.ctx().wants_keyboard_input() {
+    // Ctrl 付きのキーはコピー・貼り付けなどのショートカットに使う
+    if app.ui.input_has_focus
+        || ui.ctx().wants_keyboard_input()
+        || ui.input(|i| i.modifiers.command)
+    {
         return;
     }
 
@@ -97,6 +231,7 @@ fn change_edit_mode(app: &mut GraphEditorApp, ui: &egui::Ui) {
     if ui.input(|i| i.key_pressed(egui::Key::D)) {
         // Shift + D で無向グラフ/有向グラフを切り替え
         if ui.input(|i| i.modifiers.shift) {
+            app.record_history();
             app.state.graph.is_directed ^= true;
         } else {
             app.switch_delete_mode();
@@ -121,6 +256,7 @@ fn add_vertex(app: &mut GraphEditorApp, ui: &egui::Ui) {
                 let pos = scaled_pos + affine.translation();
 
                 let z_index = app.state.next_z_index;
+                app.record_history();
                 app.state.graph.add_vertex(pos);
                 app.state.graph_view.add_vertex(z_index);
                 app.state.next_z_index += 1;
@@ -138,12 +274,12 @@ fn update_edge_interactions(app: &mut GraphEditorApp, ui: &egui::Ui) {
         return;
     }
 
-    let is_directed = app.state.graph.is_directed;
     let mouse_pos = ui.input(|i| i.pointer.hover_pos()).unwrap_or_default();
     let vertex_radius = app
         .config
         .effective_vertex_radius(app.state.graph.vertices.len());
     let primary_clicked = ui.input(|i| i.pointer.button_clicked(egui::PointerButton::Primary));
+    let is_command = ui.input(|i| i.modifiers.command);
 
     let vertex_positions: HashMap<usize, egui::Pos2> = app
         .state
@@ -154,66 +290,98 @@ fn update_edge_interactions(app: &mut GraphEditorApp, ui: &egui::Ui) {
         .map(|v| (v.id, v.get_position()))
         .collect();
 
-    let edge_count = app
+    // 描画と同じ形の曲線に対して判定するため，有効な辺だけでずらし量を求める
+    let active_edges = app
         .state
         .graph
         .edges()
         .iter()
-        .filter(|edge| !edge.is_deleted)
-        .filter(|edge| vertex_positions.contains_key(&edge.from))
-        .filter(|edge| vertex_positions.contains_key(&edge.to))
-        .fold(HashMap::new(), |mut map, edge| {
-            *map.entry((edge.from, edge.to)).or_insert(0) += 1;
-            *map.entry((edge.to, edge.from)).or_insert(0) += 1;
-            map
-        });
-
-    for (index, edge) in app.state.graph.edges_mut().iter_mut().enumerate() {
-        let Some(view) = app.state.graph_view.edges.get_mut(index) else {
-            continue;
-        };
-        if edge.is_deleted {
-            continue;
-        }
+        .enumerate()
+        .filter(|(_, edge)| !edge.is_deleted)
+        .filter(|(_, edge)| vertex_positions.contains_key(&edge.from))
+        .filter(|(_, edge)| vertex_positions.contains_key(&edge.to))
+        .map(|(index, edge)| (index, edge.from, edge.to))
+        .collect::<Vec<_>>();
+    let offsets = parallel_edge_offsets(active_edges.iter().map(|&(_, from, to)| (from, to)));
+    let loop_directions = self_loop_directions(
+        &vertex_positions,
+        active_edges.iter().map(|&(_, from, to)| (from, to)),
+    );
 
-        let (Some(&from_pos), Some(&to_pos)) = (
-            vertex_positions.get(&edge.from),
-            vertex_positions.get(&edge.to),
-        ) else {
-            view.is_pressed = false;
-            continue;
-        };
+    // 多重辺などで複数の辺が近い場合は，最も近い辺だけを操作の対象にする
+    let threshold = 10.0;
+    let mut hovered_edge: Option<(usize, f32)> = None;
+    for (&(index, from, to), &edge_offset) in active_edges.iter().zip(&offsets) {
+        let (from_pos, to_pos) = (vertex_positions[&from], vertex_positions[&to]);
 
         let distance_from_vertex = (mouse_pos - from_pos)
             .length()
             .min((mouse_pos - to_pos).length());
-        let is_on_vertex = distance_from_vertex < vertex_radius;
+        if distance_from_vertex < vertex_radius {
+            continue;
+        }
 
-        let distance = if !is_directed || edge_count.get(&(edge.from, edge.to)) == Some(&1) {
+        let distance = if from == to {
+            let radius = app
+                .state
+                .graph_view
+                .vertices
+                .get(from)
+                .and_then(|view| view.radius)
+                .unwrap_or(vertex_radius);
+            let points = self_loop_points(
+                from_pos,
+                radius,
+                loop_directions[&from],
+                nested_self_loop_size(app.config.self_loop_size, edge_offset),
+            );
+            distance_from_self_loop(points, mouse_pos)
+        } else if edge_offset == 0.0 {
             distance_from_edge_line(from_pos, to_pos, mouse_pos)
         } else {
-            distance_from_edge_bezier(from_pos, to_pos, app.config.edge_bezier_distance, mouse_pos)
+            distance_from_edge_bezier(
+                from_pos,
+                to_pos,
+                app.config.edge_bezier_distance * edge_offset,
+                mouse_pos,
+            )
         };
 
-        let threshold = 10.0;
-        let is_on_edge = distance < threshold;
+        if distance < threshold && hovered_edge.is_none_or(|(_, nearest)| distance < nearest) {
+            hovered_edge = Some((index, distance));
+        }
+    }
+    let hovered_edge = hovered_edge.map(|(index, _)| index);
 
-        if is_on_edge && !is_on_vertex {
-            view.is_pressed = true;
+    for (index, view) in app.state.graph_view.edges.iter_mut().enumerate() {
+        view.is_pressed = hovered_edge == Some(index);
+    }
 
-            if primary_clicked && app.state.edit_mode == EditMode::Normal {
-                app.ui.edit_target = Some(EditTarget::Edge(index));
-                app.ui.edit_window_pos = Some(mouse_pos);
-            } else if ui.input(|i| i.pointer.any_click()) {
-                if app.state.edit_mode.is_colorize() {
-                    view.color = app.state.selected_color;
-                } else if app.state.edit_mode.is_delete() {
-                    edge.is_deleted = true;
-                }
-            }
-        } else {
-            view.is_pressed = false;
-        }
+    let Some(index) = hovered_edge else {
+        return;
+    };
+    let view = &mut app.state.graph_view.edges[index];
+    if primary_clicked && app.state.edit_mode == EditMode::Normal && is_command {
+        // Ctrl + クリックで選択を切り替える
+        view.is_selected ^= true;
+    } else if primary_clicked && app.state.edit_mode == EditMode::Normal {
+        app.ui.edit_target = Some(EditTarget::Edge(index));
+        app.ui.edit_window_pos = Some(mouse_pos);
+    }
+
+    let is_clicked = ui.input(|i| i.pointer.any_click())
+        && (app.state.edit_mode.is_delete()
+            || app.state.edit_mode.is_colorize() && view.color != app.state.selected_color);
+    if !is_clicked {
+        return;
+    }
+
+    // 変更前の状態を記録してから反映する
+    app.record_history();
+    if app.state.edit_mode.is_colorize() {
+        app.state.graph_view.edges[index].color = app.state.selected_color;
+    } else if app.state.edit_mode.is_delete() {
+        app.state.graph.edges[index].is_deleted = true;
     }
 }
 
@@ -262,6 +430,23 @@ fn distance_from_edge_bezier(
         .unwrap_or(f32::INFINITY)
 }
 
+/// 自己ループの曲線を折れ線で近似して，マウスとの距離を求める
+fn distance_from_self_loop(points: [egui::Pos2; 4], mouse_pos: egui::Pos2) -> f32 {
+    const SEGMENTS: usize = 32;
+
+    (0..=SEGMENTS)
+        .map(|i| cubic_bezier_curve(points, i as f32 / SEGMENTS as f32))
+        .tuple_windows()
+        .map(|(start, end)| {
+            if start == end {
+                start.distance(mouse_pos)
+            } else {
+                distance_from_edge_line(start, end, mouse_pos)
+            }
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
 fn draw_edge_undirected(
     painter: &egui::Painter,
     from_pos: egui::Pos2,
@@ -272,12 +457,33 @@ fn draw_edge_undirected(
     painter.line_segment([from_pos, to_pos], egui::Stroke::new(stroke, color));
 }
 
+/// 多重辺を曲線で描画する
+fn draw_edge_undirected_curved(
+    painter: &egui::Painter,
+    from_pos: egui::Pos2,
+    to_pos: egui::Pos2,
+    bezier_distance: f32,
+    stroke: f32,
+    color: egui::Color32,
+) {
+    let control = calc_bezier_control_point(from_pos, to_pos, bezier_distance, false);
+    painter.add(epaint::QuadraticBezierShape {
+        points: [from_pos, control, to_pos],
+        closed: false,
+        fill: egui::Color32::TRANSPARENT,
+        stroke: epaint::PathStroke::new(stroke, color),
+    });
+}
+
+/// 辺の向きに応じて矢印を描画する．`source_radius`を指定すると始点側にも矢印を付ける
+#[allow(clippy::too_many_arguments)]
 fn draw_edge_directed(
     painter: &egui::Painter,
     from_pos: egui::Pos2,
     to_pos: egui::Pos2,
     color: egui::Color32,
     stroke_width: f32,
+    source_radius: Option<f32>,
     target_radius: f32,
     config: &AppConfig,
 ) {
@@ -285,40 +491,39 @@ fn draw_edge_directed(
     let dir = (to_pos - from_pos).normalized();
     let arrowhead = to_pos - dir * target_radius;
     let endpoint = arrowhead - dir * config.edge_arrow_length;
-
-    // 矢印のヘッド（三角形）の3つの頂点を計算
-    let dir = dir * config.edge_arrow_length;
-    let left = egui::Pos2::new(
-        arrowhead.x - dir.x - dir.y * (config.edge_arrow_width / config.edge_arrow_length),
-        arrowhead.y - dir.y + dir.x * (config.edge_arrow_width / config.edge_arrow_length),
-    );
-    let right = egui::Pos2::new(
-        arrowhead.x - dir.x + dir.y * (config.edge_arrow_width / config.edge_arrow_length),
-        arrowhead.y - dir.y - dir.x * (config.edge_arrow_width / config.edge_arrow_length),
-    );
-
-    // 三角形を描画
-    painter.add(egui::Shape::convex_polygon(
-        vec![arrowhead, left, right],
-        color,
-        egui::Stroke::NONE,
-    ));
+    draw_arrowhead(painter, arrowhead, dir, color, config);
+
+    // 両向きの辺は始点側にも矢印を付ける
+    let startpoint = match source_radius {
+        Some(source_radius) => {
+            let arrowhead = from_pos + dir * source_radius;
+            draw_arrowhead(painter, arrowhead, -dir, color, config);
+            arrowhead + dir * config.edge_arrow_length
+        }
+        None => from_pos,
+    };
 
     // 線を描画
-    painter.line_segment([from_pos, endpoint], egui::Stroke::new(stroke_width, color));
+    painter.line_segment(
+        [startpoint, endpoint],
+        egui::Stroke::new(stroke_width, color),
+    );
 }
 
 /// 曲線付きの矢印を描画する関数
+#[allow(clippy::too_many_arguments)]
 fn draw_edge_directed_curved(
     painter: &egui::Painter,
     from_pos: egui::Pos2,
     to_pos: egui::Pos2,
+    bezier_distance: f32,
     color: egui::Color32,
     stroke_width: f32,
+    source_radius: Option<f32>,
     target_radius: f32,
     config: &AppConfig,
 ) -> Option<()> {
-    let control = calc_bezier_control_point(from_pos, to_pos, config.edge_bezier_distance, false);
+    let control = calc_bezier_control_point(from_pos, to_pos, bezier_distance, false);
 
     // ベジェ曲線と円の交点を計算
     let (arrowhead, dir) =
@@ -332,16 +537,50 @@ fn draw_edge_directed_curved(
         stroke: epaint::PathStroke::new(stroke_width, color),
     };
     painter.add(bezier);
+    draw_masked_arrowhead(painter, arrowhead, dir, color, stroke_width, config);
+
+    // 両向きの辺は，曲線を逆向きにたどって始点側にも矢印を付ける
+    if let Some(source_radius) = source_radius {
+        let (arrowhead, dir) = calc_intersection_of_bezier_and_circle(
+            to_pos,
+            control,
+            from_pos,
+            from_pos,
+            source_radius,
+        )?;
+        draw_masked_arrowhead(painter, arrowhead, dir, color, stroke_width, config);
+    }
 
-    // 矢印のヘッドに曲線が重ならないよう，マスクを作成
+    Some(())
+}
+
+/// 矢印のヘッドに曲線が重ならないようマスクしてから，ヘッドを描画する
+fn draw_masked_arrowhead(
+    painter: &egui::Painter,
+    arrowhead: egui::Pos2,
+    dir: egui::Vec2,
+    color: egui::Color32,
+    stroke_width: f32,
+    config: &AppConfig,
+) {
     painter.line_segment(
         [
             arrowhead - dir.normalized() * config.edge_arrow_length / 2.0,
             arrowhead,
         ],
-        egui::Stroke::new(stroke_width, config.bg_color),
+        egui::Stroke::new(stroke_width, config.theme().bg_color),
     );
+    draw_arrowhead(painter, arrowhead, dir, color, config);
+}
 
+/// `arrowhead`を先端とし，`dir`の向きを指す矢印のヘッド（三角形）を描画する
+fn draw_arrowhead(
+    painter: &egui::Painter,
+    arrowhead: egui::Pos2,
+    dir: egui::Vec2,
+    color: egui::Color32,
+    config: &AppConfig,
+) {
     // 矢印のヘッド（三角形）の3つの頂点を計算
     let dir = dir.normalized() * config.edge_arrow_length;
     let left = egui::Pos2::new(
@@ -359,8 +598,46 @@ fn draw_edge_directed_curved(
         color,
         egui::Stroke::NONE,
     ));
+}
 
-    Some(())
+/// 自己ループを描画する．辺の向きに応じて終点（両向きの場合は始点にも）矢印を付ける
+fn draw_self_loop(
+    painter: &egui::Painter,
+    points: [egui::Pos2; 4],
+    color: egui::Color32,
+    stroke_width: f32,
+    direction: EdgeDirection,
+    config: &AppConfig,
+) {
+    let stroke = epaint::PathStroke::new(stroke_width, color);
+    if direction == EdgeDirection::Undirected {
+        painter.add(epaint::CubicBezierShape::from_points_stroke(
+            points,
+            false,
+            egui::Color32::TRANSPARENT,
+            stroke,
+        ));
+        return;
+    }
+
+    // 矢印のヘッドに曲線が重ならないよう，手前で曲線を切る
+    let mut trimmed = trim_cubic_bezier_end(points, config.edge_arrow_length);
+    if direction == EdgeDirection::Bidirectional {
+        trimmed.reverse();
+        trimmed = trim_cubic_bezier_end(trimmed, config.edge_arrow_length);
+        trimmed.reverse();
+        let dir = -d_cubic_bezier_dt(points, 0.0);
+        draw_arrowhead(painter, points[0], dir, color, config);
+    }
+    painter.add(epaint::CubicBezierShape::from_points_stroke(
+        trimmed,
+        false,
+        egui::Color32::TRANSPARENT,
+        stroke,
+    ));
+
+    let dir = d_cubic_bezier_dt(points, 1.0);
+    draw_arrowhead(painter, points[3], dir, color, config);
 }
 
 /// 頂点の操作を更新する
@@ -371,12 +648,19 @@ fn update_vertex_interactions(app: &mut GraphEditorApp, ui: &egui::Ui) {
     let AppState {
         graph,
         graph_view,
+        history,
         edit_mode,
         selected_color,
         next_z_index,
         ..
     } = &mut app.state;
     let is_directed = graph.is_directed;
+    let allows_multi_edges = graph.allows_multi_edges;
+    let is_command = ui.input(|i| i.modifiers.command);
+    // AddEdge モードで始点を Shift + クリックすると自己ループを追加する
+    let is_shift = ui.input(|i| i.modifiers.shift);
+    // 選択中の頂点をドラッグした場合の移動量
+    let mut group_drag: Option<(usize, egui::Vec2)> = None;
     {
         let mut indices: Vec<usize> = graph
             .vertices
@@ -393,24 +677,52 @@ fn update_vertex_interactions(app: &mut GraphEditorApp, ui: &egui::Ui) {
                 .unwrap_or(0)
         });
 
-        let (vertices_mut, edges_mut) = graph.vertices_edges_mut();
-
         for idx in indices {
-            let vertex = &mut vertices_mut[idx];
-            let Some(view) = graph_view.vertices.get_mut(idx) else {
+            let Some(view) = graph_view.vertices.get(idx) else {
                 continue;
             };
+            let vertex_id = graph.vertices[idx].id;
             let vertex_radius = view.radius.unwrap_or(vertex_radius);
             let rect = egui::Rect::from_center_size(
-                vertex.get_position(),
+                graph.vertices[idx].get_position(),
                 egui::vec2(vertex_radius * 2.0, vertex_radius * 2.0),
             );
             let response = ui.interact(
                 rect,
-                egui::Id::new(vertex.id),
+                egui::Id::new(vertex_id),
                 egui::Sense::click_and_drag(),
             );
 
+            // グラフに変更が加わる操作の前に履歴を記録する
+            let is_clicked = response.clicked() && !response.dragged();
+            let mutates = match edit_mode {
+                EditMode::AddEdge {
+                    from_vertex: Some(from_vertex_inner),
+                    ..
+                } => {
+                    is_clicked
+                        && (*from_vertex_inner != vertex_id || is_shift)
+                        && (allows_multi_edges
+                            || !Graph::has_same_edge(
+                                is_directed,
+                                &graph.edges,
+                                *from_vertex_inner,
+                                vertex_id,
+                            ))
+                }
+                EditMode::Colorize => is_clicked && view.color != *selected_color,
+                EditMode::Delete => is_clicked,
+                _ => false,
+            };
+            if response.drag_started() || mutates {
+                history.record(graph, graph_view);
+            }
+
+            let vertex = &mut graph.vertices[idx];
+            let Some(view) = graph_view.vertices.get_mut(idx) else {
+                continue;
+            };
+
             if response.drag_started() {
                 view.is_pressed = true;
                 view.z_index = *next_z_index;
@@ -421,7 +733,11 @@ fn update_vertex_interactions(app: &mut GraphEditorApp, ui: &egui::Ui) {
                 }
             } else if response.dragged() {
                 if let Some(mouse_pos) = response.hover_pos() {
+                    let before = vertex.get_position();
                     vertex.update_position(mouse_pos.applied(&view.drag));
+                    if view.is_selected && edit_mode == &EditMode::Normal {
+                        group_drag = Some((idx, vertex.get_position() - before));
+                    }
                 }
             } else {
                 view.is_pressed = false;
@@ -441,6 +757,10 @@ fn update_vertex_interactions(app: &mut GraphEditorApp, ui: &egui::Ui) {
                 *next_z_index += 1;
 
                 match edit_mode {
+                    EditMode::Normal if is_command => {
+                        // Ctrl + クリックで選択を切り替える
+                        view.is_selected ^= true;
+                    }
                     EditMode::Normal => {
                         view.is_selected = false;
                         app.ui.edit_target = Some(EditTarget::Vertex(idx));
@@ -455,13 +775,14 @@ fn update_vertex_interactions(app: &mut GraphEditorApp, ui: &egui::Ui) {
                         ref mut confirmed,
                     } => {
                         if let Some(from_vertex_inner) = from_vertex {
-                            if *from_vertex_inner == vertex.id {
+                            if *from_vertex_inner == vertex.id && !is_shift {
                                 view.is_selected = false;
                                 *from_vertex = None;
                             } else {
-                                let added = Graph::add_unique_edge(
+                                let added = Graph::try_add_edge(
                                     is_directed,
-                                    edges_mut,
+                                    allows_multi_edges,
+                                    &mut graph.edges,
                                     *from_vertex_inner,
                                     vertex.id,
                                 );
@@ -488,6 +809,20 @@ fn update_vertex_interactions(app: &mut GraphEditorApp, ui: &egui::Ui) {
         }
     }
 
+    // 選択中の他の頂点も同じだけ移動させる
+    if let Some((dragged_idx, delta)) = group_drag {
+        for (idx, (vertex, view)) in graph
+            .vertices
+            .iter_mut()
+            .zip(&graph_view.vertices)
+            .enumerate()
+        {
+            if idx != dragged_idx && view.is_selected && !vertex.is_deleted {
+                vertex.update_position(vertex.get_position() + delta);
+            }
+        }
+    }
+
     if let EditMode::AddEdge {
         from_vertex: ref mut from_vertex @ Some(from_vertex_inner),
         confirmed: ref mut confirmed @ true,
@@ -509,13 +844,13 @@ fn render_edges(snapshot: &GraphSnapshot, painter: &egui::Painter, config: &AppC
         .map(|v| (v.id, v.position))
         .collect();
 
-    let edge_count = snapshot.edges.iter().fold(HashMap::new(), |mut map, edge| {
-        *map.entry((edge.from, edge.to)).or_insert(0) += 1;
-        *map.entry((edge.to, edge.from)).or_insert(0) += 1;
-        map
-    });
+    let offsets = parallel_edge_offsets(snapshot.edges.iter().map(|edge| (edge.from, edge.to)));
+    let loop_directions = self_loop_directions(
+        &vertex_positions,
+        snapshot.edges.iter().map(|edge| (edge.from, edge.to)),
+    );
 
-    for edge in snapshot.edges.iter() {
+    for (edge, &edge_offset) in snapshot.edges.iter().zip(&offsets) {
         let (Some(&from_pos), Some(&to_pos)) = (
             vertex_positions.get(&edge.from),
             vertex_positions.get(&edge.to),
@@ -524,26 +859,55 @@ fn render_edges(snapshot: &GraphSnapshot, painter: &egui::Painter, config: &AppC
         };
 
         let edge_color = if edge.is_pressed {
-            config.edge_color_hover
+            config.theme().edge_color_hover
+        } else if edge.is_selected {
+            config.theme().edge_color_selected
         } else {
-            edge.color.edge()
+            config.theme().edge_stroke(edge.color)
         };
         let stroke_width = edge.stroke_width.unwrap_or(config.edge_stroke);
-        let target_radius = snapshot
-            .vertices
-            .iter()
-            .find(|vertex| vertex.id == edge.to)
-            .and_then(|vertex| vertex.radius)
-            .unwrap_or(config.effective_vertex_radius(snapshot.vertices.len()));
+        let vertex_radius = |id: usize| {
+            snapshot
+                .vertices
+                .iter()
+                .find(|vertex| vertex.id == id)
+                .and_then(|vertex| vertex.radius)
+                .unwrap_or(config.effective_vertex_radius(snapshot.vertices.len()))
+        };
+        let target_radius = vertex_radius(edge.to);
+
+        let is_curved = edge_offset != 0.0;
+        let bezier_distance = config.edge_bezier_distance * edge_offset;
+        let self_loop = (edge.from == edge.to).then(|| {
+            self_loop_points(
+                from_pos,
+                target_radius,
+                loop_directions[&edge.from],
+                nested_self_loop_size(config.self_loop_size, edge_offset),
+            )
+        });
 
-        if snapshot.is_directed {
-            if edge_count.get(&(edge.from, edge.to)) == Some(&1) {
+        if let Some(points) = self_loop {
+            draw_self_loop(
+                painter,
+                points,
+                edge_color,
+                stroke_width,
+                edge.direction,
+                config,
+            );
+        } else if edge.direction != EdgeDirection::Undirected {
+            // 両向きの辺は始点側にも矢印を付ける
+            let source_radius =
+                (edge.direction == EdgeDirection::Bidirectional).then(|| vertex_radius(edge.from));
+            if !is_curved {
                 draw_edge_directed(
                     painter,
                     from_pos,
                     to_pos,
                     edge_color,
                     stroke_width,
+                    source_radius,
                     target_radius,
                     config,
                 );
@@ -552,18 +916,68 @@ fn render_edges(snapshot: &GraphSnapshot, painter: &egui::Painter, config: &AppC
                     painter,
                     from_pos,
                     to_pos,
+                    bezier_distance,
                     edge_color,
                     stroke_width,
+                    source_radius,
                     target_radius,
                     config,
                 );
             }
+        } else if is_curved {
+            draw_edge_undirected_curved(
+                painter,
+                from_pos,
+                to_pos,
+                bezier_distance,
+                stroke_width,
+                edge_color,
+            );
         } else {
             draw_edge_undirected(painter, from_pos, to_pos, stroke_width, edge_color);
         }
+
+        let text_color = edge.text_color.unwrap_or(config.theme().vertex_font_color);
+
+        // 辺の重みを中点に描画
+        if let Some(weight) = edge.weight.filter(|_| snapshot.is_weighted) {
+            let position = match self_loop {
+                Some(points) => cubic_bezier_curve(points, 0.5),
+                None => edge_midpoint(from_pos, to_pos, is_curved, bezier_distance),
+            };
+            draw_edge_text(painter, position, weight.to_string(), text_color, config);
+        }
+
+        // 辺のラベルを辺の脇に描画
+        if let Some(label) = edge.label.as_ref().filter(|label| !label.is_empty()) {
+            let offset = config.edge_label_font_size + stroke_width;
+            let position = match self_loop {
+                Some(points) => self_loop_side_point(from_pos, points, offset),
+                None => edge_side_point(from_pos, to_pos, is_curved, bezier_distance, offset),
+            };
+            draw_edge_text(painter, position, label.clone(), text_color, config);
+        }
     }
 }
 
+/// 辺に付随する文字列を背景付きで描画する
+fn draw_edge_text(
+    painter: &egui::Painter,
+    position: egui::Pos2,
+    text: String,
+    color: egui::Color32,
+    config: &AppConfig,
+) {
+    let galley = painter.layout_no_wrap(
+        text,
+        egui::FontId::proportional(config.edge_label_font_size),
+        color,
+    );
+    let rect = egui::Align2::CENTER_CENTER.anchor_size(position, galley.size());
+    painter.rect_filled(rect.expand(2.0), 4.0, config.theme().bg_color);
+    painter.galley(rect.min, galley, color);
+}
+
 /// central_panel に頂点を描画する
 fn render_vertices(
     snapshot: &GraphSnapshot,
@@ -590,7 +1004,7 @@ fn render_vertices(
         if let (Some(from_pos), Some(mouse_pos)) = (from_pos, ui.input(|i| i.pointer.hover_pos())) {
             painter.line_segment(
                 [from_pos, mouse_pos],
-                egui::Stroke::new(app.config.edge_stroke, Colors::Default.edge()),
+                egui::Stroke::new(app.config.edge_stroke, app.config.theme().edge_color),
             );
         }
     }
@@ -601,19 +1015,33 @@ fn render_vertices(
             .unwrap_or(app.config.effective_vertex_radius(snapshot.vertices.len()));
         let vertex_stroke = vertex.stroke_width.unwrap_or(app.config.vertex_stroke);
         let color = if vertex.is_selected {
-            app.config.vertex_color_selected
+            app.config.theme().vertex_color_selected
         } else if vertex.is_pressed {
-            app.config.vertex_color_dragged
+            app.config.theme().vertex_color_dragged
         } else {
-            vertex.color.vertex()
+            app.config.theme().vertex_fill(vertex.color)
         };
 
         painter.circle_filled(vertex.position, vertex_radius, color);
+        let outline = vertex
+            .stroke_color
+            .unwrap_or(app.config.theme().vertex_color_outline);
         painter.circle_stroke(
             vertex.position,
             vertex_radius,
-            egui::Stroke::new(vertex_stroke, app.config.vertex_color_outline),
+            egui::Stroke::new(vertex_stroke, outline),
         );
+        if vertex.is_pinned {
+            // 固定された頂点は右上に小さな印を付ける
+            let marker = vertex.position + egui::vec2(vertex_radius, -vertex_radius) * 0.7;
+            let marker_radius = (vertex_radius * 0.3).max(2.0);
+            painter.circle_filled(marker, marker_radius, outline);
+            painter.circle_stroke(
+                marker,
+                marker_radius,
+                egui::Stroke::new(vertex_stroke, color),
+            );
+        }
         if app.state.show_number {
             let vertex_show_id = vertex.label.clone().unwrap_or_else(|| {
                 if app.state.zero_indexed {
@@ -635,3 +1063,21 @@ fn render_vertices(
         }
     }
 }
+
+/// 範囲選択中の矩形を描画する
+fn render_marquee(app: &GraphEditorApp, ui: &egui::Ui, painter: &egui::Painter) {
+    let (Some(start), Some(end)) = (app.ui.marquee_start, ui.input(|i| i.pointer.latest_pos()))
+    else {
+        return;
+    };
+
+    let rect = egui::Rect::from_two_pos(start, end);
+    let color = app.config.theme().selection_rect_color;
+    painter.rect_filled(rect, 0.0, color.gamma_multiply(0.15));
+    painter.rect_stroke(
+        rect,
+        0.0,
+        egui::Stroke::new(1.5, color),
+        egui::StrokeKind::Inside,
+    );
+}
@@ -13,12 +13,49 @@ pub enum Colors {
     Cyan,
     Indigo,
     Gray,
+    /// パレットにない任意の色
+    Custom(egui::Color32),
 }
 
 impl Colors {
-    fn to_egui_color(self) -> Option<egui::Color32> {
+    /// 既定のパレット
+    pub const PALETTE: [Colors; 12] = [
+        Colors::Default,
+        Colors::Red,
+        Colors::Green,
+        Colors::Blue,
+        Colors::Yellow,
+        Colors::Orange,
+        Colors::Violet,
+        Colors::Pink,
+        Colors::Brown,
+        Colors::Cyan,
+        Colors::Indigo,
+        Colors::Gray,
+    ];
+
+    /// 既定のパレットに同じ色があればその色を，なければ任意の色として返す
+    pub fn from_rgb(color: egui::Color32) -> Self {
+        Self::PALETTE
+            .into_iter()
+            .find(|candidate| candidate.to_egui_color() == Some(color))
+            .unwrap_or(Self::Custom(color))
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Colors::Custom(color) => {
+                format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+            }
+            color => format!("{color:?}"),
+        }
+    }
+
+    /// 色の値．`Colors::Default`は描画する対象によって色が変わるため`None`
+    pub fn to_egui_color(self) -> Option<egui::Color32> {
         match self {
             Colors::Default => None,
+            Colors::Custom(color) => Some(color),
             Colors::Red => Some(egui::Color32::from_rgb(255, 70, 70)),
             Colors::Green => Some(egui::Color32::from_rgb(70, 255, 70)),
             Colors::Blue => Some(egui::Color32::from_rgb(70, 70, 255)),
@@ -43,6 +80,43 @@ impl Colors {
     }
 }
 
+/// 色見本のボタンを描画する．選択中の色は枠を濃くする
+pub(super) fn color_swatch(
+    ui: &mut egui::Ui,
+    color: Colors,
+    is_selected: bool,
+    size: f32,
+) -> egui::Response {
+    let stroke_color = if is_selected {
+        egui::Color32::BLACK
+    } else {
+        egui::Color32::from_gray(120)
+    };
+    ui.add(
+        egui::Button::new("")
+            .min_size(egui::vec2(size, size))
+            .fill(color.vertex())
+            .stroke(egui::Stroke::new(2.0, stroke_color)),
+    )
+    .on_hover_text(color.name())
+}
+
+/// パレットにない色を選ぶカラーピッカーを描画する．選んだ色が変わった場合は`true`を返す
+pub(super) fn draw_custom_color_picker(ui: &mut egui::Ui, color: &mut Colors) -> bool {
+    let mut rgb = color.vertex();
+    let changed = egui::color_picker::color_edit_button_srgba(
+        ui,
+        &mut rgb,
+        egui::color_picker::Alpha::Opaque,
+    )
+    .on_hover_text("Custom color")
+    .changed();
+    if changed {
+        *color = Colors::from_rgb(rgb);
+    }
+    changed
+}
+
 pub fn default_vertex_text_color(fill: egui::Color32) -> egui::Color32 {
     let luma = 0.2126 * fill.r() as f32 + 0.7152 * fill.g() as f32 + 0.0722 * fill.b() as f32;
     if luma < 140.0 {
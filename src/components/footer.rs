@@ -1,6 +1,6 @@
 use egui::Context;
 
-use crate::{config::APP_VERSION, mode::EditMode, GraphEditorApp};
+use crate::{analysis::player::SPEED_RANGE, config::APP_VERSION, mode::EditMode, GraphEditorApp};
 
 /// フッターを描画する
 pub fn draw_footer(app: &mut GraphEditorApp, ctx: &Context) {
@@ -28,12 +28,22 @@ pub fn draw_footer(app: &mut GraphEditorApp, ctx: &Context) {
     } else {
         "Undirected"
     };
+    let weight_label = if app.state.graph.is_weighted {
+        "Weighted"
+    } else {
+        "Unweighted"
+    };
 
     egui::TopBottomPanel::bottom("footer_panel").show(ctx, |ui| {
         app.ui
             .cursor_hover
             .set_footer_panel(ui.rect_contains_pointer(ui.max_rect()));
 
+        if app.ui.algorithm_player.is_some() {
+            draw_algorithm_player(app, ui);
+            ui.separator();
+        }
+
         ui.horizontal(|ui| {
             if ui
                 .button(egui::RichText::new("⚙").size(app.config.footer_font_size()))
@@ -45,10 +55,11 @@ pub fn draw_footer(app: &mut GraphEditorApp, ctx: &Context) {
             ui.separator();
             ui.label(
                 egui::RichText::new(format!(
-                    "Mode: {} | {} | {} | vertices: {} | edges: {}",
+                    "Mode: {} | {} | {} | {} | vertices: {} | edges: {}",
                     edit_mode_label(&app.state.edit_mode),
                     indexing_label,
                     direction_label,
+                    weight_label,
                     vertex_count,
                     edge_count
                 ))
@@ -86,6 +97,95 @@ pub fn draw_footer(app: &mut GraphEditorApp, ctx: &Context) {
     });
 }
 
+/// アルゴリズムの再生操作を描画する
+fn draw_algorithm_player(app: &mut GraphEditorApp, ui: &mut egui::Ui) {
+    let font_size = app.config.footer_font_size();
+    let description = app
+        .ui
+        .algorithm_player
+        .as_ref()
+        .map(|player| player.describe_current(&app.state.graph, app.state.zero_indexed))
+        .unwrap_or_default();
+    let mut close = false;
+
+    ui.horizontal(|ui| {
+        let Some(player) = &mut app.ui.algorithm_player else {
+            return;
+        };
+        ui.label(
+            egui::RichText::new(player.kind().name())
+                .size(font_size)
+                .strong(),
+        );
+        ui.separator();
+
+        if ui
+            .button(egui::RichText::new("⏮").size(font_size))
+            .on_hover_text("Rewind")
+            .clicked()
+        {
+            player.rewind();
+        }
+        if ui
+            .button(egui::RichText::new("◀").size(font_size))
+            .on_hover_text("Step back")
+            .clicked()
+        {
+            player.step_back();
+        }
+        let play_label = if player.is_playing { "⏸" } else { "▶" };
+        if ui
+            .button(egui::RichText::new(play_label).size(font_size))
+            .on_hover_text("Play / Pause")
+            .clicked()
+        {
+            player.toggle_playing();
+        }
+        if ui
+            .button(egui::RichText::new("▶|").size(font_size))
+            .on_hover_text("Step forward")
+            .clicked()
+        {
+            player.step_forward();
+        }
+        if ui
+            .add(
+                egui::Slider::new(&mut player.speed, SPEED_RANGE)
+                    .logarithmic(true)
+                    .suffix(" /s"),
+            )
+            .on_hover_text("Playback speed (events per second)")
+            .changed()
+        {
+            app.ui.algorithm_speed = player.speed;
+        }
+        ui.separator();
+        ui.label(
+            egui::RichText::new(format!(
+                "{} / {} | {}",
+                player.position(),
+                player.len(),
+                description
+            ))
+            .size(font_size),
+        );
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .button(egui::RichText::new("✖").size(font_size))
+                .on_hover_text("Close player and clear the result")
+                .clicked()
+            {
+                close = true;
+            }
+        });
+    });
+
+    if close {
+        app.clear_algorithm_result();
+    }
+}
+
 fn edit_mode_label(mode: &EditMode) -> &'static str {
     match mode {
         EditMode::Normal => "Normal",
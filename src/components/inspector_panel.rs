@@ -1,13 +1,20 @@
 use egui::{text::LayoutJob, Color32, Context, FontId, TextFormat};
 
+use super::{modal::draw_color_palette, Colors};
 use crate::{
-    graph::BaseGraph, project_io::import_graph_from_json, state::IoFormat, GraphEditorApp,
+    analysis::AlgorithmKind,
+    graph::{visualize_methods, BaseGraph, GraphTextFormat},
+    history::{CoalesceKey, HistoryEntry},
+    project_io::{dot::import_graph_from_dot, import_graph_from_json},
+    state::IoFormat,
+    GraphEditorApp,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum InspectorTab {
     #[default]
     Graph,
+    Algorithms,
     Io,
 }
 
@@ -21,7 +28,7 @@ pub fn draw_inspector_panel(app: &mut GraphEditorApp, ctx: &Context) {
                 .cursor_hover
                 .set_inspector_panel(ui.rect_contains_pointer(ui.max_rect()));
 
-            ui.columns(2, |columns| {
+            ui.columns(3, |columns| {
                 draw_tab_button(
                     &mut columns[0],
                     app.ui.inspector_tab == InspectorTab::Graph,
@@ -31,6 +38,13 @@ pub fn draw_inspector_panel(app: &mut GraphEditorApp, ctx: &Context) {
                 );
                 draw_tab_button(
                     &mut columns[1],
+                    app.ui.inspector_tab == InspectorTab::Algorithms,
+                    "Algo",
+                    app.config.tab_font_size(),
+                    || app.ui.inspector_tab = InspectorTab::Algorithms,
+                );
+                draw_tab_button(
+                    &mut columns[2],
                     app.ui.inspector_tab == InspectorTab::Io,
                     "I/O",
                     app.config.tab_font_size(),
@@ -41,6 +55,7 @@ pub fn draw_inspector_panel(app: &mut GraphEditorApp, ctx: &Context) {
 
             match app.ui.inspector_tab {
                 InspectorTab::Graph => draw_graph_tab(app, ctx, ui),
+                InspectorTab::Algorithms => draw_algorithms_tab(app, ui),
                 InspectorTab::Io => draw_io_tab(app, ctx, ui),
             }
         });
@@ -73,19 +88,103 @@ fn draw_graph_tab(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui) {
             .strong()
             .size(app.config.section_font_size()),
     );
+    let is_directed = app.state.graph.is_directed;
     draw_toggle_button(
         ui,
-        !app.state.graph.is_directed,
+        !is_directed,
         "Undirected",
         app.config.button_font_size(),
-        || app.state.graph.is_directed = false,
+        || {
+            if is_directed {
+                app.record_history();
+                app.state.graph.is_directed = false;
+            }
+        },
     );
     draw_toggle_button(
         ui,
-        app.state.graph.is_directed,
+        is_directed,
         "Directed",
         app.config.button_font_size(),
-        || app.state.graph.is_directed = true,
+        || {
+            if !is_directed {
+                app.record_history();
+                app.state.graph.is_directed = true;
+            }
+        },
+    );
+
+    ui.separator();
+    ui.label(
+        egui::RichText::new("Weight")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    let is_weighted = app.state.graph.is_weighted;
+    draw_toggle_button(
+        ui,
+        !is_weighted,
+        "Unweighted",
+        app.config.button_font_size(),
+        || {
+            if is_weighted {
+                app.record_history();
+                app.state.graph.is_weighted = false;
+            }
+        },
+    );
+    draw_toggle_button(
+        ui,
+        is_weighted,
+        "Weighted",
+        app.config.button_font_size(),
+        || {
+            if !is_weighted {
+                app.record_history();
+                app.state.graph.is_weighted = true;
+            }
+        },
+    );
+
+    ui.separator();
+    ui.label(
+        egui::RichText::new("Multi-edges")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    let allows_multi_edges = app.state.graph.allows_multi_edges;
+    // 多重辺が残っているうちは単純グラフに切り替えられない
+    let has_parallel_edges = allows_multi_edges && app.state.graph.has_parallel_edges();
+    let response = ui
+        .add_enabled_ui(!has_parallel_edges, |ui| {
+            draw_toggle_button(
+                ui,
+                !allows_multi_edges,
+                "Simple",
+                app.config.button_font_size(),
+                || {
+                    if allows_multi_edges {
+                        app.record_history();
+                        app.state.graph.allows_multi_edges = false;
+                    }
+                },
+            )
+        })
+        .inner;
+    if has_parallel_edges {
+        response.on_disabled_hover_text("Remove the parallel edges first");
+    }
+    draw_toggle_button(
+        ui,
+        allows_multi_edges,
+        "Multigraph",
+        app.config.button_font_size(),
+        || {
+            if !allows_multi_edges {
+                app.record_history();
+                app.state.graph.allows_multi_edges = true;
+            }
+        },
     );
 
     ui.separator();
@@ -109,20 +208,23 @@ fn draw_graph_tab(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui) {
 
     if ui
         .add_enabled(
-            app.state.graph.is_directed,
+            app.state.graph.has_directed_edges(),
             egui::Button::new(
                 egui::RichText::new("Revert Edge").size(app.config.button_font_size()),
             ),
         )
         .clicked()
     {
-        app.rebuild_from_base_graph(ctx, app.state.graph.calc_reverted());
+        app.record_history();
+        app.state.graph.revert_edges();
+        app.sync_io_texts_from_graph();
     }
 
     if ui
         .button(egui::RichText::new("Remove Color").size(app.config.button_font_size()))
         .clicked()
     {
+        app.record_history();
         app.state.graph_view.remove_color();
     }
 
@@ -130,9 +232,18 @@ fn draw_graph_tab(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui) {
         .button(egui::RichText::new("Remove Label").size(app.config.button_font_size()))
         .clicked()
     {
+        app.record_history();
         app.state.graph_view.remove_label();
     }
 
+    ui.separator();
+    draw_layout_section(app, ctx, ui);
+
+    if app.state.graph_view.has_selection() {
+        ui.separator();
+        draw_selection_section(app, ui);
+    }
+
     ui.separator();
     ui.label(
         egui::RichText::new("Danger Zone")
@@ -147,17 +258,335 @@ fn draw_graph_tab(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui) {
     }
 }
 
+fn draw_layout_section(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui) {
+    ui.label(
+        egui::RichText::new("Layout")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    let n = app.state.graph.vertices.len();
+    let offset = if app.state.zero_indexed { 0 } else { 1 };
+    let mut root = app
+        .config
+        .visualizer_config
+        .tree_root
+        .min(n.saturating_sub(1))
+        + offset;
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::DragValue::new(&mut root)
+                .range(offset..=(n + offset).saturating_sub(1).max(offset))
+                .prefix("root: "),
+        );
+        let selected = app
+            .state
+            .graph_view
+            .vertices
+            .iter()
+            .position(|view| view.is_selected);
+        if ui
+            .add_enabled(selected.is_some(), egui::Button::new("Use Selected"))
+            .clicked()
+        {
+            if let Some(selected) = selected {
+                root = selected + offset;
+            }
+        }
+    });
+    app.config.visualizer_config.tree_root = root - offset;
+
+    let edges: Vec<_> = app
+        .state
+        .graph
+        .edges
+        .iter()
+        .map(|edge| (edge.from, edge.to))
+        .collect();
+    let is_forest = n > 0 && visualize_methods::is_forest(n, &edges);
+    let response = ui.add_enabled(
+        is_forest,
+        egui::Button::new(
+            egui::RichText::new("Arrange as Tree").size(app.config.button_font_size()),
+        ),
+    );
+    if response.clicked() {
+        app.arrange_as_tree(ctx, root - offset);
+    }
+    if !is_forest {
+        response.on_disabled_hover_text("Requires a tree or forest");
+    }
+
+    let has_directed_edges = app.state.graph.has_directed_edges();
+    let response = ui.add_enabled(
+        has_directed_edges && n > 0,
+        egui::Button::new(
+            egui::RichText::new("Arrange as DAG").size(app.config.button_font_size()),
+        ),
+    );
+    if response.clicked() {
+        app.arrange_as_dag(ctx);
+    }
+    if !has_directed_edges {
+        response.on_disabled_hover_text("Requires directed edges");
+    }
+}
+
+fn draw_algorithms_tab(app: &mut GraphEditorApp, ui: &mut egui::Ui) {
+    ui.label(
+        egui::RichText::new("Algorithm")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    let has_directed_edges = app.state.graph.has_directed_edges();
+    for kind in AlgorithmKind::ALL {
+        let enabled = !kind.requires_directed() || has_directed_edges;
+        let response = ui
+            .add_enabled_ui(enabled, |ui| {
+                ui.radio_value(
+                    &mut app.ui.algorithm_kind,
+                    kind,
+                    egui::RichText::new(kind.name()).size(app.config.body_font_size()),
+                )
+            })
+            .inner;
+        if !enabled {
+            response.on_disabled_hover_text("Requires directed edges");
+        }
+    }
+
+    if app.ui.algorithm_kind.needs_source() {
+        ui.separator();
+        ui.label(
+            egui::RichText::new("Source")
+                .strong()
+                .size(app.config.section_font_size()),
+        );
+        let n = app.state.graph.vertices.len();
+        let offset = if app.state.zero_indexed { 0 } else { 1 };
+        let mut source = app.ui.algorithm_source.min(n.saturating_sub(1)) + offset;
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut source)
+                    .range(offset..=(n + offset).saturating_sub(1).max(offset))
+                    .prefix("vertex: "),
+            );
+            let selected = app
+                .state
+                .graph_view
+                .vertices
+                .iter()
+                .position(|view| view.is_selected);
+            if ui
+                .add_enabled(selected.is_some(), egui::Button::new("Use Selected"))
+                .clicked()
+            {
+                if let Some(selected) = selected {
+                    source = selected + offset;
+                }
+            }
+        });
+        app.ui.algorithm_source = source - offset;
+    }
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        if ui
+            .button(egui::RichText::new("Run").size(app.config.button_font_size()))
+            .clicked()
+        {
+            app.run_selected_algorithm();
+        }
+        if ui
+            .add_enabled(
+                app.ui.algorithm_kind.is_traceable(),
+                egui::Button::new(
+                    egui::RichText::new("Animate").size(app.config.button_font_size()),
+                ),
+            )
+            .on_hover_text("Replay the algorithm step by step in the footer")
+            .clicked()
+        {
+            app.start_algorithm_animation();
+        }
+        if ui
+            .add_enabled(
+                app.state.graph_view.has_highlight(),
+                egui::Button::new(
+                    egui::RichText::new("Clear Result").size(app.config.button_font_size()),
+                ),
+            )
+            .clicked()
+        {
+            app.clear_algorithm_result();
+        }
+    });
+
+    if let Some(summary) = &app.ui.algorithm_summary {
+        ui.separator();
+        ui.label(
+            egui::RichText::new("Result")
+                .strong()
+                .size(app.config.section_font_size()),
+        );
+        ui.label(egui::RichText::new(summary).size(app.config.body_font_size()));
+    }
+}
+
+/// 選択中の頂点・辺に対する一括操作
+fn draw_selection_section(app: &mut GraphEditorApp, ui: &mut egui::Ui) {
+    let view = &app.state.graph_view;
+    let selected_vertices: Vec<usize> = (0..view.vertices.len())
+        .filter(|&i| view.vertices[i].is_selected)
+        .collect();
+    let selected_edges: Vec<usize> = (0..view.edges.len())
+        .filter(|&i| view.edges[i].is_selected)
+        .collect();
+
+    ui.label(
+        egui::RichText::new("Selection")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    ui.label(format!(
+        "{} vertices, {} edges",
+        selected_vertices.len(),
+        selected_edges.len()
+    ));
+
+    // 色の変更
+    let mut color = selected_vertices
+        .first()
+        .map(|&i| view.vertices[i].color)
+        .or_else(|| selected_edges.first().map(|&i| view.edges[i].color))
+        .unwrap_or(Colors::Default);
+    let before_color = color;
+    draw_color_palette(ui, &mut color, &app.config.custom_palette);
+    if color != before_color {
+        app.record_history();
+        for &i in &selected_vertices {
+            app.state.graph_view.vertices[i].color = color;
+        }
+        for &i in &selected_edges {
+            app.state.graph_view.edges[i].color = color;
+        }
+    }
+
+    // 大きさ・線幅の変更（ドラッグ中の変更は 1 つの履歴にまとめる）
+    let view = &app.state.graph_view;
+    if let Some(&first) = selected_vertices.first() {
+        let mut radius = view.vertices[first]
+            .radius
+            .unwrap_or(app.config.vertex_radius);
+        let response = ui.add(
+            egui::DragValue::new(&mut radius)
+                .speed(0.5)
+                .range(1.0..=f32::MAX)
+                .prefix("radius: "),
+        );
+        if response.changed() {
+            record_selection_style(app);
+            for &i in &selected_vertices {
+                app.state.graph_view.vertices[i].radius = Some(radius);
+            }
+        }
+        finish_selection_style(app, &response);
+
+        let mut stroke = app.state.graph_view.vertices[first]
+            .stroke_width
+            .unwrap_or(app.config.vertex_stroke);
+        let response = ui.add(
+            egui::DragValue::new(&mut stroke)
+                .speed(0.25)
+                .range(0.0..=f32::MAX)
+                .prefix("stroke: "),
+        );
+        if response.changed() {
+            record_selection_style(app);
+            for &i in &selected_vertices {
+                app.state.graph_view.vertices[i].stroke_width = Some(stroke);
+            }
+        }
+        finish_selection_style(app, &response);
+    }
+    if let Some(&first) = selected_edges.first() {
+        let mut width = app.state.graph_view.edges[first]
+            .stroke_width
+            .unwrap_or(app.config.edge_stroke);
+        let response = ui.add(
+            egui::DragValue::new(&mut width)
+                .speed(0.25)
+                .range(0.0..=f32::MAX)
+                .prefix("width: "),
+        );
+        if response.changed() {
+            record_selection_style(app);
+            for &i in &selected_edges {
+                app.state.graph_view.edges[i].stroke_width = Some(width);
+            }
+        }
+        finish_selection_style(app, &response);
+    }
+
+    if ui
+        .button(egui::RichText::new("Copy").size(app.config.button_font_size()))
+        .on_hover_text("Ctrl + C")
+        .clicked()
+    {
+        app.copy_selection(ui.ctx());
+    }
+    if ui
+        .button(egui::RichText::new("Duplicate").size(app.config.button_font_size()))
+        .on_hover_text("Ctrl + D")
+        .clicked()
+    {
+        app.duplicate_selection();
+    }
+    if ui
+        .button(egui::RichText::new("Select Neighbors").size(app.config.button_font_size()))
+        .clicked()
+    {
+        app.select_neighbors();
+    }
+    if ui
+        .button(egui::RichText::new("Delete Selected").size(app.config.button_font_size()))
+        .clicked()
+    {
+        app.delete_selection();
+    }
+    if ui
+        .button(egui::RichText::new("Clear Selection").size(app.config.button_font_size()))
+        .clicked()
+    {
+        app.state.graph_view.clear_selection();
+    }
+}
+
+/// 選択範囲のスタイル変更前の状態を記録する
+fn record_selection_style(app: &mut GraphEditorApp) {
+    let entry = HistoryEntry::capture(&app.state.graph, &app.state.graph_view);
+    app.state
+        .history
+        .record_coalesced(CoalesceKey::SelectionStyle, entry);
+}
+
+/// ドラッグや入力が終わったら連続編集のまとまりを区切る
+fn finish_selection_style(app: &mut GraphEditorApp, response: &egui::Response) {
+    if response.drag_stopped() || response.lost_focus() {
+        app.state.history.break_coalescing();
+    }
+}
+
 fn draw_io_tab(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui) {
     const GRAPH_TEXT_EDITOR_HEIGHT: f32 = 280.0;
 
     ui.horizontal(|ui| {
         draw_text_mode_button(
             ui,
-            app.ui.io_format == IoFormat::EdgeList,
-            "Edge List",
+            app.ui.io_format == IoFormat::Text,
+            "Text",
             app.config.body_font_size(),
             || {
-                app.ui.io_format = IoFormat::EdgeList;
+                app.ui.io_format = IoFormat::Text;
                 if !app.ui.input_is_dirty {
                     app.sync_input_text_from_graph();
                 }
@@ -175,12 +604,25 @@ fn draw_io_tab(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui) {
                 }
             },
         );
+        draw_text_mode_button(
+            ui,
+            app.ui.io_format == IoFormat::Dot,
+            "DOT",
+            app.config.body_font_size(),
+            || {
+                app.ui.io_format = IoFormat::Dot;
+                if !app.ui.dot_is_dirty {
+                    app.sync_dot_text_from_graph();
+                }
+            },
+        );
     });
     ui.separator();
 
     match app.ui.io_format {
-        IoFormat::EdgeList => draw_edge_list_io(app, ctx, ui, GRAPH_TEXT_EDITOR_HEIGHT),
+        IoFormat::Text => draw_graph_text_io(app, ctx, ui, GRAPH_TEXT_EDITOR_HEIGHT),
         IoFormat::Json => draw_json_io(app, ctx, ui, GRAPH_TEXT_EDITOR_HEIGHT),
+        IoFormat::Dot => draw_dot_io(app, ctx, ui, GRAPH_TEXT_EDITOR_HEIGHT),
     }
 
     ui.separator();
@@ -200,6 +642,9 @@ fn draw_io_tab(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui) {
                 crate::export::ExportFormat::Svg => {
                     egui::RichText::new("SVG").size(app.config.body_font_size())
                 }
+                crate::export::ExportFormat::Tikz => {
+                    egui::RichText::new("TikZ").size(app.config.body_font_size())
+                }
             })
             .show_ui(ui, |ui| {
                 ui.selectable_value(
@@ -212,22 +657,56 @@ fn draw_io_tab(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui) {
                     crate::export::ExportFormat::Svg,
                     egui::RichText::new("SVG").size(app.config.body_font_size()),
                 );
+                ui.selectable_value(
+                    &mut format,
+                    crate::export::ExportFormat::Tikz,
+                    egui::RichText::new("TikZ").size(app.config.body_font_size()),
+                );
             });
         app.export.set_format(format);
     });
 
+    if app.export.format() == crate::export::ExportFormat::Png {
+        let body_font_size = app.config.body_font_size();
+        let png_options = app.export.png_options_mut();
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Scale").size(body_font_size));
+            ui.add(
+                egui::DragValue::new(&mut png_options.scale)
+                    .range(crate::export::PngOptions::SCALE_RANGE)
+                    .speed(0.05)
+                    .fixed_decimals(2)
+                    .suffix("x"),
+            );
+            ui.label(
+                egui::RichText::new(format!("({:.0} dpi)", png_options.dpi()))
+                    .size(body_font_size)
+                    .weak(),
+            );
+        });
+        ui.checkbox(
+            &mut png_options.transparent_background,
+            egui::RichText::new("Transparent Background").size(body_font_size),
+        );
+    }
+
+    if app.export.format() == crate::export::ExportFormat::Tikz {
+        let body_font_size = app.config.body_font_size();
+        ui.checkbox(
+            &mut app.export.tikz_options_mut().standalone,
+            egui::RichText::new("Standalone Document").size(body_font_size),
+        );
+    }
+
     if ui
-        .add_enabled(
-            !app.export.is_busy(),
-            egui::Button::new(egui::RichText::new("Export").size(app.config.button_font_size())),
-        )
+        .button(egui::RichText::new("Export").size(app.config.button_font_size()))
         .clicked()
     {
-        app.request_export_image(ctx);
+        app.request_export_image();
     }
 }
 
-fn draw_edge_list_io(
+fn draw_graph_text_io(
     app: &mut GraphEditorApp,
     ctx: &Context,
     ui: &mut egui::Ui,
@@ -242,6 +721,50 @@ fn draw_edge_list_io(
             .strong()
             .size(app.config.section_font_size()),
     );
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Format").size(app.config.body_font_size()));
+        let mut format = app.ui.text_format;
+        egui::ComboBox::from_id_salt("inspector_text_format")
+            .selected_text(egui::RichText::new(format.name()).size(app.config.body_font_size()))
+            .show_ui(ui, |ui| {
+                for candidate in GraphTextFormat::ALL {
+                    ui.selectable_value(
+                        &mut format,
+                        candidate,
+                        egui::RichText::new(candidate.name()).size(app.config.body_font_size()),
+                    );
+                }
+            });
+        if format != app.ui.text_format {
+            app.ui.text_format = format;
+            if !app.ui.input_is_dirty {
+                app.sync_input_text_from_graph();
+            }
+        }
+    });
+    if app.ui.text_format == GraphTextFormat::EdgeList {
+        egui::CollapsingHeader::new(
+            egui::RichText::new("Options").size(app.config.body_font_size()),
+        )
+        .id_salt("edge_list_options")
+        .default_open(false)
+        .show(ui, |ui| {
+            let options = &mut app.ui.edge_list_options;
+            ui.checkbox(
+                &mut options.detect_indexing,
+                egui::RichText::new("Detect 0-indexed / 1-indexed")
+                    .size(app.config.body_font_size()),
+            );
+            ui.checkbox(
+                &mut options.detect_edge_count,
+                egui::RichText::new("Allow omitting M").size(app.config.body_font_size()),
+            );
+            ui.checkbox(
+                &mut options.ignore_trailing,
+                egui::RichText::new("Ignore trailing lines").size(app.config.body_font_size()),
+            );
+        });
+    }
     ui.horizontal(|ui| {
         if ui
             .button(egui::RichText::new("Copy").size(app.config.button_font_size()))
@@ -254,7 +777,28 @@ fn draw_edge_list_io(
             .button(egui::RichText::new("Apply").size(app.config.button_font_size()))
             .clicked()
         {
-            let new_graph = BaseGraph::parse(&app.ui.input_text, app.state.zero_indexed);
+            let new_graph = if app.ui.text_format == GraphTextFormat::EdgeList {
+                BaseGraph::parse_edge_list(
+                    &app.ui.input_text,
+                    app.state.zero_indexed,
+                    app.state.graph.is_weighted,
+                    app.ui.edge_list_options,
+                )
+                .map(|parsed| {
+                    // 自動判定した頂点番号の形式で表示する
+                    app.state.zero_indexed = parsed.zero_indexed;
+                    parsed.graph
+                })
+                .map_err(anyhow::Error::from)
+            } else {
+                BaseGraph::parse_as(
+                    app.ui.text_format,
+                    &app.ui.input_text,
+                    app.state.zero_indexed,
+                    app.state.graph.is_weighted,
+                    app.state.graph.is_directed,
+                )
+            };
             match new_graph {
                 Ok(base_graph) => app.rebuild_from_base_graph(ctx, base_graph),
                 Err(err) => app.ui.error_message = Some(err.to_string()),
@@ -262,6 +806,19 @@ fn draw_edge_list_io(
         }
     });
 
+    if let Some(err) = app
+        .ui
+        .input_encode_error
+        .as_ref()
+        .filter(|_| !app.ui.input_is_dirty)
+    {
+        ui.label(
+            egui::RichText::new(err)
+                .size(app.config.body_font_size())
+                .color(ui.visuals().warn_fg_color),
+        );
+    }
+
     ui.separator();
     let editor = egui::TextEdit::multiline(&mut app.ui.input_text)
         .font(egui::FontId::monospace(app.config.input_font_size()))
@@ -351,22 +908,67 @@ fn draw_json_io(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui, edit
     app.ui.json_is_dirty = app.ui.json_text != app.ui.json_synced_text;
 }
 
+fn draw_dot_io(app: &mut GraphEditorApp, ctx: &Context, ui: &mut egui::Ui, editor_height: f32) {
+    if !app.ui.input_has_focus && !app.ui.dot_is_dirty {
+        app.sync_dot_text_from_graph();
+    }
+
+    ui.label(
+        egui::RichText::new("Graphviz DOT")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+
+    ui.horizontal_wrapped(|ui| {
+        if ui
+            .button(egui::RichText::new("Copy").size(app.config.button_font_size()))
+            .clicked()
+        {
+            ctx.copy_text(app.ui.dot_text.clone());
+        }
+
+        if ui
+            .button(egui::RichText::new("Apply").size(app.config.button_font_size()))
+            .clicked()
+        {
+            match import_graph_from_dot(&app.ui.dot_text) {
+                Ok(imported) => app.apply_imported_graph(ctx, imported),
+                Err(err) => app.ui.error_message = Some(err.to_string()),
+            }
+        }
+    });
+
+    ui.separator();
+    let editor = egui::TextEdit::multiline(&mut app.ui.dot_text)
+        .font(egui::FontId::monospace(app.config.input_font_size()))
+        .desired_rows(10)
+        .desired_width(f32::INFINITY);
+    let response = egui::ScrollArea::vertical()
+        .id_salt("graph_dot_editor_scroll")
+        .max_height(editor_height)
+        .show(ui, |ui| {
+            ui.add_sized([ui.available_width(), editor_height], editor)
+        })
+        .inner;
+    app.ui.input_has_focus = response.has_focus();
+    app.ui.dot_is_dirty = app.ui.dot_text != app.ui.dot_synced_text;
+}
+
 fn draw_toggle_button(
     ui: &mut egui::Ui,
     selected: bool,
     label: &str,
     font_size: f32,
     on_click: impl FnOnce(),
-) {
-    if ui
-        .add_sized(
-            [170.0, 32.0],
-            egui::SelectableLabel::new(selected, egui::RichText::new(label).size(font_size)),
-        )
-        .clicked()
-    {
+) -> egui::Response {
+    let response = ui.add_sized(
+        [170.0, 32.0],
+        egui::SelectableLabel::new(selected, egui::RichText::new(label).size(font_size)),
+    );
+    if response.clicked() {
         on_click();
     }
+    response
 }
 
 fn draw_tab_button(
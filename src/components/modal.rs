@@ -1,7 +1,10 @@
 use egui::Context;
 
+use super::color_panel::{color_swatch, draw_custom_color_picker};
 use crate::{
     components::{default_vertex_text_color, Colors},
+    graph::{EdgeDirection, EdgeWeight},
+    history::{CoalesceKey, HistoryEntry},
     mode::EditMode,
     state::EditTarget,
     GraphEditorApp,
@@ -95,6 +98,7 @@ pub fn draw_clear_all_modal(app: &mut GraphEditorApp, ctx: &Context) {
             );
 
             if ui.add(clear_button).clicked() {
+                app.record_history();
                 app.state.graph.clear();
                 app.state.graph_view.reset_for_graph(&app.state.graph);
                 app.state.next_z_index = 0;
@@ -120,6 +124,22 @@ pub fn draw_entity_editor(app: &mut GraphEditorApp, ctx: &Context) {
         return;
     };
 
+    // 編集前の状態（変更があった場合のみ履歴に記録する）
+    let before_vertex = match target {
+        EditTarget::Vertex(index) => app.state.graph_view.vertices.get(index).cloned(),
+        EditTarget::Edge(_) => None,
+    };
+    let before_edge = match target {
+        EditTarget::Vertex(_) => None,
+        EditTarget::Edge(index) => app
+            .state
+            .graph
+            .edges
+            .get(index)
+            .cloned()
+            .zip(app.state.graph_view.edges.get(index).cloned()),
+    };
+
     let mut open = true;
     let pos = app
         .ui
@@ -143,9 +163,44 @@ pub fn draw_entity_editor(app: &mut GraphEditorApp, ctx: &Context) {
         }
     });
 
+    let changed = match target {
+        EditTarget::Vertex(index) => {
+            before_vertex.as_ref() != app.state.graph_view.vertices.get(index)
+        }
+        EditTarget::Edge(index) => {
+            before_edge.as_ref().map(|(edge, view)| (edge, view))
+                != app
+                    .state
+                    .graph
+                    .edges
+                    .get(index)
+                    .zip(app.state.graph_view.edges.get(index))
+        }
+    };
+    if changed {
+        let mut entry = HistoryEntry::capture(&app.state.graph, &app.state.graph_view);
+        match target {
+            EditTarget::Vertex(index) => {
+                if let Some(before) = before_vertex {
+                    entry.view.vertices[index] = before;
+                }
+            }
+            EditTarget::Edge(index) => {
+                if let Some((edge, view)) = before_edge {
+                    entry.edges[index] = edge;
+                    entry.view.edges[index] = view;
+                }
+            }
+        }
+        app.state
+            .history
+            .record_coalesced(CoalesceKey::Entity(target), entry);
+    }
+
     if !open {
         app.ui.edit_target = None;
         app.ui.edit_window_pos = None;
+        app.state.history.break_coalescing();
     }
 }
 
@@ -181,7 +236,7 @@ fn draw_vertex_editor(app: &mut GraphEditorApp, ui: &mut egui::Ui, index: usize)
             .strong()
             .size(app.config.section_font_size()),
     );
-    draw_color_palette(ui, &mut view.color);
+    draw_color_palette(ui, &mut view.color, &app.config.custom_palette);
 
     ui.separator();
     ui.label(
@@ -191,11 +246,23 @@ fn draw_vertex_editor(app: &mut GraphEditorApp, ui: &mut egui::Ui, index: usize)
     );
     let mut text_color = view
         .text_color
-        .unwrap_or_else(|| default_vertex_text_color(view.color.vertex()));
+        .unwrap_or_else(|| default_vertex_text_color(app.config.theme().vertex_fill(view.color)));
     if ui.color_edit_button_srgba(&mut text_color).changed() {
         view.text_color = Some(text_color);
     }
 
+    ui.separator();
+    ui.label(
+        egui::RichText::new("Outline")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    draw_optional_color_editor(
+        ui,
+        &mut view.stroke_color,
+        app.config.theme().vertex_color_outline,
+    );
+
     ui.separator();
     ui.label(
         egui::RichText::new("Geometry")
@@ -227,10 +294,26 @@ fn draw_vertex_editor(app: &mut GraphEditorApp, ui: &mut egui::Ui, index: usize)
         let stroke = view.stroke_width.get_or_insert(app.config.vertex_stroke);
         ui.add(egui::DragValue::new(stroke).speed(0.25).prefix("stroke: "));
     }
+
+    ui.separator();
+    ui.label(
+        egui::RichText::new("Simulation")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    let mut is_pinned = app.state.graph.vertices[index].is_pinned;
+    if ui.checkbox(&mut is_pinned, "Pinned").changed() {
+        // 頂点の固定はグラフ側の状態なので，ここで履歴を記録する
+        app.record_history();
+        let vertex = &mut app.state.graph.vertices[index];
+        vertex.is_pinned = is_pinned;
+        vertex.velocity = egui::Vec2::ZERO;
+    }
 }
 
 fn draw_edge_editor(app: &mut GraphEditorApp, ui: &mut egui::Ui, index: usize) {
-    let Some(edge) = app.state.graph.edges.get(index) else {
+    let is_weighted = app.state.graph.is_weighted;
+    let Some(edge) = app.state.graph.edges.get_mut(index) else {
         app.ui.edit_target = None;
         return;
     };
@@ -243,12 +326,24 @@ fn draw_edge_editor(app: &mut GraphEditorApp, ui: &mut egui::Ui, index: usize) {
     ui.label(format!("to: {}", edge.to));
     ui.separator();
 
+    ui.label(
+        egui::RichText::new("Label")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    let mut label = view.label.clone().unwrap_or_default();
+    if ui.text_edit_singleline(&mut label).changed() {
+        view.label = (!label.is_empty()).then_some(label);
+    }
+
+    ui.separator();
+
     ui.label(
         egui::RichText::new("Stroke")
             .strong()
             .size(app.config.section_font_size()),
     );
-    draw_color_palette(ui, &mut view.color);
+    draw_color_palette(ui, &mut view.color, &app.config.custom_palette);
 
     let mut use_default_stroke = view.stroke_width.is_none();
     if ui
@@ -262,62 +357,112 @@ fn draw_edge_editor(app: &mut GraphEditorApp, ui: &mut egui::Ui, index: usize) {
         let stroke = view.stroke_width.get_or_insert(app.config.edge_stroke);
         ui.add(egui::DragValue::new(stroke).speed(0.25).prefix("width: "));
     }
+
+    ui.separator();
+    ui.label(
+        egui::RichText::new("Text")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    draw_optional_color_editor(
+        ui,
+        &mut view.text_color,
+        app.config.theme().vertex_font_color,
+    );
+
+    if is_weighted {
+        ui.separator();
+        ui.label(
+            egui::RichText::new("Weight")
+                .strong()
+                .size(app.config.section_font_size()),
+        );
+        draw_weight_editor(ui, &mut edge.weight);
+    }
+
+    ui.separator();
+    ui.label(
+        egui::RichText::new("Direction")
+            .strong()
+            .size(app.config.section_font_size()),
+    );
+    let is_directed = app.state.graph.is_directed;
+    let mut direction = app.state.graph.edges[index].direction;
+    let mut changed = false;
+    for candidate in EdgeDirection::ALL {
+        let text = if candidate == EdgeDirection::Inherit {
+            // 継承した結果の向きを併記する
+            let inherited = candidate.resolve(is_directed).name().to_lowercase();
+            format!("{} ({inherited})", candidate.name())
+        } else {
+            candidate.name().to_string()
+        };
+        changed |= ui.radio_value(&mut direction, candidate, text).changed();
+    }
+    if changed {
+        // 辺の向きはグラフ側の状態なので，ここで履歴を記録する
+        app.record_history();
+        app.state.graph.edges[index].direction = direction;
+    }
 }
 
-fn draw_color_palette(ui: &mut egui::Ui, color: &mut Colors) {
+/// 設定の色を使うか，個別の色を使うかを選ぶ
+fn draw_optional_color_editor(
+    ui: &mut egui::Ui,
+    color: &mut Option<egui::Color32>,
+    default: egui::Color32,
+) {
+    let mut use_default_color = color.is_none();
+    if ui
+        .checkbox(&mut use_default_color, "Use default color")
+        .changed()
+    {
+        *color = (!use_default_color).then_some(default);
+    }
+    if let Some(color) = color {
+        egui::color_picker::color_edit_button_srgba(ui, color, egui::color_picker::Alpha::Opaque);
+    }
+}
+
+fn draw_weight_editor(ui: &mut egui::Ui, weight: &mut Option<EdgeWeight>) {
+    let mut has_weight = weight.is_some();
+    if ui.checkbox(&mut has_weight, "Set weight").changed() {
+        *weight = has_weight.then_some(EdgeWeight::Int(1));
+    }
+
+    let Some(value) = weight else {
+        return;
+    };
+
+    let mut is_float = matches!(value, EdgeWeight::Float(_));
+    if ui.checkbox(&mut is_float, "Real number").changed() {
+        *value = match *value {
+            EdgeWeight::Int(v) => EdgeWeight::Float(v as f64),
+            EdgeWeight::Float(v) => EdgeWeight::Int(v.round() as i64),
+        };
+    }
+
+    match value {
+        EdgeWeight::Int(v) => ui.add(egui::DragValue::new(v).speed(0.25).prefix("weight: ")),
+        EdgeWeight::Float(v) => ui.add(egui::DragValue::new(v).speed(0.05).prefix("weight: ")),
+    };
+}
+
+/// 既定のパレットとユーザーのパレットの色を並べ，任意の色も選べるようにする
+pub(super) fn draw_color_palette(
+    ui: &mut egui::Ui,
+    color: &mut Colors,
+    custom_palette: &[egui::Color32],
+) {
     ui.horizontal_wrapped(|ui| {
-        for candidate in [
-            Colors::Default,
-            Colors::Red,
-            Colors::Green,
-            Colors::Blue,
-            Colors::Yellow,
-            Colors::Orange,
-            Colors::Violet,
-            Colors::Pink,
-            Colors::Brown,
-            Colors::Cyan,
-            Colors::Indigo,
-            Colors::Gray,
-        ] {
-            let fill = if candidate == Colors::Default {
-                egui::Color32::WHITE
-            } else {
-                candidate.vertex()
-            };
-            let stroke_color = if *color == candidate {
-                egui::Color32::BLACK
-            } else {
-                egui::Color32::from_gray(120)
-            };
-            let response = ui
-                .add(
-                    egui::Button::new("")
-                        .min_size(egui::vec2(24.0, 24.0))
-                        .fill(fill)
-                        .stroke(egui::Stroke::new(2.0, stroke_color)),
-                )
-                .on_hover_text(color_name(candidate));
-            if response.clicked() {
+        let candidates = Colors::PALETTE
+            .into_iter()
+            .chain(custom_palette.iter().map(|&rgb| Colors::from_rgb(rgb)));
+        for candidate in candidates {
+            if color_swatch(ui, candidate, *color == candidate, 24.0).clicked() {
                 *color = candidate;
             }
         }
+        draw_custom_color_picker(ui, color);
     });
 }
-
-fn color_name(color: Colors) -> &'static str {
-    match color {
-        Colors::Default => "Default",
-        Colors::Red => "Red",
-        Colors::Green => "Green",
-        Colors::Blue => "Blue",
-        Colors::Yellow => "Yellow",
-        Colors::Orange => "Orange",
-        Colors::Violet => "Violet",
-        Colors::Pink => "Pink",
-        Colors::Brown => "Brown",
-        Colors::Cyan => "Cyan",
-        Colors::Indigo => "Indigo",
-        Colors::Gray => "Gray",
-    }
-}
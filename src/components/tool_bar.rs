@@ -1,5 +1,6 @@
 use egui::Context;
 
+use super::color_panel::{color_swatch, draw_custom_color_picker};
 use crate::{components::Colors, mode::EditMode, GraphEditorApp};
 
 pub fn draw_tool_bar(app: &mut GraphEditorApp, ctx: &Context) {
@@ -50,46 +51,78 @@ pub fn draw_tool_bar(app: &mut GraphEditorApp, ctx: &Context) {
                 );
             });
 
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        app.state.history.can_undo(),
+                        egui::Button::new(
+                            egui::RichText::new("Undo").size(app.config.button_font_size()),
+                        ),
+                    )
+                    .on_hover_text("Ctrl+Z")
+                    .clicked()
+                {
+                    app.undo();
+                }
+                if ui
+                    .add_enabled(
+                        app.state.history.can_redo(),
+                        egui::Button::new(
+                            egui::RichText::new("Redo").size(app.config.button_font_size()),
+                        ),
+                    )
+                    .on_hover_text("Ctrl+Shift+Z")
+                    .clicked()
+                {
+                    app.redo();
+                }
+            });
+
             ui.separator();
             ui.label(egui::RichText::new("Color").size(app.config.section_font_size()));
 
             let prev_color = app.state.selected_color;
             ui.horizontal_wrapped(|ui| {
-                for (label, color) in [
-                    ("Default", Colors::Default),
-                    ("Red", Colors::Red),
-                    ("Green", Colors::Green),
-                    ("Blue", Colors::Blue),
-                    ("Yellow", Colors::Yellow),
-                    ("Orange", Colors::Orange),
-                    ("Violet", Colors::Violet),
-                    ("Pink", Colors::Pink),
-                    ("Brown", Colors::Brown),
-                    ("Cyan", Colors::Cyan),
-                    ("Indigo", Colors::Indigo),
-                    ("Gray", Colors::Gray),
-                ] {
-                    let fill = if color == Colors::Default {
-                        egui::Color32::WHITE
-                    } else {
-                        color.vertex()
-                    };
-                    let stroke_color = if app.state.selected_color == color {
-                        egui::Color32::BLACK
-                    } else {
-                        egui::Color32::from_gray(120)
-                    };
-                    let response = ui
-                        .add(
-                            egui::Button::new("")
-                                .min_size(egui::vec2(28.0, 28.0))
-                                .fill(fill)
-                                .stroke(egui::Stroke::new(2.0, stroke_color)),
-                        )
-                        .on_hover_text(label);
+                for color in Colors::PALETTE {
+                    let is_selected = app.state.selected_color == color;
+                    if color_swatch(ui, color, is_selected, 28.0).clicked() {
+                        app.state.selected_color = color;
+                    }
+                }
+
+                // ユーザーのパレット．右クリックで削除できる
+                let mut removed = None;
+                for (index, &rgb) in app.config.custom_palette.iter().enumerate() {
+                    let color = Colors::from_rgb(rgb);
+                    let is_selected = app.state.selected_color == color;
+                    let response = color_swatch(ui, color, is_selected, 28.0);
                     if response.clicked() {
                         app.state.selected_color = color;
                     }
+                    response.context_menu(|ui| {
+                        if ui.button("Remove from palette").clicked() {
+                            removed = Some(index);
+                            ui.close_menu();
+                        }
+                    });
+                }
+                if let Some(index) = removed {
+                    app.config.custom_palette.remove(index);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                draw_custom_color_picker(ui, &mut app.state.selected_color);
+                let custom = match app.state.selected_color {
+                    Colors::Custom(rgb) if !app.config.custom_palette.contains(&rgb) => Some(rgb),
+                    _ => None,
+                };
+                if ui
+                    .add_enabled(custom.is_some(), egui::Button::new("Add to palette"))
+                    .clicked()
+                {
+                    app.config.custom_palette.extend(custom);
                 }
             });
 
@@ -1,6 +1,8 @@
 // src/components/top_panel.rs
-use egui::Context;
+use egui::{Color32, Context};
 
+use crate::config::{LayoutPreset, SimulatorKind, Theme, ThemeKind, VisualizerKind};
+use crate::graph::simulation_methods::MAX_QUADRATIC_MODEL_VERTICES;
 use crate::GraphEditorApp;
 
 #[derive(Default)]
@@ -77,6 +79,7 @@ fn draw_settings_window(app: &mut GraphEditorApp, ctx: &Context) {
         .open(&mut open)
         .default_width(320.0)
         .resizable(true)
+        .vscroll(true)
         .show(ctx, |ui| {
             let mut edge_length_changed = false;
             let mut reset_defaults = false;
@@ -96,6 +99,47 @@ fn draw_settings_window(app: &mut GraphEditorApp, ctx: &Context) {
                     .text("Vertex font"),
             );
 
+            ui.separator();
+            ui.label(
+                egui::RichText::new("Theme")
+                    .strong()
+                    .size(app.config.section_font_size()),
+            );
+            egui::ComboBox::from_label("Canvas theme")
+                .selected_text(app.config.theme_kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in ThemeKind::ALL {
+                        ui.selectable_value(&mut app.config.theme_kind, kind, kind.name());
+                    }
+                });
+            egui::ComboBox::from_label("Export theme")
+                .selected_text(
+                    app.config
+                        .export_theme_kind
+                        .map_or("Same as canvas", ThemeKind::name),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut app.config.export_theme_kind, None, "Same as canvas");
+                    for kind in ThemeKind::ALL {
+                        ui.selectable_value(
+                            &mut app.config.export_theme_kind,
+                            Some(kind),
+                            kind.name(),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text("Used for PNG and SVG backgrounds and colors");
+            let edits_custom = app.config.theme_kind == ThemeKind::Custom
+                || app.config.export_theme_kind == Some(ThemeKind::Custom);
+            if edits_custom {
+                egui::CollapsingHeader::new("Custom theme")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        draw_custom_theme_editor(ui, &mut app.config.custom_theme)
+                    });
+            }
+
             ui.separator();
             ui.label(
                 egui::RichText::new("Canvas")
@@ -119,6 +163,149 @@ fn draw_settings_window(app: &mut GraphEditorApp, ctx: &Context) {
                 egui::Slider::new(&mut app.config.edge_bezier_distance, 0.0..=120.0)
                     .text("Bezier distance"),
             );
+            ui.add(
+                egui::Slider::new(&mut app.config.self_loop_size, 16.0..=120.0)
+                    .text("Self-loop size"),
+            );
+
+            ui.separator();
+            ui.label(
+                egui::RichText::new("Simulation")
+                    .strong()
+                    .size(app.config.section_font_size()),
+            );
+            ui.horizontal(|ui| {
+                ui.label("Preset");
+                for preset in LayoutPreset::ALL {
+                    if ui.button(preset.name()).clicked() {
+                        preset.apply(&mut app.config, app.state.graph.vertices.len());
+                        edge_length_changed = true;
+                    }
+                }
+            });
+            egui::ComboBox::from_label("Simulator")
+                .selected_text(app.config.simulator_kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in SimulatorKind::ALL {
+                        ui.selectable_value(&mut app.config.simulator_kind, kind, kind.name());
+                    }
+                });
+            if app.config.simulator_kind.is_quadratic()
+                && app.state.graph.vertices.len() > MAX_QUADRATIC_MODEL_VERTICES
+            {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "More than {MAX_QUADRATIC_MODEL_VERTICES} vertices: Barnes-Hut is used instead"
+                    ))
+                    .color(ui.visuals().warn_fg_color),
+                );
+            }
+            let simulator_config = &mut app.config.simulator_config;
+            if matches!(
+                app.config.simulator_kind,
+                SimulatorKind::ForceDirected | SimulatorKind::BarnesHut
+            ) {
+                ui.add(
+                    egui::Slider::new(&mut simulator_config.c, 1e3..=1e6)
+                        .logarithmic(true)
+                        .text("Repulsion"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut simulator_config.k, 0.1..=30.0)
+                        .logarithmic(true)
+                        .text("Spring"),
+                );
+                ui.add(egui::Slider::new(&mut simulator_config.h, 0.05..=0.99).text("Damping"));
+                ui.add(
+                    egui::Slider::new(&mut simulator_config.m, 1.0..=100.0)
+                        .logarithmic(true)
+                        .text("Mass"),
+                );
+            }
+            if app.config.simulator_kind == SimulatorKind::BarnesHut {
+                ui.add(egui::Slider::new(&mut simulator_config.theta, 0.0..=1.5).text("Theta"));
+            }
+            ui.add(
+                egui::Slider::new(&mut simulator_config.max_v, 5.0..=500.0)
+                    .logarithmic(true)
+                    .text("Max speed"),
+            );
+            ui.add(egui::Slider::new(&mut simulator_config.dt, 0.01..=1.0).text("Time step"));
+
+            ui.separator();
+            ui.label(
+                egui::RichText::new("Layout")
+                    .strong()
+                    .size(app.config.section_font_size()),
+            );
+            egui::ComboBox::from_label("Initial layout")
+                .selected_text(app.config.visualizer_kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in VisualizerKind::ALL {
+                        ui.selectable_value(&mut app.config.visualizer_kind, kind, kind.name());
+                    }
+                });
+            let visualizer_config = &mut app.config.visualizer_config;
+            match app.config.visualizer_kind {
+                VisualizerKind::HillClimbing => {
+                    ui.add(
+                        egui::Slider::new(&mut visualizer_config.hill_climbing_iter, 100..=20000)
+                            .logarithmic(true)
+                            .text("Iterations"),
+                    );
+                }
+                VisualizerKind::SimulatedAnnealing => {
+                    ui.add(
+                        egui::Slider::new(
+                            &mut visualizer_config.simulated_annealing_max_iter,
+                            100..=20000,
+                        )
+                        .logarithmic(true)
+                        .text("Iterations"),
+                    );
+                    ui.add(
+                        egui::Slider::new(
+                            &mut visualizer_config.simulated_annealing_initial_temp,
+                            0.1..=100.0,
+                        )
+                        .logarithmic(true)
+                        .text("Initial temperature"),
+                    );
+                    ui.add(
+                        egui::Slider::new(
+                            &mut visualizer_config.simulated_annealing_cooling_rate,
+                            0.9..=0.9999,
+                        )
+                        .text("Cooling rate"),
+                    );
+                }
+                VisualizerKind::Sugiyama => {
+                    ui.add(
+                        egui::Slider::new(&mut visualizer_config.sugiyama_sweeps, 1..=100)
+                            .text("Sweeps"),
+                    );
+                }
+                VisualizerKind::Tree => {
+                    ui.label("The root is chosen in Graph > Layout.");
+                }
+                VisualizerKind::Naive | VisualizerKind::Spectral => {}
+            }
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut app.config.visualizer_config.seed).prefix("Seed: "),
+                )
+                .on_hover_text("The same seed gives the same random layout");
+                if ui.button("Randomize").clicked() {
+                    // DragValue は f64 で値を扱うため，表示した値をそのまま入力し直せる範囲に収める
+                    app.config.visualizer_config.seed = rand::random::<u32>().into();
+                }
+            });
+            ui.add_enabled(
+                !app.config.visualizer_kind.is_static(),
+                egui::Slider::new(&mut app.config.density_threshold, 0.0..=1.0)
+                    .text("Density threshold"),
+            )
+            .on_hover_text("Denser graphs are placed randomly instead");
 
             ui.separator();
             ui.label(
@@ -137,8 +324,12 @@ fn draw_settings_window(app: &mut GraphEditorApp, ctx: &Context) {
                 .button(egui::RichText::new("Reset Defaults").size(app.config.button_font_size()))
                 .clicked()
             {
-                let defaults = crate::config::AppConfig::default();
-                app.config = defaults;
+                // ユーザーが作った色は既定値に戻さない
+                app.config = crate::config::AppConfig {
+                    custom_theme: app.config.custom_theme,
+                    custom_palette: std::mem::take(&mut app.config.custom_palette),
+                    ..Default::default()
+                };
                 reset_defaults = true;
             }
 
@@ -148,3 +339,35 @@ fn draw_settings_window(app: &mut GraphEditorApp, ctx: &Context) {
         });
     app.ui.show_settings = open;
 }
+
+/// ユーザー定義の配色を編集する
+fn draw_custom_theme_editor(ui: &mut egui::Ui, theme: &mut Theme) {
+    ui.horizontal(|ui| {
+        ui.label("Start from");
+        for kind in [ThemeKind::Light, ThemeKind::Dark, ThemeKind::HighContrast] {
+            if ui.button(kind.name()).clicked() {
+                *theme = kind.resolve(theme);
+            }
+        }
+    });
+    ui.checkbox(&mut theme.dark_ui, "Dark widgets");
+    egui::Grid::new("custom_theme_colors")
+        .num_columns(2)
+        .show(ui, |ui| {
+            let color_row = |ui: &mut egui::Ui, label: &str, color: &mut Color32| {
+                ui.label(label);
+                ui.color_edit_button_srgba(color);
+                ui.end_row();
+            };
+            color_row(ui, "Background", &mut theme.bg_color);
+            color_row(ui, "Vertex", &mut theme.vertex_color);
+            color_row(ui, "Vertex outline", &mut theme.vertex_color_outline);
+            color_row(ui, "Vertex text", &mut theme.vertex_font_color);
+            color_row(ui, "Dragged vertex", &mut theme.vertex_color_dragged);
+            color_row(ui, "Selected vertex", &mut theme.vertex_color_selected);
+            color_row(ui, "Edge", &mut theme.edge_color);
+            color_row(ui, "Hovered edge", &mut theme.edge_color_hover);
+            color_row(ui, "Selected edge", &mut theme.edge_color_selected);
+            color_row(ui, "Selection box", &mut theme.selection_rect_color);
+        });
+}
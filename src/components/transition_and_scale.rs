@@ -3,11 +3,11 @@ use crate::{
     GraphEditorApp,
 };
 
-pub fn drag_central_panel(app: &mut GraphEditorApp, ui: &mut egui::Ui) {
-    let response = ui.allocate_response(ui.available_size(), egui::Sense::drag());
+pub fn drag_central_panel(app: &mut GraphEditorApp, ui: &mut egui::Ui) -> egui::Response {
+    let response = ui.allocate_response(ui.available_size(), egui::Sense::click_and_drag());
 
-    // マウス入力の処理
-    if response.dragged_by(egui::PointerButton::Primary) {
+    // マウス入力の処理（範囲選択中は平行移動しない）
+    if response.dragged_by(egui::PointerButton::Primary) && app.ui.marquee_start.is_none() {
         if let Some(mouse_pos) = ui.input(|i| i.pointer.hover_pos()) {
             if let Some(last_pos) = app.state.last_mouse_pos {
                 let cur_affine = app.state.graph.affine.borrow().to_owned();
@@ -23,6 +23,8 @@ pub fn drag_central_panel(app: &mut GraphEditorApp, ui: &mut egui::Ui) {
     } else {
         app.state.last_mouse_pos = None;
     }
+
+    response
 }
 
 /// グラフのスケールを行う
@@ -1,5 +1,8 @@
 use egui::Color32;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
+use crate::components::Colors;
 use crate::graph::{simulation_methods, visualize_methods, Simulator, Visualizer};
 
 /// バージョン情報
@@ -7,19 +10,24 @@ pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// 全体の設定
 pub struct AppConfig {
-    pub bg_color: Color32,
+    /// 編集中の配色
+    pub theme_kind: ThemeKind,
+    /// ユーザー定義の配色
+    pub custom_theme: Theme,
+    /// 画像出力に使う配色．`None`の場合は編集中の配色を使う
+    pub export_theme_kind: Option<ThemeKind>,
     pub vertex_radius: f32,
     pub vertex_stroke: f32,
-    pub vertex_color_outline: Color32,
-    pub vertex_color_dragged: Color32,
-    pub vertex_color_selected: Color32,
     pub vertex_font_size: f32,
-    pub vertex_font_color: Color32,
-    pub edge_color_hover: Color32,
+    /// ユーザーがパレットに追加した色
+    pub custom_palette: Vec<Color32>,
     pub edge_arrow_length: f32,
     pub edge_arrow_width: f32,
     pub edge_bezier_distance: f32,
+    /// 自己ループが頂点から膨らむ大きさ
+    pub self_loop_size: f32,
     pub edge_stroke: f32,
+    pub edge_label_font_size: f32,
     pub ui_font_size: f32,
     /// 最大倍率
     pub scale_max: f32,
@@ -29,6 +37,10 @@ pub struct AppConfig {
     pub scale_delta: f32,
     /// 回転の刻み（ラジアン）
     pub rotate_delta: f32,
+    /// 保持する編集履歴の最大数
+    pub history_limit: usize,
+    /// 複製した部分グラフをずらす量
+    pub duplicate_offset: f32,
     /// 初期配置を省略する密度のしきい値
     pub density_threshold: f32,
     /// 可視化アルゴリズム種別
@@ -44,24 +56,26 @@ pub struct AppConfig {
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
-            bg_color: Color32::from_rgb(230, 230, 230),
+            theme_kind: ThemeKind::Light,
+            custom_theme: Theme::dark(),
+            export_theme_kind: None,
             vertex_radius: 36.0,
             vertex_stroke: 3.0,
-            vertex_color_outline: Color32::from_rgb(150, 150, 150),
-            vertex_color_dragged: Color32::from_rgb(200, 100, 100),
-            vertex_color_selected: Color32::from_rgb(100, 200, 100),
             vertex_font_size: 40.0,
-            vertex_font_color: Color32::BLACK,
-            edge_color_hover: Color32::from_rgb(200, 100, 100),
+            custom_palette: vec![],
             edge_stroke: 6.0,
             edge_arrow_length: 18.0,
             edge_arrow_width: 9.0,
             edge_bezier_distance: 50.0,
+            self_loop_size: 48.0,
+            edge_label_font_size: 24.0,
             ui_font_size: 16.0,
             scale_max: 3.0,
             scale_min: 0.1,
             scale_delta: 0.002,
             rotate_delta: 0.03,
+            history_limit: 100,
+            duplicate_offset: 40.0,
             density_threshold: 0.2,
             visualizer_kind: VisualizerKind::Spectral,
             visualizer_config: VisualizerConfig::default(),
@@ -72,6 +86,18 @@ impl Default for AppConfig {
 }
 
 impl AppConfig {
+    /// 編集中の配色
+    pub fn theme(&self) -> Theme {
+        self.theme_kind.resolve(&self.custom_theme)
+    }
+
+    /// 画像出力に使う配色
+    pub fn export_theme(&self) -> Theme {
+        self.export_theme_kind
+            .unwrap_or(self.theme_kind)
+            .resolve(&self.custom_theme)
+    }
+
     pub fn title_font_size(&self) -> f32 {
         self.ui_font_size * 1.25
     }
@@ -118,6 +144,15 @@ impl AppConfig {
         (self.vertex_font_size * scale).max(6.0)
     }
 
+    /// 高密度グラフをランダム配置にするしきい値．形を保つ配置では常に指定した配置を使う．
+    pub fn layout_density_threshold(&self) -> f32 {
+        if self.visualizer_kind.is_static() {
+            f32::INFINITY
+        } else {
+            self.density_threshold
+        }
+    }
+
     pub fn visualizer(&self) -> Box<dyn Visualizer> {
         match self.visualizer_kind {
             VisualizerKind::Naive => Box::new(visualize_methods::Naive),
@@ -130,6 +165,12 @@ impl AppConfig {
                 initial_temp: self.visualizer_config.simulated_annealing_initial_temp,
                 cooling_rate: self.visualizer_config.simulated_annealing_cooling_rate,
             }),
+            VisualizerKind::Tree => Box::new(visualize_methods::Tree {
+                root: self.visualizer_config.tree_root,
+            }),
+            VisualizerKind::Sugiyama => Box::new(visualize_methods::Sugiyama {
+                sweeps: self.visualizer_config.sugiyama_sweeps,
+            }),
         }
     }
 
@@ -138,24 +179,207 @@ impl AppConfig {
             SimulatorKind::ForceDirected => Box::new(simulation_methods::ForceDirectedModel {
                 config: self.simulator_config,
             }),
+            SimulatorKind::BarnesHut => Box::new(simulation_methods::BarnesHutModel {
+                config: self.simulator_config,
+            }),
+            SimulatorKind::KamadaKawai => Box::new(simulation_methods::KamadaKawaiModel {
+                config: self.simulator_config,
+                distances: Default::default(),
+            }),
+            SimulatorKind::StressMajorization => {
+                Box::new(simulation_methods::StressMajorizationModel {
+                    config: self.simulator_config,
+                    distances: Default::default(),
+                })
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// キャンバスと画像出力の配色
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// egui のウィジェットを暗い配色にするか
+    pub dark_ui: bool,
+    pub bg_color: Color32,
+    /// 色を指定していない頂点の塗り
+    pub vertex_color: Color32,
+    pub vertex_color_outline: Color32,
+    pub vertex_color_dragged: Color32,
+    pub vertex_color_selected: Color32,
+    pub vertex_font_color: Color32,
+    /// 色を指定していない辺の色
+    pub edge_color: Color32,
+    pub edge_color_hover: Color32,
+    pub edge_color_selected: Color32,
+    pub selection_rect_color: Color32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Self {
+            dark_ui: false,
+            bg_color: Color32::from_rgb(230, 230, 230),
+            vertex_color: Color32::WHITE,
+            vertex_color_outline: Color32::from_rgb(150, 150, 150),
+            vertex_color_dragged: Color32::from_rgb(200, 100, 100),
+            vertex_color_selected: Color32::from_rgb(100, 200, 100),
+            vertex_font_color: Color32::BLACK,
+            edge_color: Color32::from_rgb(100, 100, 100),
+            edge_color_hover: Color32::from_rgb(200, 100, 100),
+            edge_color_selected: Color32::from_rgb(100, 200, 100),
+            selection_rect_color: Color32::from_rgb(70, 130, 220),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            dark_ui: true,
+            bg_color: Color32::from_rgb(32, 33, 36),
+            vertex_color: Color32::from_rgb(60, 63, 68),
+            vertex_color_outline: Color32::from_rgb(150, 150, 150),
+            vertex_color_dragged: Color32::from_rgb(210, 110, 110),
+            vertex_color_selected: Color32::from_rgb(90, 180, 90),
+            vertex_font_color: Color32::from_rgb(235, 235, 235),
+            edge_color: Color32::from_rgb(170, 170, 170),
+            edge_color_hover: Color32::from_rgb(230, 120, 120),
+            edge_color_selected: Color32::from_rgb(120, 220, 120),
+            selection_rect_color: Color32::from_rgb(100, 160, 240),
+        }
+    }
+
+    /// 白地に黒の線で，状態を原色で示す配色
+    pub fn high_contrast() -> Self {
+        Self {
+            dark_ui: false,
+            bg_color: Color32::WHITE,
+            vertex_color: Color32::WHITE,
+            vertex_color_outline: Color32::BLACK,
+            vertex_color_dragged: Color32::from_rgb(220, 0, 0),
+            vertex_color_selected: Color32::from_rgb(0, 150, 0),
+            vertex_font_color: Color32::BLACK,
+            edge_color: Color32::BLACK,
+            edge_color_hover: Color32::from_rgb(220, 0, 0),
+            edge_color_selected: Color32::from_rgb(0, 150, 0),
+            selection_rect_color: Color32::from_rgb(0, 0, 220),
+        }
+    }
+
+    /// 頂点の塗りの色．`Colors::Default`は配色の色になる
+    pub fn vertex_fill(&self, color: Colors) -> Color32 {
+        color.to_egui_color().unwrap_or(self.vertex_color)
+    }
+
+    /// 辺の色．`Colors::Default`は配色の色になる
+    pub fn edge_stroke(&self, color: Colors) -> Color32 {
+        color.to_egui_color().unwrap_or(self.edge_color)
+    }
+
+    /// egui のウィジェットの配色
+    pub fn egui_theme(&self) -> egui::Theme {
+        if self.dark_ui {
+            egui::Theme::Dark
+        } else {
+            egui::Theme::Light
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeKind {
+    Light,
+    Dark,
+    HighContrast,
+    /// ユーザー定義の配色
+    Custom,
+}
+
+impl ThemeKind {
+    pub const ALL: [Self; 4] = [Self::Light, Self::Dark, Self::HighContrast, Self::Custom];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Light => "Light",
+            Self::Dark => "Dark",
+            Self::HighContrast => "High Contrast",
+            Self::Custom => "Custom",
+        }
+    }
+
+    pub fn resolve(self, custom: &Theme) -> Theme {
+        match self {
+            Self::Light => Theme::light(),
+            Self::Dark => Theme::dark(),
+            Self::HighContrast => Theme::high_contrast(),
+            Self::Custom => *custom,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VisualizerKind {
     Naive,
     Spectral,
     HillClimbing,
     SimulatedAnnealing,
+    Tree,
+    Sugiyama,
 }
 
-#[derive(Debug, Clone, Copy)]
+impl VisualizerKind {
+    pub const ALL: [Self; 6] = [
+        Self::Naive,
+        Self::Spectral,
+        Self::HillClimbing,
+        Self::SimulatedAnnealing,
+        Self::Tree,
+        Self::Sugiyama,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Naive => "Naive",
+            Self::Spectral => "Spectral",
+            Self::HillClimbing => "Hill Climbing",
+            Self::SimulatedAnnealing => "Simulated Annealing",
+            Self::Tree => "Tree",
+            Self::Sugiyama => "Sugiyama",
+        }
+    }
+
+    /// 配置した形を保つため，配置後に力学モデルを動かさない
+    pub fn is_static(self) -> bool {
+        matches!(self, Self::Tree | Self::Sugiyama)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VisualizerConfig {
     pub hill_climbing_iter: usize,
     pub simulated_annealing_max_iter: usize,
     pub simulated_annealing_initial_temp: f32,
     pub simulated_annealing_cooling_rate: f32,
+    /// 木として配置するときの根
+    pub tree_root: usize,
+    /// 層状に配置するときに交差を減らす走査の回数
+    pub sugiyama_sweeps: usize,
+    /// 乱択を行う配置の乱数の種．同じ種からは同じ配置になる
+    pub seed: u64,
+}
+
+impl VisualizerConfig {
+    /// 配置に使う乱数生成器
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
+    }
 }
 
 impl Default for VisualizerConfig {
@@ -165,17 +389,47 @@ impl Default for VisualizerConfig {
             simulated_annealing_max_iter: 1000,
             simulated_annealing_initial_temp: 10.0,
             simulated_annealing_cooling_rate: 0.995,
+            tree_root: 0,
+            sugiyama_sweeps: 24,
+            seed: 0,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SimulatorKind {
     ForceDirected,
+    BarnesHut,
+    KamadaKawai,
+    StressMajorization,
+}
+
+impl SimulatorKind {
+    pub const ALL: [Self; 4] = [
+        Self::ForceDirected,
+        Self::BarnesHut,
+        Self::KamadaKawai,
+        Self::StressMajorization,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ForceDirected => "Force Directed",
+            Self::BarnesHut => "Barnes-Hut",
+            Self::KamadaKawai => "Kamada-Kawai",
+            Self::StressMajorization => "Stress Majorization",
+        }
+    }
+
+    /// 1ステップに O(n^2) かかるか．頂点が多すぎると Barnes–Hut 法で代用する
+    pub fn is_quadratic(self) -> bool {
+        !matches!(self, Self::BarnesHut)
+    }
 }
 
 /// シミュレーションの設定
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SimulateConfig {
     /// クーロン定数
     pub c: f32,
@@ -191,6 +445,8 @@ pub struct SimulateConfig {
     pub max_v: f32,
     /// 微小時間
     pub dt: f32,
+    /// Barnes–Hut 法で離れた頂点群をまとめて扱う閾値（大きいほど粗く速い）
+    pub theta: f32,
 }
 
 impl Default for SimulateConfig {
@@ -203,6 +459,65 @@ impl Default for SimulateConfig {
             m: 10.0,
             max_v: 100.0,
             dt: 0.2,
+            theta: 0.8,
+        }
+    }
+}
+
+/// 配置とシミュレーションの設定をまとめて切り替えるプリセット
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutPreset {
+    /// 辺を短く，頂点を詰めて配置する
+    Tight,
+    /// 辺を長く，頂点をゆったり配置する
+    Loose,
+    /// 木を根から順に配置し，小さなグラフではグラフ上の距離を保つモデルで動かす
+    TreeFriendly,
+}
+
+/// Tree-friendly プリセットで応力最小化に切り替える頂点数の上限
+const TREE_FRIENDLY_STRESS_MAX_VERTICES: usize = 500;
+
+impl LayoutPreset {
+    pub const ALL: [Self; 3] = [Self::Tight, Self::Loose, Self::TreeFriendly];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tight => "Tight",
+            Self::Loose => "Loose",
+            Self::TreeFriendly => "Tree-friendly",
+        }
+    }
+
+    /// 設定にプリセットを反映する．プリセットが決めない項目は変えない．
+    /// `vertex_count`はシミュレーションを切り替えてよいかの判定に使う．
+    pub fn apply(self, config: &mut AppConfig, vertex_count: usize) {
+        let defaults = SimulateConfig::default();
+        let simulator_config = &mut config.simulator_config;
+        match self {
+            Self::Tight => {
+                simulator_config.c = 8e4;
+                simulator_config.k = 10.0;
+                simulator_config.l = 110.0;
+                simulator_config.h = 0.7;
+            }
+            Self::Loose => {
+                simulator_config.c = 6e5;
+                simulator_config.k = 5.0;
+                simulator_config.l = 260.0;
+                simulator_config.h = 0.78;
+            }
+            Self::TreeFriendly => {
+                simulator_config.c = defaults.c;
+                simulator_config.k = defaults.k;
+                simulator_config.l = 150.0;
+                simulator_config.h = defaults.h;
+                config.visualizer_kind = VisualizerKind::Tree;
+                // 全頂点の組の距離を使うモデルは大きなグラフでは重いので，今のモデルのままにする
+                if vertex_count <= TREE_FRIENDLY_STRESS_MAX_VERTICES {
+                    config.simulator_kind = SimulatorKind::StressMajorization;
+                }
+            }
         }
     }
 }
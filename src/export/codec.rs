@@ -5,15 +5,21 @@ use anyhow::Context;
 use eframe::egui;
 
 use crate::components::default_vertex_text_color;
-use crate::config::AppConfig;
-use crate::graph::Graph;
-use crate::math::bezier::{calc_bezier_control_point, calc_intersection_of_bezier_and_circle};
+use crate::config::{AppConfig, Theme};
+use crate::graph::{EdgeDirection, Graph};
+use crate::math::bezier::{
+    calc_bezier_control_point, calc_intersection_of_bezier_and_circle, cubic_bezier_curve,
+    d_cubic_bezier_dt, edge_midpoint, edge_side_point, nested_self_loop_size,
+    parallel_edge_offsets, self_loop_directions, self_loop_points, self_loop_side_point,
+    trim_cubic_bezier_end,
+};
 use crate::view_state::GraphViewState;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExportFormat {
     Png,
     Svg,
+    Tikz,
 }
 
 impl ExportFormat {
@@ -21,6 +27,7 @@ impl ExportFormat {
         match self {
             Self::Png => "png",
             Self::Svg => "svg",
+            Self::Tikz => "tex",
         }
     }
 }
@@ -36,6 +43,8 @@ pub struct ExportContext<'a> {
     pub graph: &'a Graph,
     pub view: &'a GraphViewState,
     pub config: &'a AppConfig,
+    /// 出力に使う配色
+    pub theme: Theme,
     pub show_number: bool,
     pub zero_indexed: bool,
 }
@@ -48,6 +57,7 @@ pub fn build_export_request(format: ExportFormat) -> Option<ExportRequest> {
         let dialog = rfd::FileDialog::new()
             .add_filter("PNG Image", &["png"])
             .add_filter("SVG Image", &["svg"])
+            .add_filter("TikZ (LaTeX)", &["tex"])
             .set_file_name(&default_name);
 
         let mut path = dialog.save_file()?;
@@ -60,6 +70,7 @@ pub fn build_export_request(format: ExportFormat) -> Option<ExportRequest> {
         {
             Some("svg") => ExportFormat::Svg,
             Some("png") => ExportFormat::Png,
+            Some("tex") => ExportFormat::Tikz,
             _ => {
                 path.set_extension(format.extension());
                 format
@@ -104,11 +115,9 @@ pub fn save_export_bytes(export_request: &ExportRequest, bytes: Vec<u8>) -> anyh
         let filter_label = match export_request.format {
             ExportFormat::Png => "PNG Image",
             ExportFormat::Svg => "SVG Image",
+            ExportFormat::Tikz => "TikZ (LaTeX)",
         };
-        let filter_ext = match export_request.format {
-            ExportFormat::Png => "png",
-            ExportFormat::Svg => "svg",
-        };
+        let filter_ext = export_request.format.extension();
         wasm_bindgen_futures::spawn_local(async move {
             let handle = rfd::AsyncFileDialog::new()
                 .set_file_name(&file_name)
@@ -125,32 +134,86 @@ pub fn save_export_bytes(export_request: &ExportRequest, bytes: Vec<u8>) -> anyh
     }
 }
 
-pub fn export_color_image(color_image: &mut egui::ColorImage) -> anyhow::Result<Vec<u8>> {
-    use image::ImageEncoder as _;
+/// PNG 出力の設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PngOptions {
+    /// SVG の 1px に対応するピクセル数
+    pub scale: f32,
+    /// 背景を塗らずに透過させる
+    pub transparent_background: bool,
+}
 
-    let width = color_image.width() as u32;
-    let height = color_image.height() as u32;
+impl PngOptions {
+    /// 指定できる拡大率の範囲
+    pub const SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.25..=8.0;
+    /// 拡大率 1 のときの解像度
+    pub const BASE_DPI: f32 = 96.0;
 
-    let mut rgba = Vec::with_capacity((width * height) as usize * 4);
-    for color in &color_image.pixels {
-        rgba.extend_from_slice(&color.to_srgba_unmultiplied());
+    pub fn dpi(&self) -> f32 {
+        self.scale * Self::BASE_DPI
     }
+}
 
-    let rgba_image =
-        image::RgbaImage::from_raw(width, height, rgba).context("failed to build RGBA image")?;
+impl Default for PngOptions {
+    fn default() -> Self {
+        Self {
+            scale: 2.0,
+            transparent_background: false,
+        }
+    }
+}
+
+/// 出力できる PNG の一辺の最大ピクセル数
+const MAX_PNG_SIDE: f32 = 16384.0;
 
-    let (w, h) = (rgba_image.width(), rgba_image.height());
+/// SVG と同じ内容をラスタライズして PNG を作る
+pub fn export_png_bytes(ctx: &ExportContext<'_>, options: PngOptions) -> anyhow::Result<Vec<u8>> {
+    use resvg::{tiny_skia, usvg};
 
-    let mut bytes = Vec::new();
-    let encoder = image::codecs::png::PngEncoder::new(&mut bytes);
-    encoder
-        .write_image(&rgba_image, w, h, image::ColorType::Rgba8.into())
-        .context("failed to encode PNG")?;
+    let svg = build_svg(ctx, !options.transparent_background)?;
 
-    Ok(bytes)
+    let mut svg_options = usvg::Options::default();
+    svg_options
+        .fontdb_mut()
+        .load_font_data(epaint_default_fonts::UBUNTU_LIGHT.to_vec());
+    if let Some((family, _)) = svg_options
+        .fontdb
+        .faces()
+        .next()
+        .and_then(|face| face.families.first().cloned())
+    {
+        svg_options.font_family = family;
+    }
+
+    let tree = usvg::Tree::from_str(&svg, &svg_options).context("failed to parse SVG")?;
+
+    let scale = options.scale.clamp(
+        *PngOptions::SCALE_RANGE.start(),
+        *PngOptions::SCALE_RANGE.end(),
+    );
+    let width = (tree.size().width() * scale).round().max(1.0);
+    let height = (tree.size().height() * scale).round().max(1.0);
+    if width > MAX_PNG_SIDE || height > MAX_PNG_SIDE {
+        anyhow::bail!("image is too large: {width} x {height} px");
+    }
+
+    let mut pixmap =
+        tiny_skia::Pixmap::new(width as u32, height as u32).context("failed to allocate image")?;
+    let transform = tiny_skia::Transform::from_scale(
+        width / tree.size().width(),
+        height / tree.size().height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    pixmap.encode_png().context("failed to encode PNG")
 }
 
 pub fn export_svg_bytes(ctx: &ExportContext<'_>) -> anyhow::Result<Vec<u8>> {
+    Ok(build_svg(ctx, true)?.into_bytes())
+}
+
+/// SVG を組み立てる．`include_background`が`false`の場合は背景を塗らない．
+fn build_svg(ctx: &ExportContext<'_>, include_background: bool) -> anyhow::Result<String> {
     let active_vertex_count = ctx.graph.vertices.iter().filter(|v| !v.is_deleted).count();
     let default_vertex_radius = ctx.config.effective_vertex_radius(active_vertex_count);
     let vertex_font_size = ctx.config.effective_vertex_font_size(active_vertex_count);
@@ -164,15 +227,17 @@ pub fn export_svg_bytes(ctx: &ExportContext<'_>) -> anyhow::Result<Vec<u8>> {
         "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
     ));
 
-    let (bg_hex, bg_alpha) = color_to_svg(ctx.config.bg_color);
-    if let Some(alpha) = bg_alpha {
-        svg.push_str(&format!(
-            "  <rect width=\"100%\" height=\"100%\" fill=\"{bg_hex}\" fill-opacity=\"{alpha}\" />\n"
-        ));
-    } else {
-        svg.push_str(&format!(
-            "  <rect width=\"100%\" height=\"100%\" fill=\"{bg_hex}\" />\n"
-        ));
+    if include_background {
+        let (bg_hex, bg_alpha) = color_to_svg(ctx.theme.bg_color);
+        if let Some(alpha) = bg_alpha {
+            svg.push_str(&format!(
+                "  <rect width=\"100%\" height=\"100%\" fill=\"{bg_hex}\" fill-opacity=\"{alpha}\" />\n"
+            ));
+        } else {
+            svg.push_str(&format!(
+                "  <rect width=\"100%\" height=\"100%\" fill=\"{bg_hex}\" />\n"
+            ));
+        }
     }
 
     let snapshot = ctx.view.snapshot(ctx.graph);
@@ -181,13 +246,15 @@ pub fn export_svg_bytes(ctx: &ExportContext<'_>) -> anyhow::Result<Vec<u8>> {
 
     let vertex_map: HashMap<usize, _> = vertices.iter().map(|v| (v.id, v.clone())).collect();
 
-    let mut edge_count: HashMap<(usize, usize), usize> = HashMap::new();
-    for edge in edges.iter() {
-        *edge_count.entry((edge.from, edge.to)).or_insert(0) += 1;
-        *edge_count.entry((edge.to, edge.from)).or_insert(0) += 1;
-    }
+    let offsets = parallel_edge_offsets(edges.iter().map(|edge| (edge.from, edge.to)));
+    let vertex_positions: HashMap<usize, egui::Pos2> =
+        vertices.iter().map(|v| (v.id, v.position)).collect();
+    let loop_directions = self_loop_directions(
+        &vertex_positions,
+        edges.iter().map(|edge| (edge.from, edge.to)),
+    );
 
-    for edge in edges.drain(..) {
+    for (edge, edge_offset) in edges.drain(..).zip(offsets) {
         let Some(from_vertex) = vertex_map.get(&edge.from) else {
             continue;
         };
@@ -197,7 +264,7 @@ pub fn export_svg_bytes(ctx: &ExportContext<'_>) -> anyhow::Result<Vec<u8>> {
 
         let from_pos = from_vertex.position;
         let to_pos = to_vertex.position;
-        let edge_color = edge.color.edge();
+        let edge_color = ctx.theme.edge_stroke(edge.color);
         let (stroke_hex, stroke_alpha) = color_to_svg(edge_color);
         let stroke_style = if let Some(alpha) = stroke_alpha {
             format!("stroke=\"{stroke_hex}\" stroke-opacity=\"{alpha}\"")
@@ -215,131 +282,136 @@ pub fn export_svg_bytes(ctx: &ExportContext<'_>) -> anyhow::Result<Vec<u8>> {
             .get(&edge.to)
             .and_then(|vertex| vertex.radius)
             .unwrap_or(default_vertex_radius);
-        if snapshot.is_directed {
-            if edge_count.get(&(edge.from, edge.to)) == Some(&1) {
+        let is_curved = edge_offset != 0.0;
+        let bezier_distance = ctx.config.edge_bezier_distance * edge_offset;
+        let self_loop = (edge.from == edge.to).then(|| {
+            self_loop_points(
+                from_pos,
+                target_radius,
+                loop_directions[&edge.from],
+                nested_self_loop_size(ctx.config.self_loop_size, edge_offset),
+            )
+        });
+        if let Some(points) = self_loop {
+            let mut path = points;
+            if edge.direction != EdgeDirection::Undirected {
+                path = trim_cubic_bezier_end(path, ctx.config.edge_arrow_length);
+                let dir = d_cubic_bezier_dt(points, 1.0);
+                push_svg_arrowhead(&mut svg, points[3], dir, edge_color, ctx, bounds);
+            }
+            if edge.direction == EdgeDirection::Bidirectional {
+                path.reverse();
+                path = trim_cubic_bezier_end(path, ctx.config.edge_arrow_length);
+                path.reverse();
+                let dir = -d_cubic_bezier_dt(points, 0.0);
+                push_svg_arrowhead(&mut svg, points[0], dir, edge_color, ctx, bounds);
+            }
+            let [start, control1, control2, end] = path.map(|point| svg_point(point, bounds));
+            svg.push_str(&format!(
+                "  <path d=\"M {start} C {control1} {control2} {end}\" {stroke_style} stroke-width=\"{stroke_width}\" fill=\"none\" />\n",
+            ));
+        } else if edge.direction != EdgeDirection::Undirected {
+            // 両向きの辺は始点側にも矢印を付ける
+            let source_radius = (edge.direction == EdgeDirection::Bidirectional)
+                .then(|| from_vertex.radius.unwrap_or(default_vertex_radius));
+            if !is_curved {
                 let dir = (to_pos - from_pos).normalized();
                 let arrowhead = to_pos - dir * target_radius;
                 let endpoint = arrowhead - dir * ctx.config.edge_arrow_length;
-                let arrow_dir = dir * ctx.config.edge_arrow_length;
-                let left = egui::Pos2::new(
-                    arrowhead.x
-                        - arrow_dir.x
-                        - arrow_dir.y
-                            * (ctx.config.edge_arrow_width / ctx.config.edge_arrow_length),
-                    arrowhead.y - arrow_dir.y
-                        + arrow_dir.x
-                            * (ctx.config.edge_arrow_width / ctx.config.edge_arrow_length),
-                );
-                let right = egui::Pos2::new(
-                    arrowhead.x - arrow_dir.x
-                        + arrow_dir.y
-                            * (ctx.config.edge_arrow_width / ctx.config.edge_arrow_length),
-                    arrowhead.y
-                        - arrow_dir.y
-                        - arrow_dir.x
-                            * (ctx.config.edge_arrow_width / ctx.config.edge_arrow_length),
-                );
-
-                let end_x = endpoint.x - bounds.min.x;
-                let end_y = endpoint.y - bounds.min.y;
+                let startpoint = match source_radius {
+                    Some(source_radius) => {
+                        let arrowhead = from_pos + dir * source_radius;
+                        push_svg_arrowhead(&mut svg, arrowhead, -dir, edge_color, ctx, bounds);
+                        arrowhead + dir * ctx.config.edge_arrow_length
+                    }
+                    None => from_pos,
+                };
+
                 svg.push_str(&format!(
-                    "  <line x1=\"{from_x}\" y1=\"{from_y}\" x2=\"{end_x}\" y2=\"{end_y}\" {stroke_style} stroke-width=\"{stroke_width}\" fill=\"none\" />\n",
+                    "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {stroke_style} stroke-width=\"{stroke_width}\" fill=\"none\" />\n",
+                    startpoint.x - bounds.min.x,
+                    startpoint.y - bounds.min.y,
+                    endpoint.x - bounds.min.x,
+                    endpoint.y - bounds.min.y,
                 ));
-
-                let (fill_hex, fill_alpha) = color_to_svg(edge_color);
-                if let Some(alpha) = fill_alpha {
-                    svg.push_str(&format!(
-                        "  <polygon points=\"{} {} {}\" fill=\"{fill_hex}\" fill-opacity=\"{alpha}\" />\n",
-                        svg_point(arrowhead, bounds),
-                        svg_point(left, bounds),
-                        svg_point(right, bounds)
-                    ));
-                } else {
-                    svg.push_str(&format!(
-                        "  <polygon points=\"{} {} {}\" fill=\"{fill_hex}\" />\n",
-                        svg_point(arrowhead, bounds),
-                        svg_point(left, bounds),
-                        svg_point(right, bounds)
-                    ));
-                }
+                push_svg_arrowhead(&mut svg, arrowhead, dir, edge_color, ctx, bounds);
             } else {
-                let control = calc_bezier_control_point(
-                    from_pos,
-                    to_pos,
-                    ctx.config.edge_bezier_distance,
-                    false,
-                );
-                if let Some((arrowhead, dir)) = calc_intersection_of_bezier_and_circle(
-                    from_pos,
-                    control,
-                    to_pos,
-                    to_pos,
-                    target_radius,
-                ) {
-                    let control_x = control.x - bounds.min.x;
-                    let control_y = control.y - bounds.min.y;
-                    svg.push_str(&format!(
-                        "  <path d=\"M {from_x} {from_y} Q {control_x} {control_y} {to_x} {to_y}\" {stroke_style} stroke-width=\"{stroke_width}\" fill=\"none\" />\n",
-                    ));
-
-                    let mask_start =
-                        arrowhead - dir.normalized() * ctx.config.edge_arrow_length / 2.0;
-                    let (mask_hex, mask_alpha) = color_to_svg(ctx.config.bg_color);
-                    let mask_style = if let Some(alpha) = mask_alpha {
-                        format!("stroke=\"{mask_hex}\" stroke-opacity=\"{alpha}\"")
-                    } else {
-                        format!("stroke=\"{mask_hex}\"")
+                let control = calc_bezier_control_point(from_pos, to_pos, bezier_distance, false);
+                let control_x = control.x - bounds.min.x;
+                let control_y = control.y - bounds.min.y;
+                svg.push_str(&format!(
+                    "  <path d=\"M {from_x} {from_y} Q {control_x} {control_y} {to_x} {to_y}\" {stroke_style} stroke-width=\"{stroke_width}\" fill=\"none\" />\n",
+                ));
+
+                // 終点側の矢印と，両向きの場合は曲線を逆向きにたどった始点側の矢印
+                let heads = [
+                    Some((from_pos, to_pos, target_radius)),
+                    source_radius.map(|radius| (to_pos, from_pos, radius)),
+                ];
+                for (start, end, radius) in heads.into_iter().flatten() {
+                    let Some((arrowhead, dir)) =
+                        calc_intersection_of_bezier_and_circle(start, control, end, end, radius)
+                    else {
+                        continue;
                     };
-                    svg.push_str(&format!(
-                        "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {mask_style} stroke-width=\"{stroke_width}\" fill=\"none\" />\n",
-                        mask_start.x - bounds.min.x,
-                        mask_start.y - bounds.min.y,
-                        arrowhead.x - bounds.min.x,
-                        arrowhead.y - bounds.min.y,
-                    ));
-
-                    let arrow_dir = dir.normalized() * ctx.config.edge_arrow_length;
-                    let left = egui::Pos2::new(
-                        arrowhead.x
-                            - arrow_dir.x
-                            - arrow_dir.y
-                                * (ctx.config.edge_arrow_width / ctx.config.edge_arrow_length),
-                        arrowhead.y - arrow_dir.y
-                            + arrow_dir.x
-                                * (ctx.config.edge_arrow_width / ctx.config.edge_arrow_length),
-                    );
-                    let right = egui::Pos2::new(
-                        arrowhead.x - arrow_dir.x
-                            + arrow_dir.y
-                                * (ctx.config.edge_arrow_width / ctx.config.edge_arrow_length),
-                        arrowhead.y
-                            - arrow_dir.y
-                            - arrow_dir.x
-                                * (ctx.config.edge_arrow_width / ctx.config.edge_arrow_length),
-                    );
-                    let (fill_hex, fill_alpha) = color_to_svg(edge_color);
-                    if let Some(alpha) = fill_alpha {
-                        svg.push_str(&format!(
-                            "  <polygon points=\"{} {} {}\" fill=\"{fill_hex}\" fill-opacity=\"{alpha}\" />\n",
-                            svg_point(arrowhead, bounds),
-                            svg_point(left, bounds),
-                            svg_point(right, bounds)
-                        ));
-                    } else {
+
+                    // 背景色で矢印の根元の線を隠す．背景を塗らない場合は隠せないので省略する．
+                    if include_background {
+                        let mask_start =
+                            arrowhead - dir.normalized() * ctx.config.edge_arrow_length / 2.0;
+                        let (mask_hex, mask_alpha) = color_to_svg(ctx.theme.bg_color);
+                        let mask_style = if let Some(alpha) = mask_alpha {
+                            format!("stroke=\"{mask_hex}\" stroke-opacity=\"{alpha}\"")
+                        } else {
+                            format!("stroke=\"{mask_hex}\"")
+                        };
                         svg.push_str(&format!(
-                            "  <polygon points=\"{} {} {}\" fill=\"{fill_hex}\" />\n",
-                            svg_point(arrowhead, bounds),
-                            svg_point(left, bounds),
-                            svg_point(right, bounds)
+                            "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {mask_style} stroke-width=\"{stroke_width}\" fill=\"none\" />\n",
+                            mask_start.x - bounds.min.x,
+                            mask_start.y - bounds.min.y,
+                            arrowhead.x - bounds.min.x,
+                            arrowhead.y - bounds.min.y,
                         ));
                     }
+
+                    push_svg_arrowhead(&mut svg, arrowhead, dir, edge_color, ctx, bounds);
                 }
             }
+        } else if is_curved {
+            let control = calc_bezier_control_point(from_pos, to_pos, bezier_distance, false);
+            let control_x = control.x - bounds.min.x;
+            let control_y = control.y - bounds.min.y;
+            svg.push_str(&format!(
+                "  <path d=\"M {from_x} {from_y} Q {control_x} {control_y} {to_x} {to_y}\" {stroke_style} stroke-width=\"{stroke_width}\" fill=\"none\" />\n",
+            ));
         } else {
             svg.push_str(&format!(
                 "  <line x1=\"{from_x}\" y1=\"{from_y}\" x2=\"{to_x}\" y2=\"{to_y}\" {stroke_style} stroke-width=\"{stroke_width}\" fill=\"none\" />\n",
             ));
         }
+
+        if let Some(weight) = edge.weight.filter(|_| snapshot.is_weighted) {
+            let position = match self_loop {
+                Some(points) => cubic_bezier_curve(points, 0.5),
+                None => edge_midpoint(from_pos, to_pos, is_curved, bezier_distance),
+            };
+            push_svg_edge_text(
+                &mut svg,
+                position,
+                &weight.to_string(),
+                edge.text_color,
+                ctx,
+                bounds,
+            );
+        }
+        if let Some(label) = edge.label.as_ref().filter(|label| !label.is_empty()) {
+            let offset = ctx.config.edge_label_font_size + stroke_width;
+            let position = match self_loop {
+                Some(points) => self_loop_side_point(from_pos, points, offset),
+                None => edge_side_point(from_pos, to_pos, is_curved, bezier_distance, offset),
+            };
+            push_svg_edge_text(&mut svg, position, label, edge.text_color, ctx, bounds);
+        }
     }
 
     vertices.sort_by_key(|v| v.z_index);
@@ -349,7 +421,7 @@ pub fn export_svg_bytes(ctx: &ExportContext<'_>) -> anyhow::Result<Vec<u8>> {
         let y = pos.y - bounds.min.y;
         let vertex_radius = vertex.radius.unwrap_or(default_vertex_radius);
         let vertex_stroke = vertex.stroke_width.unwrap_or(ctx.config.vertex_stroke);
-        let fill_color = vertex.color.vertex();
+        let fill_color = ctx.theme.vertex_fill(vertex.color);
         let (fill_hex, fill_alpha) = color_to_svg(fill_color);
         if let Some(alpha) = fill_alpha {
             svg.push_str(&format!(
@@ -361,7 +433,11 @@ pub fn export_svg_bytes(ctx: &ExportContext<'_>) -> anyhow::Result<Vec<u8>> {
             ));
         }
 
-        let (stroke_hex, stroke_alpha) = color_to_svg(ctx.config.vertex_color_outline);
+        let (stroke_hex, stroke_alpha) = color_to_svg(
+            vertex
+                .stroke_color
+                .unwrap_or(ctx.theme.vertex_color_outline),
+        );
         if let Some(alpha) = stroke_alpha {
             svg.push_str(&format!(
                 "  <circle cx=\"{x}\" cy=\"{y}\" r=\"{vertex_radius}\" fill=\"none\" stroke=\"{stroke_hex}\" stroke-opacity=\"{alpha}\" stroke-width=\"{vertex_stroke}\" />\n",
@@ -373,19 +449,18 @@ pub fn export_svg_bytes(ctx: &ExportContext<'_>) -> anyhow::Result<Vec<u8>> {
         }
 
         if ctx.show_number {
-            let vertex_show_id = vertex.label.clone().unwrap_or_else(|| {
+            let vertex_show_id = escape_xml(&vertex.label.clone().unwrap_or_else(|| {
                 if ctx.zero_indexed {
                     vertex.id
                 } else {
                     vertex.id + 1
                 }
                 .to_string()
-            });
-            let (text_hex, text_alpha) = color_to_svg(
-                vertex
-                    .text_color
-                    .unwrap_or_else(|| default_vertex_text_color(vertex.color.vertex())),
-            );
+            }));
+            let (text_hex, text_alpha) =
+                color_to_svg(vertex.text_color.unwrap_or_else(|| {
+                    default_vertex_text_color(ctx.theme.vertex_fill(vertex.color))
+                }));
             let text_adjust_y = y + 4.5;
             if let Some(alpha) = text_alpha {
                 svg.push_str(&format!(
@@ -400,7 +475,7 @@ pub fn export_svg_bytes(ctx: &ExportContext<'_>) -> anyhow::Result<Vec<u8>> {
     }
 
     svg.push_str("</svg>\n");
-    Ok(svg.into_bytes())
+    Ok(svg)
 }
 
 pub fn graph_bounds_rect(ctx: &ExportContext<'_>) -> Option<egui::Rect> {
@@ -430,7 +505,35 @@ pub fn graph_bounds_rect(ctx: &ExportContext<'_>) -> Option<egui::Rect> {
         max.y = max.y.max(pos.y);
     }
 
+    // 自己ループや多重辺の曲線は頂点の外側に膨らむので，その分も余白に加える
+    let offsets = parallel_edge_offsets(snapshot.edges.iter().map(|edge| (edge.from, edge.to)));
+    let edge_extent = snapshot
+        .edges
+        .iter()
+        .zip(offsets)
+        .map(|(edge, edge_offset)| {
+            if edge.from != edge.to {
+                // 2 次ベジェ曲線は制御点までの距離の半分だけ膨らむ
+                let bulge = ctx.config.edge_bezier_distance * edge_offset.abs() / 2.0;
+                return (bulge - max_vertex_radius).max(0.0);
+            }
+
+            // 自己ループのラベルはループのさらに外側に置く
+            let label_extent = edge
+                .label
+                .as_ref()
+                .filter(|label| !label.is_empty())
+                .map_or(0.0, |label| {
+                    // 文字幅はフォントサイズの 0.6 倍程度とみなす
+                    let half_width = label.chars().count() as f32 * 0.3;
+                    ctx.config.edge_label_font_size * (1.0 + half_width.max(0.5))
+                        + edge.stroke_width.unwrap_or(ctx.config.edge_stroke)
+                });
+            nested_self_loop_size(ctx.config.self_loop_size, edge_offset) + label_extent
+        })
+        .fold(0.0, f32::max);
     let padding = max_vertex_radius
+        + edge_extent
         + max_edge_stroke
         + ctx
             .config
@@ -445,6 +548,67 @@ pub fn graph_bounds_rect(ctx: &ExportContext<'_>) -> Option<egui::Rect> {
     Some(egui::Rect::from_min_max(min, max))
 }
 
+/// 矢印のヘッド（三角形）を出力する．`dir`は矢印の向き
+fn push_svg_arrowhead(
+    svg: &mut String,
+    arrowhead: egui::Pos2,
+    dir: egui::Vec2,
+    color: egui::Color32,
+    ctx: &ExportContext<'_>,
+    bounds: egui::Rect,
+) {
+    let arrow_dir = dir.normalized() * ctx.config.edge_arrow_length;
+    let ratio = ctx.config.edge_arrow_width / ctx.config.edge_arrow_length;
+    let left = egui::Pos2::new(
+        arrowhead.x - arrow_dir.x - arrow_dir.y * ratio,
+        arrowhead.y - arrow_dir.y + arrow_dir.x * ratio,
+    );
+    let right = egui::Pos2::new(
+        arrowhead.x - arrow_dir.x + arrow_dir.y * ratio,
+        arrowhead.y - arrow_dir.y - arrow_dir.x * ratio,
+    );
+
+    let (fill_hex, fill_alpha) = color_to_svg(color);
+    let fill_style = if let Some(alpha) = fill_alpha {
+        format!("fill=\"{fill_hex}\" fill-opacity=\"{alpha}\"")
+    } else {
+        format!("fill=\"{fill_hex}\"")
+    };
+    svg.push_str(&format!(
+        "  <polygon points=\"{} {} {}\" {fill_style} />\n",
+        svg_point(arrowhead, bounds),
+        svg_point(left, bounds),
+        svg_point(right, bounds)
+    ));
+}
+
+/// 辺に付随する文字列を，背景色の縁取り付きで出力する
+fn push_svg_edge_text(
+    svg: &mut String,
+    position: egui::Pos2,
+    text: &str,
+    text_color: Option<egui::Color32>,
+    ctx: &ExportContext<'_>,
+    bounds: egui::Rect,
+) {
+    let x = position.x - bounds.min.x;
+    let y = position.y - bounds.min.y;
+    let font_size = ctx.config.edge_label_font_size;
+    let (text_hex, _) = color_to_svg(text_color.unwrap_or(ctx.theme.vertex_font_color));
+    let (halo_hex, _) = color_to_svg(ctx.theme.bg_color);
+    let text = escape_xml(text);
+    svg.push_str(&format!(
+        "  <text x=\"{x}\" y=\"{y}\" text-anchor=\"middle\" dominant-baseline=\"middle\" font-size=\"{font_size}\" fill=\"{text_hex}\" stroke=\"{halo_hex}\" stroke-width=\"4\" paint-order=\"stroke\">{text}</text>\n",
+    ));
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 fn svg_point(pos: egui::Pos2, bounds: egui::Rect) -> String {
     format!("{} {}", pos.x - bounds.min.x, pos.y - bounds.min.y)
 }
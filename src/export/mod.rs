@@ -1,8 +1,10 @@
 mod codec;
 mod service;
+mod tikz;
 
 pub use codec::{
-    build_export_request, export_color_image, export_svg_bytes, graph_bounds_rect,
-    save_export_bytes, ExportContext, ExportFormat, ExportRequest,
+    build_export_request, export_png_bytes, export_svg_bytes, save_export_bytes, ExportContext,
+    ExportFormat, PngOptions,
 };
 pub use service::ExportService;
+pub use tikz::{export_tikz_bytes, TikzOptions};
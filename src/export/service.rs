@@ -1,22 +1,20 @@
-use eframe::egui;
-
 use crate::export::{
-    build_export_request, export_color_image, export_svg_bytes, graph_bounds_rect,
-    save_export_bytes, ExportContext, ExportFormat, ExportRequest,
+    build_export_request, export_png_bytes, export_svg_bytes, export_tikz_bytes, save_export_bytes,
+    ExportContext, ExportFormat, PngOptions, TikzOptions,
 };
 
 pub struct ExportService {
     format: ExportFormat,
-    in_progress: bool,
-    request: Option<ExportRequest>,
+    png_options: PngOptions,
+    tikz_options: TikzOptions,
 }
 
 impl Default for ExportService {
     fn default() -> Self {
         Self {
             format: ExportFormat::Png,
-            in_progress: false,
-            request: None,
+            png_options: PngOptions::default(),
+            tikz_options: TikzOptions::default(),
         }
     }
 }
@@ -30,88 +28,25 @@ impl ExportService {
         self.format = format;
     }
 
-    pub fn is_busy(&self) -> bool {
-        self.in_progress
+    pub fn png_options_mut(&mut self) -> &mut PngOptions {
+        &mut self.png_options
     }
 
-    pub fn request_export(
-        &mut self,
-        ctx: &egui::Context,
-        export_ctx: &ExportContext<'_>,
-    ) -> Option<String> {
-        if self.in_progress {
-            return None;
-        }
-
-        let export_request = build_export_request(self.format)?;
-
-        if export_request.format == ExportFormat::Svg {
-            self.in_progress = true;
-            let result = export_svg_bytes(export_ctx)
-                .and_then(|bytes| save_export_bytes(&export_request, bytes));
-            self.in_progress = false;
-            if let Err(err) = result {
-                return Some(err.to_string());
-            }
-            return None;
-        }
-
-        self.in_progress = true;
-        self.request = Some(export_request);
-        ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::new(
-            "graph-export",
-        )));
-        ctx.request_repaint();
-        None
+    pub fn tikz_options_mut(&mut self) -> &mut TikzOptions {
+        &mut self.tikz_options
     }
 
-    pub fn handle_events(
-        &mut self,
-        ctx: &egui::Context,
-        export_ctx: &ExportContext<'_>,
-    ) -> Option<String> {
-        self.request.as_ref()?;
-
-        let screenshot = ctx.input(|i| {
-            i.raw.events.iter().find_map(|event| {
-                if let egui::Event::Screenshot { image, .. } = event {
-                    Some(image.clone())
-                } else {
-                    None
-                }
-            })
-        });
-
-        let screenshot = screenshot?;
-
-        let export_request = self.request.take();
-        self.in_progress = false;
-
-        let export_request = export_request?;
+    /// 保存先を選び，グラフのモデルから画像などを生成して保存する
+    pub fn request_export(&mut self, export_ctx: &ExportContext<'_>) -> Option<String> {
+        let export_request = build_export_request(self.format)?;
 
-        let pixels_per_point = ctx.pixels_per_point();
-        let Some(mut region) = graph_bounds_rect(export_ctx) else {
-            return Some("Export failed: no vertices to capture.".to_string());
+        let bytes = match export_request.format {
+            ExportFormat::Png => export_png_bytes(export_ctx, self.png_options),
+            ExportFormat::Svg => export_svg_bytes(export_ctx),
+            ExportFormat::Tikz => export_tikz_bytes(export_ctx, self.tikz_options),
         };
-        region = region.intersect(ctx.screen_rect());
-
-        if region.width() <= 0.0 || region.height() <= 0.0 {
-            return Some("Export failed: invalid capture region.".to_string());
-        }
-
-        let mut color_image = screenshot.region(&region, Some(pixels_per_point));
-
-        if color_image.width() == 0 || color_image.height() == 0 {
-            return Some("Export failed: empty capture region.".to_string());
-        }
-
-        let result = export_color_image(&mut color_image)
-            .and_then(|bytes| save_export_bytes(&export_request, bytes));
-
-        if let Err(err) = result {
-            return Some(err.to_string());
-        }
+        let result = bytes.and_then(|bytes| save_export_bytes(&export_request, bytes));
 
-        None
+        result.err().map(|err| format!("Export failed: {err}"))
     }
 }
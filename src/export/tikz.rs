@@ -0,0 +1,408 @@
+//! TikZ（LaTeX）形式の出力
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use anyhow::Context;
+
+use crate::components::{default_vertex_text_color, Colors};
+use crate::export::codec::{graph_bounds_rect, ExportContext};
+use crate::graph::EdgeDirection;
+use crate::math::bezier::{
+    calc_bezier_control_point, cubic_bezier_curve, edge_midpoint, edge_side_point,
+    nested_self_loop_size, parallel_edge_offsets, self_loop_directions, self_loop_points,
+    self_loop_side_point,
+};
+
+/// SVG の 1px に対応する TikZ の長さ（pt）
+const PT_PER_PX: f32 = 0.75;
+
+/// TikZ 出力の設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TikzOptions {
+    /// `standalone`クラスの文書として出力し，そのままコンパイルできるようにする
+    pub standalone: bool,
+}
+
+/// 現在の配置をもとに`tikzpicture`を出力する．座標は SVG と同じく画像の左上を原点とする．
+pub fn export_tikz_bytes(ctx: &ExportContext<'_>, options: TikzOptions) -> anyhow::Result<Vec<u8>> {
+    let snapshot = ctx.view.snapshot(ctx.graph);
+    let default_vertex_radius = ctx.config.effective_vertex_radius(snapshot.vertices.len());
+    let vertex_font_size = ctx
+        .config
+        .effective_vertex_font_size(snapshot.vertices.len());
+    let bounds = graph_bounds_rect(ctx).context("missing graph bounds")?;
+    let point = |pos: egui::Pos2| {
+        format!(
+            "({}, {})",
+            format_number(pos.x - bounds.min.x),
+            format_number(pos.y - bounds.min.y)
+        )
+    };
+
+    let mut colors = ColorTable::default();
+    let outline = colors.name("outline", ctx.theme.vertex_color_outline);
+    let background = colors.name("background", ctx.theme.bg_color);
+    let label_color = colors.name("label", ctx.theme.vertex_font_color);
+
+    let mut body = String::new();
+
+    let mut vertices = snapshot.vertices.clone();
+    vertices.sort_by_key(|vertex| vertex.z_index);
+    let mut node_names = HashMap::new();
+    for vertex in &vertices {
+        let name = format!("v{}", vertex.id);
+        let fill = colors.name(
+            &color_hint(vertex.color, "vertex"),
+            ctx.theme.vertex_fill(vertex.color),
+        );
+        let text = vertex
+            .text_color
+            .unwrap_or_else(|| default_vertex_text_color(ctx.theme.vertex_fill(vertex.color)));
+        let text = colors.name("text", text);
+
+        let mut style = format!("ge vertex, fill={fill}, text={text}");
+        if let Some(stroke) = vertex.stroke_color {
+            let _ = write!(style, ", draw={}", colors.name("outline", stroke));
+        }
+        if let Some(radius) = vertex.radius {
+            let _ = write!(
+                style,
+                ", minimum size={}pt",
+                format_number(radius * 2.0 * PT_PER_PX)
+            );
+        }
+        if let Some(width) = vertex.stroke_width {
+            let _ = write!(style, ", line width={}pt", format_number(width * PT_PER_PX));
+        }
+
+        let text = if ctx.show_number {
+            escape_latex(&vertex.label.clone().unwrap_or_else(|| {
+                if ctx.zero_indexed {
+                    vertex.id
+                } else {
+                    vertex.id + 1
+                }
+                .to_string()
+            }))
+        } else {
+            String::new()
+        };
+        let _ = writeln!(
+            body,
+            "  \\node[{style}] ({name}) at {} {{{text}}};",
+            point(vertex.position)
+        );
+        node_names.insert(vertex.id, (name, vertex.position));
+    }
+
+    let offsets = parallel_edge_offsets(snapshot.edges.iter().map(|edge| (edge.from, edge.to)));
+    let vertex_positions: HashMap<usize, egui::Pos2> = vertices
+        .iter()
+        .map(|vertex| (vertex.id, vertex.position))
+        .collect();
+    let loop_directions = self_loop_directions(
+        &vertex_positions,
+        snapshot.edges.iter().map(|edge| (edge.from, edge.to)),
+    );
+
+    for (edge, &edge_offset) in snapshot.edges.iter().zip(&offsets) {
+        let (Some((from, from_pos)), Some((to, to_pos))) =
+            (node_names.get(&edge.from), node_names.get(&edge.to))
+        else {
+            continue;
+        };
+        let (from_pos, to_pos) = (*from_pos, *to_pos);
+
+        let stroke = colors.name(
+            &color_hint(edge.color, "edge"),
+            ctx.theme.edge_stroke(edge.color),
+        );
+        let mut style = format!("ge edge, draw={stroke}");
+        match edge.direction {
+            EdgeDirection::Directed => style.push_str(", ->"),
+            EdgeDirection::Bidirectional => style.push_str(", <->"),
+            EdgeDirection::Undirected | EdgeDirection::Inherit => {}
+        }
+        if let Some(width) = edge.stroke_width {
+            let _ = write!(style, ", line width={}pt", format_number(width * PT_PER_PX));
+        }
+
+        let is_curved = edge_offset != 0.0;
+        let bezier_distance = ctx.config.edge_bezier_distance * edge_offset;
+        let self_loop = (edge.from == edge.to).then(|| {
+            let radius = vertices
+                .iter()
+                .find(|vertex| vertex.id == edge.to)
+                .and_then(|vertex| vertex.radius)
+                .unwrap_or(default_vertex_radius);
+            self_loop_points(
+                from_pos,
+                radius,
+                loop_directions[&edge.from],
+                nested_self_loop_size(ctx.config.self_loop_size, edge_offset),
+            )
+        });
+        if let Some([_, control1, control2, _]) = self_loop {
+            // 端点はノードの境界から TikZ に求めさせる
+            let _ = writeln!(
+                body,
+                "  \\draw[{style}] ({from}) .. controls {} and {} .. ({to});",
+                point(control1),
+                point(control2)
+            );
+        } else if is_curved {
+            // 2 次ベジェ曲線を同じ形の 3 次ベジェ曲線に変換する
+            let control = calc_bezier_control_point(from_pos, to_pos, bezier_distance, false);
+            let (control1, control2) = quadratic_to_cubic(from_pos, control, to_pos);
+            let _ = writeln!(
+                body,
+                "  \\draw[{style}] ({from}) .. controls {} and {} .. ({to});",
+                point(control1),
+                point(control2)
+            );
+        } else {
+            let _ = writeln!(body, "  \\draw[{style}] ({from}) -- ({to});");
+        }
+
+        // 文字色を変えた辺だけラベルの色を上書きする
+        let label_style = match edge.text_color {
+            Some(color) => format!("ge edge label, text={}", colors.name("label", color)),
+            None => "ge edge label".to_string(),
+        };
+        if let Some(weight) = edge.weight.filter(|_| snapshot.is_weighted) {
+            let position = match self_loop {
+                Some(points) => cubic_bezier_curve(points, 0.5),
+                None => edge_midpoint(from_pos, to_pos, is_curved, bezier_distance),
+            };
+            let _ = writeln!(
+                body,
+                "  \\node[{label_style}] at {} {{{}}};",
+                point(position),
+                escape_latex(&weight.to_string())
+            );
+        }
+        if let Some(label) = edge.label.as_ref().filter(|label| !label.is_empty()) {
+            let stroke_width = edge.stroke_width.unwrap_or(ctx.config.edge_stroke);
+            let offset = ctx.config.edge_label_font_size + stroke_width;
+            let position = match self_loop {
+                Some(points) => self_loop_side_point(from_pos, points, offset),
+                None => edge_side_point(from_pos, to_pos, is_curved, bezier_distance, offset),
+            };
+            let _ = writeln!(
+                body,
+                "  \\node[{label_style}] at {} {{{}}};",
+                point(position),
+                escape_latex(label)
+            );
+        }
+    }
+
+    let mut tikz = String::new();
+    if options.standalone {
+        tikz.push_str("\\documentclass[tikz, border=2pt]{standalone}\n");
+        tikz.push_str("\\usetikzlibrary{arrows.meta}\n");
+        tikz.push_str("\\begin{document}\n");
+    } else {
+        tikz.push_str("% \\usepackage{tikz}\n");
+        tikz.push_str("% \\usetikzlibrary{arrows.meta}\n");
+    }
+    for (name, color) in &colors.definitions {
+        let [r, g, b, _] = color.to_srgba_unmultiplied();
+        let _ = writeln!(
+            tikz,
+            "\\definecolor{{{name}}}{{HTML}}{{{r:02X}{g:02X}{b:02X}}}"
+        );
+    }
+
+    let _ = writeln!(tikz, "\\begin{{tikzpicture}}[");
+    let _ = writeln!(tikz, "  x={PT_PER_PX}pt, y=-{PT_PER_PX}pt,");
+    let _ = writeln!(
+        tikz,
+        "  ge vertex/.style={{circle, draw={outline}, line width={}pt, minimum size={}pt, inner sep=0pt, font=\\fontsize{{{}pt}}{{{}pt}}\\selectfont}},",
+        format_number(ctx.config.vertex_stroke * PT_PER_PX),
+        format_number(default_vertex_radius * 2.0 * PT_PER_PX),
+        format_number(vertex_font_size * PT_PER_PX),
+        format_number(vertex_font_size * PT_PER_PX * 1.2),
+    );
+    let _ = writeln!(
+        tikz,
+        "  ge edge/.style={{line width={}pt}},",
+        format_number(ctx.config.edge_stroke * PT_PER_PX)
+    );
+    let _ = writeln!(
+        tikz,
+        "  ge edge label/.style={{fill={background}, text={label_color}, inner sep=1pt, font=\\fontsize{{{}pt}}{{{}pt}}\\selectfont}},",
+        format_number(ctx.config.edge_label_font_size * PT_PER_PX),
+        format_number(ctx.config.edge_label_font_size * PT_PER_PX * 1.2),
+    );
+    let _ = writeln!(
+        tikz,
+        "  >={{Stealth[length={}pt, width={}pt]}},",
+        format_number(ctx.config.edge_arrow_length * PT_PER_PX),
+        format_number(ctx.config.edge_arrow_width * 2.0 * PT_PER_PX),
+    );
+    tikz.push_str("]\n");
+    tikz.push_str(&body);
+    tikz.push_str("\\end{tikzpicture}\n");
+
+    if options.standalone {
+        tikz.push_str("\\end{document}\n");
+    }
+
+    Ok(tikz.into_bytes())
+}
+
+/// 使われた色に名前を付けて`\definecolor`で定義する
+#[derive(Default)]
+struct ColorTable {
+    definitions: Vec<(String, egui::Color32)>,
+}
+
+impl ColorTable {
+    fn name(&mut self, hint: &str, color: egui::Color32) -> String {
+        if let Some((name, _)) = self
+            .definitions
+            .iter()
+            .find(|(name, defined)| *defined == color && name.starts_with(&format!("ge{hint}")))
+        {
+            return name.clone();
+        }
+        let count = self
+            .definitions
+            .iter()
+            .filter(|(name, _)| name.starts_with(&format!("ge{hint}")))
+            .count();
+        let name = if count == 0 {
+            format!("ge{hint}")
+        } else {
+            format!("ge{hint}{}", letter_suffix(count))
+        };
+        self.definitions.push((name.clone(), color));
+        name
+    }
+}
+
+/// LaTeX の色名に数字を使えないため，2 個目以降の同種の色に`b`, `c`, ..., `z`, `ba`, ...を付ける．
+/// `a`を 0 とする 26 進数で`index`を表す
+fn letter_suffix(mut index: usize) -> String {
+    let mut suffix = String::new();
+    loop {
+        suffix.insert(0, (b'a' + (index % 26) as u8) as char);
+        index /= 26;
+        if index == 0 {
+            return suffix;
+        }
+    }
+}
+
+/// 2 次ベジェ曲線と同じ形になる 3 次ベジェ曲線の 2 つの制御点を求める
+fn quadratic_to_cubic(
+    start: egui::Pos2,
+    control: egui::Pos2,
+    end: egui::Pos2,
+) -> (egui::Pos2, egui::Pos2) {
+    (
+        start + (control - start) * (2.0 / 3.0),
+        end + (control - end) * (2.0 / 3.0),
+    )
+}
+
+fn color_hint(color: Colors, kind: &str) -> String {
+    match color {
+        Colors::Default => kind.to_string(),
+        Colors::Custom(_) => "custom".to_string(),
+        color => color.name().to_lowercase(),
+    }
+}
+
+fn format_number(value: f32) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" | "" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_latex, format_number, letter_suffix, quadratic_to_cubic, ColorTable};
+    use crate::math::bezier::{bezier_curve, cubic_bezier_curve};
+
+    #[test]
+    fn colors_of_the_same_hint_get_letter_suffixes() {
+        let mut colors = ColorTable::default();
+        let names: Vec<_> = (0..28)
+            .map(|i| colors.name("red", egui::Color32::from_gray(i)))
+            .collect();
+        assert_eq!(names[0], "gered");
+        assert_eq!(names[1], "geredb");
+        assert_eq!(names[25], "geredz");
+        assert_eq!(names[26], "geredba");
+        assert_eq!(names[27], "geredbb");
+
+        // 同じ色は同じ名前を使い回し，種類が違えば別に定義する
+        assert_eq!(colors.name("red", egui::Color32::from_gray(1)), "geredb");
+        assert_eq!(colors.name("edge", egui::Color32::from_gray(1)), "geedge");
+        assert_eq!(colors.definitions.len(), 29);
+
+        assert_eq!(letter_suffix(1), "b");
+        assert_eq!(letter_suffix(26 * 26), "baa");
+    }
+
+    #[test]
+    fn escape_latex_escapes_every_special_character() {
+        assert_eq!(
+            escape_latex("\\~^&%$#_{}a"),
+            "\\textbackslash{}\\textasciitilde{}\\textasciicircum{}\\&\\%\\$\\#\\_\\{\\}a"
+        );
+    }
+
+    #[test]
+    fn format_number_trims_zeros_and_negative_zero() {
+        assert_eq!(format_number(1.5), "1.5");
+        assert_eq!(format_number(2.0), "2");
+        assert_eq!(format_number(0.125), "0.12");
+        assert_eq!(format_number(-0.001), "0");
+        assert_eq!(format_number(0.0), "0");
+        assert_eq!(format_number(-3.25), "-3.25");
+    }
+
+    #[test]
+    fn cubic_control_points_trace_the_quadratic_curve() {
+        let (start, control, end) = (
+            egui::pos2(0.0, 0.0),
+            egui::pos2(30.0, 60.0),
+            egui::pos2(90.0, 0.0),
+        );
+        let (control1, control2) = quadratic_to_cubic(start, control, end);
+        assert_eq!(control1, egui::pos2(20.0, 40.0));
+        assert_eq!(control2, egui::pos2(50.0, 40.0));
+        for t in [0.0, 0.25, 0.5, 0.8, 1.0] {
+            let quadratic = bezier_curve(start, control, end, t);
+            let cubic = cubic_bezier_curve([start, control1, control2, end], t);
+            assert!(
+                quadratic.distance(cubic) < 1e-3,
+                "{t}: {quadratic:?} {cubic:?}"
+            );
+        }
+    }
+}
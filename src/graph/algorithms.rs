@@ -0,0 +1,633 @@
+//! グラフアルゴリズム
+//!
+//! 頂点は`Graph::vertices`の添字で表し，削除済みの頂点・辺は無視する．
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, VecDeque},
+};
+
+use super::{EdgeDirection, Graph};
+
+/// 探索の結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Traversal {
+    /// 訪問した順の頂点
+    pub order: Vec<usize>,
+    /// 探索木で各頂点に入る辺
+    pub parent_edge: Vec<Option<usize>>,
+    /// 探索木での深さ
+    pub depth: Vec<Option<usize>>,
+}
+
+/// 単一始点最短路の結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortestPaths {
+    pub dist: Vec<Option<f64>>,
+    /// 最短路木で各頂点に入る辺
+    pub parent_edge: Vec<Option<usize>>,
+}
+
+/// 閉路を構成する頂点と辺
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cycle {
+    pub vertices: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+/// アルゴリズムの実行過程で発生するイベント
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceEvent {
+    /// 頂点をキュー・スタックに積む
+    Push(usize),
+    /// 頂点をキュー・スタックから取り出す
+    Pop(usize),
+    /// 頂点を訪問する
+    Visit(usize),
+    /// 辺を調べる
+    Examine(usize),
+    /// 辺を使って頂点までの距離を更新する
+    Relax { edge: usize, to: usize, dist: f64 },
+}
+
+/// 隣接リスト（行き先の頂点，辺の添字）を作る．辺ごとに指定した向きを反映する．
+/// `follow_direction`が`false`の場合，有向グラフでも向きを無視する．
+pub fn adjacency(graph: &Graph, follow_direction: bool) -> Vec<Vec<(usize, usize)>> {
+    let n = graph.vertices.len();
+    let is_active = |v: usize| v < n && !graph.vertices[v].is_deleted;
+    let mut adj = vec![vec![]; n];
+
+    for (index, edge) in graph.edges.iter().enumerate() {
+        if edge.is_deleted || !is_active(edge.from) || !is_active(edge.to) {
+            continue;
+        }
+        adj[edge.from].push((edge.to, index));
+        let is_one_way = edge.direction.resolve(graph.is_directed) == EdgeDirection::Directed;
+        if edge.from != edge.to && (!is_one_way || !follow_direction) {
+            adj[edge.to].push((edge.from, index));
+        }
+    }
+
+    adj
+}
+
+/// 削除されていない頂点の添字
+fn active_vertices(graph: &Graph) -> impl Iterator<Item = usize> + '_ {
+    graph
+        .vertices
+        .iter()
+        .enumerate()
+        .filter(|(_, vertex)| !vertex.is_deleted)
+        .map(|(index, _)| index)
+}
+
+/// 幅優先探索
+pub fn bfs(graph: &Graph, start: usize) -> Traversal {
+    bfs_traced(graph, start, |_| {})
+}
+
+/// 幅優先探索（実行過程を`trace`に記録する）
+pub fn bfs_traced(graph: &Graph, start: usize, mut trace: impl FnMut(TraceEvent)) -> Traversal {
+    let adj = adjacency(graph, true);
+    let n = adj.len();
+    let mut traversal = Traversal {
+        order: vec![],
+        parent_edge: vec![None; n],
+        depth: vec![None; n],
+    };
+    if start >= n || graph.vertices[start].is_deleted {
+        return traversal;
+    }
+
+    let mut queue = VecDeque::from([start]);
+    traversal.depth[start] = Some(0);
+    trace(TraceEvent::Push(start));
+    while let Some(u) = queue.pop_front() {
+        trace(TraceEvent::Pop(u));
+        trace(TraceEvent::Visit(u));
+        traversal.order.push(u);
+        let depth = traversal.depth[u].unwrap_or(0);
+        for &(v, edge) in &adj[u] {
+            trace(TraceEvent::Examine(edge));
+            if traversal.depth[v].is_none() {
+                traversal.depth[v] = Some(depth + 1);
+                traversal.parent_edge[v] = Some(edge);
+                trace(TraceEvent::Relax {
+                    edge,
+                    to: v,
+                    dist: (depth + 1) as f64,
+                });
+                trace(TraceEvent::Push(v));
+                queue.push_back(v);
+            }
+        }
+    }
+
+    traversal
+}
+
+/// 深さ優先探索（行きがけ順）
+pub fn dfs(graph: &Graph, start: usize) -> Traversal {
+    dfs_traced(graph, start, |_| {})
+}
+
+/// 深さ優先探索（実行過程を`trace`に記録する）
+pub fn dfs_traced(graph: &Graph, start: usize, mut trace: impl FnMut(TraceEvent)) -> Traversal {
+    let adj = adjacency(graph, true);
+    let n = adj.len();
+    let mut traversal = Traversal {
+        order: vec![],
+        parent_edge: vec![None; n],
+        depth: vec![None; n],
+    };
+    if start >= n || graph.vertices[start].is_deleted {
+        return traversal;
+    }
+
+    // (頂点, 次に調べる隣接頂点の位置)
+    let mut stack = vec![(start, 0)];
+    traversal.depth[start] = Some(0);
+    traversal.order.push(start);
+    trace(TraceEvent::Push(start));
+    trace(TraceEvent::Visit(start));
+    while let Some((u, next)) = stack.last_mut() {
+        let u = *u;
+        let Some(&(v, edge)) = adj[u].get(*next) else {
+            trace(TraceEvent::Pop(u));
+            stack.pop();
+            continue;
+        };
+        *next += 1;
+        trace(TraceEvent::Examine(edge));
+        if traversal.depth[v].is_none() {
+            let depth = traversal.depth[u].map(|depth| depth + 1);
+            traversal.depth[v] = depth;
+            traversal.parent_edge[v] = Some(edge);
+            traversal.order.push(v);
+            trace(TraceEvent::Relax {
+                edge,
+                to: v,
+                dist: depth.unwrap_or(0) as f64,
+            });
+            trace(TraceEvent::Push(v));
+            trace(TraceEvent::Visit(v));
+            stack.push((v, 0));
+        }
+    }
+
+    traversal
+}
+
+/// 単一始点最短路．重みなしグラフでは BFS，重み付きグラフでは Dijkstra 法を用いる．
+/// 重みが設定されていない辺の重みは 1 とみなす．
+pub fn shortest_paths(graph: &Graph, start: usize) -> anyhow::Result<ShortestPaths> {
+    if !graph.is_weighted {
+        let traversal = bfs(graph, start);
+        return Ok(ShortestPaths {
+            dist: traversal
+                .depth
+                .iter()
+                .map(|depth| depth.map(|depth| depth as f64))
+                .collect(),
+            parent_edge: traversal.parent_edge,
+        });
+    }
+
+    dijkstra_traced(graph, start, |_| {})
+}
+
+/// 負の重みを持つ（削除されていない）辺の番号
+pub fn negative_edge(graph: &Graph) -> Option<usize> {
+    (0..graph.edges.len())
+        .find(|&index| !graph.edges[index].is_deleted && edge_cost(graph, index) < 0.0)
+}
+
+/// Dijkstra 法（実行過程を`trace`に記録する）．負の重みの辺がある場合はエラー（頂点番号は 0 始まり）．
+pub fn dijkstra_traced(
+    graph: &Graph,
+    start: usize,
+    mut trace: impl FnMut(TraceEvent),
+) -> anyhow::Result<ShortestPaths> {
+    if let Some(index) = negative_edge(graph) {
+        return Err(anyhow::anyhow!(
+            "Negative weight on edge {} -> {}",
+            graph.edges[index].from,
+            graph.edges[index].to
+        ));
+    }
+
+    let adj = adjacency(graph, true);
+    let n = adj.len();
+    let mut result = ShortestPaths {
+        dist: vec![None; n],
+        parent_edge: vec![None; n],
+    };
+    if start >= n || graph.vertices[start].is_deleted {
+        return Ok(result);
+    }
+
+    let mut heap = BinaryHeap::from([Reverse(HeapEntry(0.0, start))]);
+    result.dist[start] = Some(0.0);
+    trace(TraceEvent::Push(start));
+    while let Some(Reverse(HeapEntry(d, u))) = heap.pop() {
+        if result.dist[u].is_some_and(|best| d > best) {
+            continue;
+        }
+        trace(TraceEvent::Pop(u));
+        trace(TraceEvent::Visit(u));
+        for &(v, edge) in &adj[u] {
+            trace(TraceEvent::Examine(edge));
+            let next = d + edge_cost(graph, edge);
+            if result.dist[v].is_none_or(|best| next < best) {
+                result.dist[v] = Some(next);
+                result.parent_edge[v] = Some(edge);
+                trace(TraceEvent::Relax {
+                    edge,
+                    to: v,
+                    dist: next,
+                });
+                trace(TraceEvent::Push(v));
+                heap.push(Reverse(HeapEntry(next, v)));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// 辺の重み（重みなしグラフ，または未設定の場合は 1）
+pub fn edge_cost(graph: &Graph, edge: usize) -> f64 {
+    graph.edges[edge]
+        .weight
+        .filter(|_| graph.is_weighted)
+        .map(|weight| weight.as_f64())
+        .unwrap_or(1.0)
+}
+
+/// Dijkstra 法のヒープに積む (距離, 頂点)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HeapEntry(f64, usize);
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+/// 連結成分分解（有向グラフでは弱連結成分）．削除済みの頂点は`None`．
+pub fn connected_components(graph: &Graph) -> Vec<Option<usize>> {
+    let adj = adjacency(graph, false);
+    let mut component = vec![None; adj.len()];
+    let mut count = 0;
+
+    for start in active_vertices(graph) {
+        if component[start].is_some() {
+            continue;
+        }
+        component[start] = Some(count);
+        let mut stack = vec![start];
+        while let Some(u) = stack.pop() {
+            for &(v, _) in &adj[u] {
+                if component[v].is_none() {
+                    component[v] = Some(count);
+                    stack.push(v);
+                }
+            }
+        }
+        count += 1;
+    }
+
+    component
+}
+
+/// 強連結成分分解（Kosaraju 法）．成分の番号はトポロジカル順．
+pub fn strongly_connected_components(graph: &Graph) -> Vec<Option<usize>> {
+    let adj = adjacency(graph, true);
+    let n = adj.len();
+    let mut reverse_adj = vec![vec![]; n];
+    for (u, neighbors) in adj.iter().enumerate() {
+        for &(v, edge) in neighbors {
+            reverse_adj[v].push((u, edge));
+        }
+    }
+
+    // 帰りがけ順を求める
+    let mut visited = vec![false; n];
+    let mut finished = Vec::with_capacity(n);
+    for start in active_vertices(graph) {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut stack = vec![(start, 0)];
+        while let Some((u, next)) = stack.last_mut() {
+            let u = *u;
+            if let Some(&(v, _)) = adj[u].get(*next) {
+                *next += 1;
+                if !visited[v] {
+                    visited[v] = true;
+                    stack.push((v, 0));
+                }
+            } else {
+                finished.push(u);
+                stack.pop();
+            }
+        }
+    }
+
+    // 逆グラフを帰りがけ順の逆から探索する
+    let mut component = vec![None; n];
+    let mut count = 0;
+    for &start in finished.iter().rev() {
+        if component[start].is_some() {
+            continue;
+        }
+        component[start] = Some(count);
+        let mut stack = vec![start];
+        while let Some(u) = stack.pop() {
+            for &(v, _) in &reverse_adj[u] {
+                if component[v].is_none() {
+                    component[v] = Some(count);
+                    stack.push(v);
+                }
+            }
+        }
+        count += 1;
+    }
+
+    component
+}
+
+/// トポロジカルソート（Kahn 法）．閉路がある場合は`None`．
+/// 無向グラフは各辺を両向きの辺とみなす．
+pub fn topological_sort(graph: &Graph) -> Option<Vec<usize>> {
+    let adj = adjacency(graph, true);
+    let mut indegree = vec![0; adj.len()];
+    for neighbors in &adj {
+        for &(v, _) in neighbors {
+            indegree[v] += 1;
+        }
+    }
+
+    let mut queue: VecDeque<usize> = active_vertices(graph)
+        .filter(|&v| indegree[v] == 0)
+        .collect();
+    let mut order = vec![];
+    while let Some(u) = queue.pop_front() {
+        order.push(u);
+        for &(v, _) in &adj[u] {
+            indegree[v] -= 1;
+            if indegree[v] == 0 {
+                queue.push_back(v);
+            }
+        }
+    }
+
+    (order.len() == active_vertices(graph).count()).then_some(order)
+}
+
+/// 二部グラフ判定．辺の向きは無視する．
+/// 二部グラフの場合は各頂点の側を，そうでない場合は矛盾した辺を返す．
+pub fn bipartition(graph: &Graph) -> Result<Vec<Option<bool>>, usize> {
+    let adj = adjacency(graph, false);
+    let mut side = vec![None; adj.len()];
+
+    for start in active_vertices(graph) {
+        if side[start].is_some() {
+            continue;
+        }
+        side[start] = Some(false);
+        let mut queue = VecDeque::from([start]);
+        while let Some(u) = queue.pop_front() {
+            let current = side[u].unwrap_or(false);
+            for &(v, edge) in &adj[u] {
+                match side[v] {
+                    None => {
+                        side[v] = Some(!current);
+                        queue.push_back(v);
+                    }
+                    Some(other) if other == current => return Err(edge),
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+
+    Ok(side)
+}
+
+/// 閉路を 1 つ見つける．有向グラフでは辺の向きに沿った閉路を探す．
+/// 辺ごとに向きを指定した場合は，その向きに従う．
+pub fn find_cycle(graph: &Graph) -> Option<Cycle> {
+    const WHITE: u8 = 0;
+    const GRAY: u8 = 1;
+    const BLACK: u8 = 2;
+
+    let adj = adjacency(graph, true);
+    let n = adj.len();
+    let mut state = vec![WHITE; n];
+    let mut parent_edge: Vec<Option<usize>> = vec![None; n];
+
+    for start in active_vertices(graph) {
+        if state[start] != WHITE {
+            continue;
+        }
+        state[start] = GRAY;
+        let mut stack = vec![(start, 0)];
+        while let Some((u, next)) = stack.last_mut() {
+            let u = *u;
+            let Some(&(v, edge)) = adj[u].get(*next) else {
+                state[u] = BLACK;
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+
+            // 向きのない辺では，たどってきた辺を戻らない
+            if parent_edge[u] == Some(edge)
+                && graph.edges[edge].direction.resolve(graph.is_directed) != EdgeDirection::Directed
+            {
+                continue;
+            }
+            match state[v] {
+                WHITE => {
+                    state[v] = GRAY;
+                    parent_edge[v] = Some(edge);
+                    stack.push((v, 0));
+                }
+                GRAY => {
+                    // u から親をたどって v まで戻る
+                    let mut vertices = vec![u];
+                    let mut edges = vec![edge];
+                    let mut current = u;
+                    while current != v {
+                        let Some(parent) = parent_edge[current] else {
+                            break;
+                        };
+                        edges.push(parent);
+                        let e = &graph.edges[parent];
+                        current = if e.to == current { e.from } else { e.to };
+                        vertices.push(current);
+                    }
+                    vertices.reverse();
+                    edges.reverse();
+                    return Some(Cycle { vertices, edges });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bfs, bipartition, connected_components, dfs, dijkstra_traced, find_cycle, shortest_paths,
+        strongly_connected_components, topological_sort, TraceEvent,
+    };
+    use crate::graph::{BaseGraph, Edge, EdgeDirection, EdgeWeight, Graph};
+
+    fn build(input: &str, is_directed: bool, weighted: bool) -> Graph {
+        let base = BaseGraph::parse(input, true, weighted).unwrap();
+        let mut graph = Graph::default();
+        graph.clear();
+        graph.is_directed = is_directed;
+        graph.is_weighted = weighted;
+        for _ in 0..base.n {
+            graph.add_vertex(egui::Pos2::ZERO);
+        }
+        for (index, &(from, to)) in base.edges.iter().enumerate() {
+            let weight = base.weights.as_ref().map(|weights| weights[index]);
+            graph.edges.push(Edge::with_weight(from, to, weight));
+        }
+        graph
+    }
+
+    #[test]
+    fn bfs_and_dfs_visit_reachable_vertices() {
+        let graph = build("5 4\n0 1\n0 2\n1 3\n2 3", false, false);
+
+        let bfs = bfs(&graph, 0);
+        assert_eq!(bfs.order, vec![0, 1, 2, 3]);
+        assert_eq!(bfs.depth[3], Some(2));
+        assert_eq!(bfs.depth[4], None);
+
+        let dfs = dfs(&graph, 0);
+        assert_eq!(dfs.order, vec![0, 1, 3, 2]);
+        assert_eq!(dfs.depth[2], Some(3));
+    }
+
+    #[test]
+    fn per_edge_directions_override_the_graph_setting() {
+        let mut graph = build("3 2\n1 0\n1 2", true, false);
+        assert_eq!(bfs(&graph, 0).order, vec![0]);
+
+        graph.edges[0].direction = EdgeDirection::Undirected;
+        assert_eq!(bfs(&graph, 0).order, vec![0, 1, 2]);
+        // 向きのない辺を往復しても閉路にはならない
+        assert!(find_cycle(&graph).is_none());
+
+        graph.is_directed = false;
+        graph.edges[1].direction = EdgeDirection::Directed;
+        assert_eq!(bfs(&graph, 2).order, vec![2]);
+    }
+
+    #[test]
+    fn shortest_paths_use_weights_when_weighted() {
+        let mut graph = build("3 3\n0 1 5\n0 2 1\n2 1 1", true, true);
+        let paths = shortest_paths(&graph, 0).unwrap();
+        assert_eq!(paths.dist, vec![Some(0.0), Some(2.0), Some(1.0)]);
+
+        graph.edges[1].weight = Some(EdgeWeight::Int(-1));
+        assert!(shortest_paths(&graph, 0).is_err());
+    }
+
+    #[test]
+    fn reverting_edges_keeps_per_edge_directions() {
+        let mut graph = build("3 2\n0 1\n1 2", false, false);
+        graph.edges[1].direction = EdgeDirection::Directed;
+        assert_eq!(bfs(&graph, 2).order, vec![2]);
+
+        graph.revert_edges();
+        assert_eq!((graph.edges[1].from, graph.edges[1].to), (2, 1));
+        assert_eq!(graph.edges[0].direction, EdgeDirection::Inherit);
+        assert_eq!(graph.edges[1].direction, EdgeDirection::Directed);
+        assert_eq!(bfs(&graph, 2).order, vec![2, 1, 0]);
+        assert_eq!(bfs(&graph, 1).order, vec![1, 0]);
+    }
+
+    #[test]
+    fn traced_dijkstra_relaxes_every_improvement() {
+        let graph = build("3 3\n0 1 5\n0 2 1\n2 1 1", true, true);
+        let mut events = vec![];
+        dijkstra_traced(&graph, 0, |event| events.push(event)).unwrap();
+
+        let relaxed: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                TraceEvent::Relax { to, dist, .. } => Some((*to, *dist)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(relaxed, vec![(1, 5.0), (2, 1.0), (1, 2.0)]);
+        assert_eq!(events.first(), Some(&TraceEvent::Push(0)));
+    }
+
+    #[test]
+    fn components_and_scc() {
+        let graph = build("5 4\n0 1\n1 0\n1 2\n3 4", true, false);
+
+        let cc = connected_components(&graph);
+        assert_eq!(cc[0], cc[2]);
+        assert_ne!(cc[0], cc[3]);
+
+        let scc = strongly_connected_components(&graph);
+        assert_eq!(scc[0], scc[1]);
+        assert_ne!(scc[1], scc[2]);
+        assert!(scc[1] < scc[2]);
+    }
+
+    #[test]
+    fn topological_sort_and_cycle_detection() {
+        let dag = build("3 2\n0 1\n2 1", true, false);
+        let order = topological_sort(&dag).unwrap();
+        assert_eq!(order.last(), Some(&1));
+        assert!(find_cycle(&dag).is_none());
+
+        let cyclic = build("3 3\n0 1\n1 2\n2 0", true, false);
+        assert!(topological_sort(&cyclic).is_none());
+        let cycle = find_cycle(&cyclic).unwrap();
+        assert_eq!(cycle.vertices.len(), 3);
+        assert_eq!(cycle.edges.len(), 3);
+    }
+
+    #[test]
+    fn undirected_cycle_ignores_the_tree_edge() {
+        let path = build("3 2\n0 1\n1 2", false, false);
+        assert!(find_cycle(&path).is_none());
+
+        let triangle = build("3 3\n0 1\n1 2\n2 0", false, false);
+        assert_eq!(find_cycle(&triangle).unwrap().vertices.len(), 3);
+    }
+
+    #[test]
+    fn bipartition_detects_odd_cycles() {
+        let square = build("4 4\n0 1\n1 2\n2 3\n3 0", false, false);
+        let sides = bipartition(&square).unwrap();
+        assert_ne!(sides[0], sides[1]);
+        assert_eq!(sides[0], sides[2]);
+
+        let triangle = build("3 3\n0 1\n1 2\n2 0", false, false);
+        assert!(bipartition(&triangle).is_err());
+    }
+}
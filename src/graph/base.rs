@@ -1,78 +1,492 @@
+use std::fmt;
+
+/// 辺の重み（整数または実数）
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum EdgeWeight {
+    Int(i64),
+    Float(f64),
+}
+
+impl EdgeWeight {
+    /// 文字列から重みを読み取る．整数として読めない場合は実数として扱う．
+    pub fn parse(token: &str) -> anyhow::Result<Self> {
+        if let Ok(value) = token.parse::<i64>() {
+            return Ok(Self::Int(value));
+        }
+        match token.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(Self::Float(value)),
+            _ => Err(anyhow::anyhow!("Invalid weight: {}", token)),
+        }
+    }
+
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Self::Int(value) => value as f64,
+            Self::Float(value) => value,
+        }
+    }
+
+    /// 整数と実数の違いによらず，値が等しいかどうか
+    pub fn same_value(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (a, b) => a.as_f64() == b.as_f64(),
+        }
+    }
+}
+
+impl fmt::Display for EdgeWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BaseGraph {
     pub n: usize,
     pub edges: Vec<(usize, usize)>,
+    /// 重み付きグラフの場合，各辺の重み
+    pub weights: Option<Vec<EdgeWeight>>,
+}
+
+/// 辺リストを読み込むときの設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EdgeListOptions {
+    /// 頂点番号に 0 または N が現れるかどうかで，0-indexed / 1-indexed を判定する
+    pub detect_indexing: bool,
+    /// 1 行目が `N` だけの場合，辺の数 `M` が省略されているものとして読み込む
+    pub detect_edge_count: bool,
+    /// 辺の後に続く行（クエリなど）を無視する
+    pub ignore_trailing: bool,
+}
+
+/// 入力の問題点と，その位置（行・列は 1 始まり）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDiagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// 辺リストの読み込みで見つかったすべての問題
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub diagnostics: Vec<ParseDiagnostic>,
+}
+
+impl ParseError {
+    /// エラー表示に含める問題の最大数
+    const MAX_DISPLAYED: usize = 20;
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, diagnostic) in self
+            .diagnostics
+            .iter()
+            .take(Self::MAX_DISPLAYED)
+            .enumerate()
+        {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{diagnostic}")?;
+        }
+        if self.diagnostics.len() > Self::MAX_DISPLAYED {
+            write!(
+                f,
+                "\n... and {} more",
+                self.diagnostics.len() - Self::MAX_DISPLAYED
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 辺リストの読み込み結果
+#[derive(Debug)]
+pub struct ParsedEdgeList {
+    pub graph: BaseGraph,
+    /// 読み込みに使った頂点番号の形式（自動判定した場合はその結果）
+    pub zero_indexed: bool,
+}
+
+/// 入力中のトークンと，その位置
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    line: usize,
+    column: usize,
+    text: &'a str,
+}
+
+impl Token<'_> {
+    fn diagnostic(&self, message: impl Into<String>) -> ParseDiagnostic {
+        ParseDiagnostic {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    /// トークンの直後の位置
+    fn end_diagnostic(&self, message: impl Into<String>) -> ParseDiagnostic {
+        ParseDiagnostic {
+            line: self.line,
+            column: self.column + self.text.chars().count(),
+            message: message.into(),
+        }
+    }
+}
+
+/// `#` 以降をコメントとして除き，入力をトークンに分割する
+fn tokenize(input_text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for (i, line) in input_text.lines().enumerate() {
+        let line_text = line.split('#').next().unwrap_or_default();
+        let mut start = None;
+        for (column, (offset, c)) in line_text.char_indices().enumerate() {
+            match (c.is_ascii_whitespace(), start) {
+                (false, None) => start = Some((column, offset)),
+                (true, Some((token_column, token_offset))) => {
+                    tokens.push(Token {
+                        line: i + 1,
+                        column: token_column + 1,
+                        text: &line_text[token_offset..offset],
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some((token_column, token_offset)) = start {
+            tokens.push(Token {
+                line: i + 1,
+                column: token_column + 1,
+                text: &line_text[token_offset..],
+            });
+        }
+    }
+    tokens
 }
 
 impl BaseGraph {
     /// 文字列からグラフの基本構造を生成する．
     /// ```text
     /// N M
-    /// u_1 v_1
+    /// u_1 v_1 [w_1]
     /// ...
-    /// u_M v_M
+    /// u_M v_M [w_M]
     /// ```
-    pub fn parse(input_text: &str, zero_indexed: bool) -> anyhow::Result<Self> {
-        let mut source = input_text
-            .split_ascii_whitespace()
-            .map(|s| s.parse::<usize>());
-
-        let n = source
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("Insufficient input"))??;
-        let m = source
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("Insufficient input"))??;
-
-        let edges = (0..m)
-            .map(|_| {
-                let mut from = source
-                    .next()
-                    .ok_or_else(|| anyhow::anyhow!("Insufficient input"))??;
-                let mut to = source
-                    .next()
-                    .ok_or_else(|| anyhow::anyhow!("Insufficient input"))??;
-
-                if !zero_indexed {
-                    from = from
-                        .checked_sub(1)
-                        .ok_or_else(|| anyhow::anyhow!("Invalid edge: {} {}", from, to))?;
-                    to = to
-                        .checked_sub(1)
-                        .ok_or_else(|| anyhow::anyhow!("Invalid edge: {} {}", from, to))?;
+    /// `weighted` が `true` の場合，各辺の 3 つ目の値を重みとして読み取る．
+    pub fn parse(input_text: &str, zero_indexed: bool, weighted: bool) -> anyhow::Result<Self> {
+        let parsed = Self::parse_edge_list(
+            input_text,
+            zero_indexed,
+            weighted,
+            EdgeListOptions::default(),
+        )?;
+        Ok(parsed.graph)
+    }
+
+    /// 辺リストを空白区切りのトークン列として読み込む．`#` 以降はコメントとして無視する．
+    /// `M` を省略した場合に限り，1 行に 1 辺として読み込む．
+    /// 問題が見つかっても読み込みを続け，すべての問題を行・列とともに返す．
+    pub fn parse_edge_list(
+        input_text: &str,
+        zero_indexed: bool,
+        weighted: bool,
+        options: EdgeListOptions,
+    ) -> Result<ParsedEdgeList, ParseError> {
+        let tokens = tokenize(input_text);
+        let mut diagnostics = Vec::new();
+        let fail = |mut diagnostics: Vec<ParseDiagnostic>| {
+            diagnostics.sort_by_key(|d| (d.line, d.column));
+            Err(ParseError { diagnostics })
+        };
+
+        let Some(n_token) = tokens.first() else {
+            return fail(vec![ParseDiagnostic {
+                line: 1,
+                column: 1,
+                message: "Insufficient input: missing N M".to_string(),
+            }]);
+        };
+
+        let parse_usize = |token: &Token<'_>, what: &str| {
+            token
+                .text
+                .parse::<usize>()
+                .map_err(|_| token.diagnostic(format!("Invalid {what}: {}", token.text)))
+        };
+
+        // `M` を省略できるのは，1 行目が `N` だけの場合
+        let m_token = tokens
+            .get(1)
+            .filter(|token| !options.detect_edge_count || token.line == n_token.line);
+        let n = parse_usize(n_token, "N");
+        let m = match m_token {
+            Some(token) => parse_usize(token, "M").map(Some),
+            None if options.detect_edge_count => Ok(None),
+            None => Err(n_token.end_diagnostic("Insufficient input: missing M")),
+        };
+        let (n, m) = match (n, m) {
+            (Ok(n), Ok(m)) => (n, m),
+            (n, m) => {
+                diagnostics.extend(n.err());
+                diagnostics.extend(m.err());
+                return fail(diagnostics);
+            }
+        };
+
+        let width = if weighted { 3 } else { 2 };
+        let body = &tokens[if m.is_some() { 2 } else { 1 }..];
+
+        let rows: Vec<&[Token<'_>]> = match m {
+            Some(m) => {
+                // 改行の位置によらず，トークンを順に `width` 個ずつ辺として読む
+                let (edge_tokens, rest) = body.split_at(m.saturating_mul(width).min(body.len()));
+                let rows: Vec<_> = edge_tokens.chunks_exact(width).collect();
+                if rows.len() < m {
+                    let last = tokens.last().unwrap_or(n_token);
+                    diagnostics.push(last.end_diagnostic(format!(
+                        "Insufficient input: expected {m} edges, found {}",
+                        rows.len()
+                    )));
+                } else if let Some(token) = rest.first().filter(|_| !options.ignore_trailing) {
+                    diagnostics.push(token.diagnostic("Excessive input"));
                 }
+                rows
+            }
+            None => {
+                let looks_like_edge = |row: &[Token<'_>]| {
+                    row.len() == width
+                        && row[..2]
+                            .iter()
+                            .all(|token| token.text.parse::<usize>().is_ok())
+                };
+                body.chunk_by(|a, b| a.line == b.line)
+                    .take_while(|row| !options.ignore_trailing || looks_like_edge(row))
+                    .collect()
+            }
+        };
 
-                if from >= n || to >= n {
-                    return Err(anyhow::anyhow!("Invalid edge: {} {}", from, to));
+        // 頂点番号は添字の形式が決まってから範囲を確認する
+        let mut raw_edges = Vec::new();
+        let mut weights = Vec::new();
+
+        for row in rows {
+            if row.len() < 2 {
+                diagnostics.push(row[0].end_diagnostic("Insufficient input: expected u v"));
+                continue;
+            }
+            if let Some(token) = row.get(width) {
+                diagnostics.push(token.diagnostic("Excessive input"));
+            }
+
+            let from = parse_usize(&row[0], "vertex");
+            let to = parse_usize(&row[1], "vertex");
+            let weight = if weighted {
+                match row.get(2) {
+                    Some(token) => EdgeWeight::parse(token.text)
+                        .map(Some)
+                        .map_err(|err| token.diagnostic(err.to_string())),
+                    None => Err(row[1].end_diagnostic("Insufficient input: missing weight")),
+                }
+            } else {
+                Ok(None)
+            };
+
+            match (from, to, weight) {
+                (Ok(from), Ok(to), Ok(weight)) => {
+                    raw_edges.push(((from, row[0]), (to, row[1])));
+                    weights.extend(weight);
+                }
+                (from, to, weight) => {
+                    diagnostics.extend(from.err());
+                    diagnostics.extend(to.err());
+                    diagnostics.extend(weight.err());
                 }
+            }
+        }
 
-                anyhow::Ok((from, to))
-            })
-            .collect::<anyhow::Result<_>>()?;
+        let zero_indexed = if options.detect_indexing {
+            let mut values = raw_edges.iter().flat_map(|((u, _), (v, _))| [*u, *v]);
+            if values.clone().any(|value| value == 0) {
+                true
+            } else if values.any(|value| value == n) {
+                false
+            } else {
+                zero_indexed
+            }
+        } else {
+            zero_indexed
+        };
 
-        if source.next().is_some() {
-            return Err(anyhow::anyhow!("Excessive input"));
+        let mut edges = Vec::with_capacity(raw_edges.len());
+        for ((from, from_token), (to, to_token)) in raw_edges {
+            let to_index = |value: usize, token: Token<'_>| {
+                let index = if zero_indexed {
+                    Some(value)
+                } else {
+                    value.checked_sub(1)
+                };
+                index.filter(|&index| index < n).ok_or_else(|| {
+                    token.diagnostic(format!("Invalid edge: vertex {value} is out of range"))
+                })
+            };
+            match (to_index(from, from_token), to_index(to, to_token)) {
+                (Ok(from), Ok(to)) => edges.push((from, to)),
+                (from, to) => {
+                    diagnostics.extend(from.err());
+                    diagnostics.extend(to.err());
+                }
+            }
+        }
+
+        if !diagnostics.is_empty() {
+            return fail(diagnostics);
         }
 
-        Ok(Self { n, edges })
+        Ok(ParsedEdgeList {
+            graph: Self {
+                n,
+                edges,
+                weights: weighted.then_some(weights),
+            },
+            zero_indexed,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::BaseGraph;
+    use super::{BaseGraph, EdgeListOptions, EdgeWeight};
 
     #[test]
     fn parse_rejects_out_of_range_vertex_for_one_indexed_input() {
         let input = "3 1\n4 1\n";
-        let err = BaseGraph::parse(input, false).unwrap_err();
+        let err = BaseGraph::parse(input, false, false).unwrap_err();
         assert!(err.to_string().contains("Invalid edge"));
     }
 
     #[test]
     fn parse_rejects_out_of_range_vertex_for_zero_indexed_input() {
         let input = "3 1\n3 0\n";
-        let err = BaseGraph::parse(input, true).unwrap_err();
+        let err = BaseGraph::parse(input, true, false).unwrap_err();
         assert!(err.to_string().contains("Invalid edge"));
     }
+
+    #[test]
+    fn parse_reads_integer_and_float_weights() {
+        let input = "3 2\n1 2 5\n2 3 -1.5\n";
+        let graph = BaseGraph::parse(input, false, true).unwrap();
+        assert_eq!(graph.edges, vec![(0, 1), (1, 2)]);
+        assert_eq!(
+            graph.weights,
+            Some(vec![EdgeWeight::Int(5), EdgeWeight::Float(-1.5)])
+        );
+    }
+
+    #[test]
+    fn parse_rejects_weight_token_when_unweighted() {
+        let input = "2 1\n1 2 5\n";
+        let err = BaseGraph::parse(input, false, false).unwrap_err();
+        assert!(err.to_string().contains("Excessive input"));
+    }
+
+    #[test]
+    fn parse_rejects_missing_weight() {
+        let input = "2 1\n1 2\n";
+        let err = BaseGraph::parse(input, false, true).unwrap_err();
+        assert!(err.to_string().contains("Insufficient input"));
+    }
+
+    #[test]
+    fn parse_edge_list_reports_every_problem_with_position() {
+        let input = "# sample\n3 2 # N M\n1 2\n2 x\n5 1\n";
+        let err = BaseGraph::parse_edge_list(input, false, false, EdgeListOptions::default())
+            .unwrap_err();
+        let positions: Vec<_> = err
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.column))
+            .collect();
+        assert_eq!(positions, vec![(4, 3), (5, 1)]);
+        assert_eq!(
+            err.to_string(),
+            "line 4, column 3: Invalid vertex: x\nline 5, column 1: Excessive input"
+        );
+    }
+
+    #[test]
+    fn parse_accepts_tokens_regardless_of_line_breaks() {
+        for input in ["3 2 1 2 2 3", "3\n2\n1 2\n2 3"] {
+            let graph = BaseGraph::parse(input, false, false).unwrap();
+            assert_eq!(graph.n, 3);
+            assert_eq!(graph.edges, vec![(0, 1), (1, 2)]);
+        }
+
+        let err = BaseGraph::parse("3 2\n1 2\n2", false, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 2: Insufficient input: expected 2 edges, found 1"
+        );
+    }
+
+    #[test]
+    fn parse_edge_list_detects_indexing_and_missing_edge_count() {
+        let options = EdgeListOptions {
+            detect_indexing: true,
+            detect_edge_count: true,
+            ..Default::default()
+        };
+        let parsed = BaseGraph::parse_edge_list("3\n0 1\n1 2\n", false, false, options).unwrap();
+        assert!(parsed.zero_indexed);
+        assert_eq!(parsed.graph.edges, vec![(0, 1), (1, 2)]);
+
+        let parsed = BaseGraph::parse_edge_list("3\n3 1\n1 2\n", true, false, options).unwrap();
+        assert!(!parsed.zero_indexed);
+        assert_eq!(parsed.graph.edges, vec![(2, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn parse_edge_list_ignores_trailing_lines() {
+        let input = "3 2\n1 2\n2 3\n2\n1 3\n";
+        let err = BaseGraph::parse(input, false, false).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("line 4, column 1: Excessive input"));
+
+        let options = EdgeListOptions {
+            ignore_trailing: true,
+            ..Default::default()
+        };
+        let parsed = BaseGraph::parse_edge_list(input, false, false, options).unwrap();
+        assert_eq!(parsed.graph.edges, vec![(0, 1), (1, 2)]);
+
+        let options = EdgeListOptions {
+            detect_edge_count: true,
+            ignore_trailing: true,
+            ..Default::default()
+        };
+        let input = "3\n1 2 4\n2 3 1.5\n1\n3 1\n";
+        let parsed = BaseGraph::parse_edge_list(input, false, true, options).unwrap();
+        assert_eq!(parsed.graph.edges, vec![(0, 1), (1, 2)]);
+    }
 }
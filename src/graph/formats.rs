@@ -0,0 +1,696 @@
+//! 辺リスト以外のテキスト形式（隣接行列・隣接リスト・親配列）の読み書き
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+
+use super::base::{ParseDiagnostic, ParseError};
+use super::{BaseGraph, EdgeWeight, Graph};
+
+/// I/O タブで扱うグラフのテキスト形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphTextFormat {
+    /// `N M` に続けて辺を 1 行ずつ並べる形式
+    #[default]
+    EdgeList,
+    /// `N` に続けて N×N の行列を並べる形式
+    AdjacencyMatrix,
+    /// `N` に続けて各頂点の `k v_1 ... v_k` を並べる形式
+    AdjacencyList,
+    /// `N` に続けて頂点 2, ..., N の親 `p_2 ... p_N` を並べる形式（根付き木）
+    ParentArray,
+}
+
+impl GraphTextFormat {
+    pub const ALL: [Self; 4] = [
+        Self::EdgeList,
+        Self::AdjacencyMatrix,
+        Self::AdjacencyList,
+        Self::ParentArray,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::EdgeList => "Edge List",
+            Self::AdjacencyMatrix => "Adjacency Matrix",
+            Self::AdjacencyList => "Adjacency List",
+            Self::ParentArray => "Parent Array",
+        }
+    }
+}
+
+/// `#` 以降のコメントと空行を除いた各行を，行番号（1 始まり）とトークンの組として返す
+fn token_lines(input_text: &str) -> impl Iterator<Item = (usize, Vec<&str>)> {
+    input_text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line.split('#').next().unwrap_or_default();
+            (i + 1, line.split_ascii_whitespace().collect::<Vec<_>>())
+        })
+        .filter(|(_, tokens)| !tokens.is_empty())
+}
+
+fn parse_count(line: usize, token: &str) -> anyhow::Result<usize> {
+    token
+        .parse::<usize>()
+        .map_err(|_| anyhow::anyhow!("line {line}: expected a non-negative integer, found {token}"))
+}
+
+/// 頂点番号を読み取り，0-indexed に直して範囲を確認する
+fn parse_vertex(line: usize, token: &str, n: usize, zero_indexed: bool) -> anyhow::Result<usize> {
+    let value = parse_count(line, token)?;
+    let vertex = if zero_indexed {
+        Some(value)
+    } else {
+        value.checked_sub(1)
+    };
+    vertex
+        .filter(|&vertex| vertex < n)
+        .ok_or_else(|| anyhow::anyhow!("line {line}: vertex {value} is out of range"))
+}
+
+/// `token`が`input_text`の`line`行目の何文字目（1 始まり）から始まるかを求め，その位置の問題として返す
+fn token_diagnostic(
+    input_text: &str,
+    line: usize,
+    token: &str,
+    message: impl Into<String>,
+) -> ParseDiagnostic {
+    let line_text = input_text.lines().nth(line - 1).unwrap_or_default();
+    let offset = (token.as_ptr() as usize).saturating_sub(line_text.as_ptr() as usize);
+    ParseDiagnostic {
+        line,
+        column: line_text
+            .get(..offset)
+            .map_or(0, |text| text.chars().count())
+            + 1,
+        message: message.into(),
+    }
+}
+
+/// 重みなしの`None`どうしも等しいとみなして，辺の重みを比べる
+fn same_weight(a: Option<EdgeWeight>, b: Option<EdgeWeight>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.same_value(b),
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+fn weight_mismatch_message(token: &str, expected: Option<EdgeWeight>) -> String {
+    let expected = expected.map_or_else(|| "none".to_string(), |w| w.to_string());
+    format!("Weight mismatch: {token} differs from the weight {expected} of the same edge")
+}
+
+fn parse_weight(line: usize, token: &str) -> anyhow::Result<EdgeWeight> {
+    EdgeWeight::parse(token).map_err(|err| anyhow::anyhow!("line {line}: {err}"))
+}
+
+/// 1 行目の頂点数 `N` を読み取る
+fn parse_header<'a>(
+    lines: &mut impl Iterator<Item = (usize, Vec<&'a str>)>,
+) -> anyhow::Result<usize> {
+    let (line, tokens) = lines
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Insufficient input"))?;
+    match tokens.as_slice() {
+        [n] => parse_count(line, n),
+        _ => Err(anyhow::anyhow!(
+            "line {line}: expected the number of vertices N"
+        )),
+    }
+}
+
+fn reject_trailing<'a>(
+    mut lines: impl Iterator<Item = (usize, Vec<&'a str>)>,
+) -> anyhow::Result<()> {
+    match lines.next() {
+        Some((line, _)) => Err(anyhow::anyhow!("line {line}: excessive input")),
+        None => Ok(()),
+    }
+}
+
+impl BaseGraph {
+    /// 指定した形式の文字列からグラフの基本構造を生成する．
+    /// エラーには問題のある行番号が含まれる．
+    pub fn parse_as(
+        format: GraphTextFormat,
+        input_text: &str,
+        zero_indexed: bool,
+        weighted: bool,
+        directed: bool,
+    ) -> anyhow::Result<Self> {
+        match format {
+            GraphTextFormat::EdgeList => Self::parse(input_text, zero_indexed, weighted),
+            GraphTextFormat::AdjacencyMatrix => {
+                Self::parse_adjacency_matrix(input_text, weighted, directed)
+            }
+            GraphTextFormat::AdjacencyList => {
+                Self::parse_adjacency_list(input_text, zero_indexed, weighted, directed)
+            }
+            GraphTextFormat::ParentArray => Self::parse_parent_array(input_text, zero_indexed),
+        }
+    }
+
+    /// ```text
+    /// N
+    /// a_11 ... a_1N
+    /// ...
+    /// a_N1 ... a_NN
+    /// ```
+    /// 重みなしの場合は 0 / 1 を，重み付きの場合は 0 を辺なしとして重みを読み取る．
+    /// 無向グラフの場合は対称行列でなければならない．
+    fn parse_adjacency_matrix(
+        input_text: &str,
+        weighted: bool,
+        directed: bool,
+    ) -> anyhow::Result<Self> {
+        let mut lines = token_lines(input_text);
+        let n = parse_header(&mut lines)?;
+
+        let mut matrix = Vec::with_capacity(n);
+        let mut row_lines = Vec::with_capacity(n);
+
+        for i in 0..n {
+            let (line, tokens) = lines
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("Insufficient input: missing row {}", i + 1))?;
+            if tokens.len() != n {
+                return Err(anyhow::anyhow!(
+                    "line {line}: expected {n} entries, found {}",
+                    tokens.len()
+                ));
+            }
+
+            let row = tokens
+                .iter()
+                .map(|&token| -> anyhow::Result<Option<EdgeWeight>> {
+                    if weighted {
+                        let weight = parse_weight(line, token)?;
+                        Ok((weight.as_f64() != 0.0).then_some(weight))
+                    } else {
+                        match token {
+                            "0" => Ok(None),
+                            "1" => Ok(Some(EdgeWeight::Int(1))),
+                            _ => Err(anyhow::anyhow!(
+                                "line {line}: expected 0 or 1, found {token}"
+                            )),
+                        }
+                    }
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            matrix.push(row);
+            row_lines.push(line);
+        }
+
+        reject_trailing(lines)?;
+
+        let mut edges = Vec::new();
+        let mut weights = Vec::new();
+
+        for (i, row) in matrix.iter().enumerate() {
+            for (j, entry) in row.iter().enumerate() {
+                if !directed {
+                    if !same_weight(matrix[j][i], *entry) {
+                        let line = row_lines[i.max(j)];
+                        return Err(anyhow::anyhow!(
+                            "line {line}: the matrix of an undirected graph must be symmetric"
+                        ));
+                    }
+                    if j < i {
+                        continue;
+                    }
+                }
+                if let Some(weight) = entry {
+                    edges.push((i, j));
+                    weights.push(*weight);
+                }
+            }
+        }
+
+        Ok(Self {
+            n,
+            edges,
+            weights: weighted.then_some(weights),
+        })
+    }
+
+    /// ```text
+    /// N
+    /// k_1 v_1 ... v_k
+    /// ...
+    /// k_N v_1 ... v_k
+    /// ```
+    /// 重み付きの場合は `k v_1 w_1 ... v_k w_k` の形式で読み取る．
+    /// 無向グラフの場合，両端の行に現れる辺は 1 本として扱う．
+    fn parse_adjacency_list(
+        input_text: &str,
+        zero_indexed: bool,
+        weighted: bool,
+        directed: bool,
+    ) -> anyhow::Result<Self> {
+        let mut lines = token_lines(input_text);
+        let n = parse_header(&mut lines)?;
+
+        let mut edges = Vec::new();
+        let mut weights = Vec::new();
+        // 無向グラフで，逆向きの出現を待っている辺の重み
+        let mut pending: HashMap<(usize, usize), Vec<Option<EdgeWeight>>> = HashMap::new();
+
+        for from in 0..n {
+            let (line, tokens) = lines.next().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Insufficient input: missing the list of vertex {}",
+                    if zero_indexed { from } else { from + 1 }
+                )
+            })?;
+            let k = parse_count(line, tokens[0])?;
+            let width = if weighted { 2 } else { 1 };
+            if k.checked_mul(width) != Some(tokens.len() - 1) {
+                return Err(anyhow::anyhow!(
+                    "line {line}: expected {k} neighbors, found {} values",
+                    tokens.len() - 1
+                ));
+            }
+
+            for entry in tokens[1..].chunks(width) {
+                let to = parse_vertex(line, entry[0], n, zero_indexed)?;
+                let weight = entry
+                    .get(1)
+                    .map(|token| parse_weight(line, token))
+                    .transpose()?;
+
+                if !directed {
+                    let mirrored = pending
+                        .get_mut(&(to, from))
+                        .filter(|weights| !weights.is_empty());
+                    if let Some(mirrored) = mirrored {
+                        match mirrored.iter().position(|&w| same_weight(w, weight)) {
+                            Some(index) => {
+                                mirrored.swap_remove(index);
+                                continue;
+                            }
+                            None => {
+                                let token = entry[entry.len() - 1];
+                                let diagnostic = token_diagnostic(
+                                    input_text,
+                                    line,
+                                    token,
+                                    weight_mismatch_message(token, mirrored[0]),
+                                );
+                                return Err(ParseError {
+                                    diagnostics: vec![diagnostic],
+                                }
+                                .into());
+                            }
+                        }
+                    }
+                    pending.entry((from, to)).or_default().push(weight);
+                }
+
+                edges.push((from, to));
+                weights.extend(weight);
+            }
+        }
+
+        reject_trailing(lines)?;
+
+        Ok(Self {
+            n,
+            edges,
+            weights: weighted.then_some(weights),
+        })
+    }
+
+    /// ```text
+    /// N
+    /// p_2 ... p_N
+    /// ```
+    /// 最初の頂点を根とし，各頂点とその親を結ぶ辺を親から子の向きで読み取る．
+    /// 親の列は複数行に分かれていてもよい．
+    fn parse_parent_array(input_text: &str, zero_indexed: bool) -> anyhow::Result<Self> {
+        let mut lines = token_lines(input_text);
+        let n = parse_header(&mut lines)?;
+
+        let tokens: Vec<(usize, &str)> = lines
+            .by_ref()
+            .flat_map(|(line, tokens)| tokens.into_iter().map(move |token| (line, token)))
+            .collect();
+        let expected = n.saturating_sub(1);
+        if tokens.len() < expected {
+            return Err(anyhow::anyhow!(
+                "Insufficient input: expected {expected} parents, found {}",
+                tokens.len()
+            ));
+        }
+        if let Some((line, _)) = tokens.get(expected) {
+            return Err(anyhow::anyhow!("line {line}: excessive input"));
+        }
+
+        let mut parent = vec![None; n];
+        for (child, &(line, token)) in (1..n).zip(&tokens) {
+            let p = parse_vertex(line, token, n, zero_indexed)?;
+            if p == child {
+                return Err(anyhow::anyhow!(
+                    "line {line}: vertex {token} cannot be its own parent"
+                ));
+            }
+            parent[child] = Some(p);
+        }
+
+        // すべての頂点から親をたどって根に到達できることを確認する
+        let mut reaches_root = vec![false; n];
+        if n > 0 {
+            reaches_root[0] = true;
+        }
+        for start in 1..n {
+            let mut path = Vec::new();
+            let mut current = start;
+            while !reaches_root[current] {
+                if path.len() > n {
+                    let (line, _) = tokens[start - 1];
+                    return Err(anyhow::anyhow!("line {line}: the parents form a cycle"));
+                }
+                path.push(current);
+                current = parent[current].expect("every non-root vertex has a parent");
+            }
+            for vertex in path {
+                reaches_root[vertex] = true;
+            }
+        }
+
+        let edges = (1..n)
+            .map(|child| {
+                (
+                    parent[child].expect("every non-root vertex has a parent"),
+                    child,
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            n,
+            edges,
+            weights: None,
+        })
+    }
+}
+
+impl Graph {
+    /// 指定した形式でグラフを文字列に変換する．
+    /// 親配列は，最初の頂点を根とする木でない場合にエラーとなる．
+    pub fn encode_as(&self, format: GraphTextFormat, zero_indexed: bool) -> anyhow::Result<String> {
+        match format {
+            GraphTextFormat::EdgeList => Ok(self.encode(zero_indexed)),
+            GraphTextFormat::AdjacencyMatrix => self.encode_adjacency_matrix(zero_indexed),
+            GraphTextFormat::AdjacencyList => Ok(self.encode_adjacency_list(zero_indexed)),
+            GraphTextFormat::ParentArray => self.encode_parent_array(zero_indexed),
+        }
+    }
+
+    /// 隣接行列の成分 0 は辺がないことを表すため，重み 0 の辺や多重辺は書き出せずエラーを返す
+    fn encode_adjacency_matrix(&self, zero_indexed: bool) -> anyhow::Result<String> {
+        let (n, edges) = self.compact_edges();
+        let display = |v: usize| if zero_indexed { v } else { v + 1 };
+        let arrow = if self.is_directed { "->" } else { "-" };
+
+        let mut matrix = vec![vec![None; n]; n];
+        for (from, to, weight) in edges {
+            // 重みが未設定の辺は 1 として出力する
+            let weight = weight.unwrap_or(EdgeWeight::Int(1));
+            if self.is_weighted && weight.as_f64() == 0.0 {
+                return Err(anyhow::anyhow!(
+                    "Edge {} {arrow} {} has weight 0, which an adjacency matrix cannot represent",
+                    display(from),
+                    display(to)
+                ));
+            }
+            if matrix[from][to].is_some() {
+                return Err(anyhow::anyhow!(
+                    "Parallel edges {} {arrow} {} cannot be represented in an adjacency matrix",
+                    display(from),
+                    display(to)
+                ));
+            }
+            matrix[from][to] = Some(weight);
+            if !self.is_directed {
+                matrix[to][from] = Some(weight);
+            }
+        }
+
+        let mut res = n.to_string();
+        for row in matrix {
+            let row: Vec<_> = row
+                .into_iter()
+                .map(|entry| match entry {
+                    Some(weight) if self.is_weighted => weight.to_string(),
+                    Some(_) => "1".to_string(),
+                    None => "0".to_string(),
+                })
+                .collect();
+            res.push('\n');
+            res.push_str(&row.join(" "));
+        }
+
+        Ok(res)
+    }
+
+    fn encode_adjacency_list(&self, zero_indexed: bool) -> String {
+        let (n, edges) = self.compact_edges();
+
+        let mut adjacency = vec![Vec::new(); n];
+        for (from, to, weight) in edges {
+            let weight = weight.unwrap_or(EdgeWeight::Int(1));
+            adjacency[from].push((to, weight));
+            if !self.is_directed && from != to {
+                adjacency[to].push((from, weight));
+            }
+        }
+
+        let mut res = n.to_string();
+        for neighbors in adjacency {
+            let _ = write!(res, "\n{}", neighbors.len());
+            for (to, weight) in neighbors {
+                let _ = write!(res, " {}", if zero_indexed { to } else { to + 1 });
+                if self.is_weighted {
+                    let _ = write!(res, " {weight}");
+                }
+            }
+        }
+
+        res
+    }
+
+    fn encode_parent_array(&self, zero_indexed: bool) -> anyhow::Result<String> {
+        let (n, edges) = self.compact_edges();
+        let root = if zero_indexed { 0 } else { 1 };
+        let not_a_tree = || anyhow::anyhow!("The graph is not a tree rooted at vertex {root}");
+
+        if n == 0 || edges.len() != n - 1 {
+            return Err(not_a_tree());
+        }
+
+        let mut adjacency = vec![Vec::new(); n];
+        for &(from, to, _) in &edges {
+            adjacency[from].push(to);
+            if !self.is_directed {
+                adjacency[to].push(from);
+            }
+        }
+
+        // 辺の数が N-1 なので，根からすべての頂点に到達できれば木である
+        let mut parent = vec![None; n];
+        let mut visited = vec![false; n];
+        let mut queue = VecDeque::from([0]);
+        visited[0] = true;
+        while let Some(u) = queue.pop_front() {
+            for &v in &adjacency[u] {
+                if !visited[v] {
+                    visited[v] = true;
+                    parent[v] = Some(u);
+                    queue.push_back(v);
+                }
+            }
+        }
+        if visited.contains(&false) {
+            return Err(not_a_tree());
+        }
+
+        let parents: Vec<_> = parent[1..]
+            .iter()
+            .map(|p| {
+                let p = p.expect("every visited non-root vertex has a parent");
+                if zero_indexed { p } else { p + 1 }.to_string()
+            })
+            .collect();
+
+        let mut res = n.to_string();
+        if !parents.is_empty() {
+            res.push('\n');
+            res.push_str(&parents.join(" "));
+        }
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GraphTextFormat;
+    use crate::graph::{BaseGraph, Edge, EdgeDirection, EdgeWeight, Graph};
+
+    fn build_graph(base: BaseGraph, is_directed: bool, weighted: bool) -> Graph {
+        let mut graph = Graph::default();
+        graph.clear();
+        graph.is_directed = is_directed;
+        graph.is_weighted = weighted;
+        for _ in 0..base.n {
+            graph.add_vertex(egui::Pos2::ZERO);
+        }
+        for (index, &(from, to)) in base.edges.iter().enumerate() {
+            let weight = base.weights.as_ref().map(|weights| weights[index]);
+            graph.edges.push(Edge::with_weight(from, to, weight));
+        }
+        graph
+    }
+
+    #[test]
+    fn adjacency_matrix_round_trips() {
+        let input = "3\n0 1 1\n1 0 0\n1 0 0";
+        let base =
+            BaseGraph::parse_as(GraphTextFormat::AdjacencyMatrix, input, false, false, false)
+                .unwrap();
+        assert_eq!(base.edges, vec![(0, 1), (0, 2)]);
+
+        let graph = build_graph(base, false, false);
+        let encoded = graph
+            .encode_as(GraphTextFormat::AdjacencyMatrix, false)
+            .unwrap();
+        assert_eq!(encoded, input);
+    }
+
+    #[test]
+    fn adjacency_matrix_rejects_edges_it_cannot_represent() {
+        let base = BaseGraph::parse("3 2\n1 2 0\n2 3 4\n", false, true).unwrap();
+        let graph = build_graph(base, false, true);
+        let err = graph
+            .encode_as(GraphTextFormat::AdjacencyMatrix, false)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Edge 1 - 2 has weight 0, which an adjacency matrix cannot represent"
+        );
+
+        let base = BaseGraph::parse("3 3\n1 2\n2 3\n3 2\n", false, false).unwrap();
+        let mut graph = build_graph(base, false, false);
+        graph.allows_multi_edges = true;
+        let err = graph
+            .encode_as(GraphTextFormat::AdjacencyMatrix, true)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parallel edges 2 - 1 cannot be represented in an adjacency matrix"
+        );
+    }
+
+    #[test]
+    fn mixed_edges_expand_into_arcs_in_directed_graphs() {
+        let base = BaseGraph::parse("3 3\n1 2\n2 3\n3 1\n", false, false).unwrap();
+        let mut graph = build_graph(base, true, false);
+        graph.edges[1].direction = EdgeDirection::Undirected;
+        graph.edges[2].direction = EdgeDirection::Bidirectional;
+        assert_eq!(graph.encode(false), "3 5\n1 2\n2 3\n3 2\n3 1\n1 3");
+        assert_eq!(
+            graph
+                .encode_as(GraphTextFormat::AdjacencyMatrix, false)
+                .unwrap(),
+            "3\n0 1 1\n0 0 1\n1 1 0"
+        );
+
+        // 無向グラフでは向きの指定によらず 1 本の辺として書き出す
+        graph.is_directed = false;
+        graph.edges[0].direction = EdgeDirection::Directed;
+        assert_eq!(graph.encode(false), "3 3\n1 2\n2 3\n3 1");
+    }
+
+    #[test]
+    fn adjacency_matrix_rejects_asymmetric_undirected_input() {
+        let input = "2\n\n0 1\n0 0\n";
+        let err = BaseGraph::parse_as(GraphTextFormat::AdjacencyMatrix, input, false, false, false)
+            .unwrap_err();
+        assert!(err.to_string().starts_with("line 4:"), "{err}");
+    }
+
+    #[test]
+    fn adjacency_list_adds_undirected_edges_once() {
+        let input = "3\n2 2 3 5\n1 1 5\n1 1 7\n";
+        let err = BaseGraph::parse_as(GraphTextFormat::AdjacencyList, input, false, true, false)
+            .unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"), "{err}");
+
+        let input = "3\n2 2 5 3 7\n1 1 5\n1 1 7\n";
+        let base =
+            BaseGraph::parse_as(GraphTextFormat::AdjacencyList, input, false, true, false).unwrap();
+        assert_eq!(base.edges, vec![(0, 1), (0, 2)]);
+        assert_eq!(
+            base.weights,
+            Some(vec![EdgeWeight::Int(5), EdgeWeight::Int(7)])
+        );
+
+        let graph = build_graph(base, false, true);
+        let encoded = graph
+            .encode_as(GraphTextFormat::AdjacencyList, false)
+            .unwrap();
+        assert_eq!(encoded, "3\n2 2 5 3 7\n1 1 5\n1 1 7");
+    }
+
+    #[test]
+    fn adjacency_list_rejects_mismatched_undirected_weights() {
+        let input = "2\n1 2 5\n1 1 7\n";
+        let err = BaseGraph::parse_as(GraphTextFormat::AdjacencyList, input, false, true, false)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 5: Weight mismatch: 7 differs from the weight 5 of the same edge"
+        );
+
+        // 整数と実数でも値が等しければ同じ辺とみなす
+        let input = "2\n1 2 5\n1 1 5.0\n";
+        let base =
+            BaseGraph::parse_as(GraphTextFormat::AdjacencyList, input, false, true, false).unwrap();
+        assert_eq!(base.edges, vec![(0, 1)]);
+        assert_eq!(base.weights, Some(vec![EdgeWeight::Int(5)]));
+
+        let input = "2\n0 2.0\n2 0\n";
+        let base = BaseGraph::parse_as(GraphTextFormat::AdjacencyMatrix, input, false, true, false)
+            .unwrap();
+        assert_eq!(base.edges, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn parent_array_reports_the_offending_line() {
+        let input = "4\n1 1\n5\n";
+        let err = BaseGraph::parse_as(GraphTextFormat::ParentArray, input, false, false, true)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "line 3: vertex 5 is out of range");
+
+        let input = "3\n3\n2\n";
+        let err = BaseGraph::parse_as(GraphTextFormat::ParentArray, input, false, false, true)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "line 2: the parents form a cycle");
+
+        let input = "4\n0 0 1\n";
+        let base =
+            BaseGraph::parse_as(GraphTextFormat::ParentArray, input, true, false, true).unwrap();
+        assert_eq!(base.edges, vec![(0, 1), (0, 2), (1, 3)]);
+
+        let graph = build_graph(base, true, false);
+        assert_eq!(
+            graph.encode_as(GraphTextFormat::ParentArray, true).unwrap(),
+            "4\n0 0 1"
+        );
+        assert!(graph
+            .encode_as(GraphTextFormat::AdjacencyMatrix, true)
+            .is_ok());
+    }
+}
@@ -1,9 +1,12 @@
+pub mod algorithms;
 mod base;
+mod formats;
 mod simulator;
 mod structures;
 mod visualizer;
 
-pub use base::BaseGraph;
+pub use base::{BaseGraph, EdgeListOptions, EdgeWeight};
+pub use formats::GraphTextFormat;
 pub use simulator::{simulation_methods, Simulator};
-pub use structures::{Edge, Graph, Vertex};
+pub use structures::{Edge, EdgeDirection, Graph, Vertex};
 pub use visualizer::{visualize_methods, Visualizer};
@@ -6,16 +6,19 @@ use std::{
 
 use egui::Vec2;
 use num_traits::One;
+use rand::RngCore;
 
 use crate::math::affine::{Affine2D, ApplyAffine};
 
-use super::{visualize_methods, BaseGraph, Visualizer};
+use super::{visualize_methods, BaseGraph, EdgeWeight, Visualizer};
 
 #[derive(Debug, Clone)]
 pub struct Vertex {
     pub id: usize,
     pub position: egui::Pos2,
     pub velocity: egui::Vec2,
+    /// シミュレーションで動かさない（固定する）頂点
+    pub is_pinned: bool,
     pub is_deleted: bool,
     pub affine: Rc<RefCell<Affine2D>>,
 }
@@ -36,10 +39,59 @@ impl Vertex {
     }
 }
 
-#[derive(Debug, Clone)]
+/// 辺ごとの向きの指定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeDirection {
+    /// グラフ全体の設定（有向 / 無向）に従う
+    #[default]
+    Inherit,
+    /// 始点から終点へ向かう辺
+    Directed,
+    /// 向きのない辺
+    Undirected,
+    /// 両方向に向かう辺
+    Bidirectional,
+}
+
+impl EdgeDirection {
+    pub const ALL: [Self; 4] = [
+        Self::Inherit,
+        Self::Directed,
+        Self::Undirected,
+        Self::Bidirectional,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Inherit => "Inherit",
+            Self::Directed => "Directed",
+            Self::Undirected => "Undirected",
+            Self::Bidirectional => "Bidirectional",
+        }
+    }
+
+    pub fn is_inherit(&self) -> bool {
+        *self == Self::Inherit
+    }
+
+    /// グラフ全体の設定を反映した向きを求める．`Inherit`は返さない．
+    pub fn resolve(self, is_directed: bool) -> Self {
+        match self {
+            Self::Inherit if is_directed => Self::Directed,
+            Self::Inherit => Self::Undirected,
+            direction => direction,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Edge {
     pub from: usize,
     pub to: usize,
+    pub weight: Option<EdgeWeight>,
+    /// グラフ全体の設定を上書きする辺の向き
+    pub direction: EdgeDirection,
     pub is_deleted: bool,
 }
 
@@ -48,15 +100,28 @@ impl Edge {
         Self {
             from,
             to,
+            weight: None,
+            direction: EdgeDirection::Inherit,
             is_deleted: false,
         }
     }
+
+    pub fn with_weight(from: usize, to: usize, weight: Option<EdgeWeight>) -> Self {
+        Self {
+            weight,
+            ..Self::new(from, to)
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Graph {
     /// 有向グラフ / 無向グラフ
     pub is_directed: bool,
+    /// 重み付きグラフ / 重みなしグラフ
+    pub is_weighted: bool,
+    /// 同じ頂点対を結ぶ辺（多重辺）を追加できるか
+    pub allows_multi_edges: bool,
     /// 頂点集合に対するアフィン変換
     pub affine: Rc<RefCell<Affine2D>>,
     /// 頂点集合
@@ -126,26 +191,85 @@ impl Graph {
             id: self.vertices.len(),
             position,
             velocity: Vec2::ZERO,
+            is_pinned: false,
             is_deleted: false,
             affine: self.affine.clone(),
         });
     }
 
+    /// 別のグラフの頂点と辺を新しい番号で追加する．
+    /// `other`の頂点位置を画面座標とみなし，`offset`だけずらして配置する．
+    /// 追加した頂点の先頭の番号を返す．
+    pub fn append(&mut self, other: &Graph, offset: Vec2) -> usize {
+        let base = self.vertices.len();
+
+        for vertex in &other.vertices {
+            let mut new_vertex = Vertex {
+                id: base + vertex.id,
+                position: egui::Pos2::ZERO,
+                velocity: Vec2::ZERO,
+                is_pinned: vertex.is_pinned,
+                is_deleted: false,
+                affine: self.affine.clone(),
+            };
+            new_vertex.update_position(vertex.get_position() + offset);
+            self.vertices.push(new_vertex);
+        }
+
+        // 有向・無向の設定が異なるグラフの辺は，元の向きを辺ごとの指定として保つ
+        let keeps_direction = other.is_directed != self.is_directed;
+        self.edges.extend(other.edges.iter().map(|edge| Edge {
+            direction: if keeps_direction {
+                edge.direction.resolve(other.is_directed)
+            } else {
+                edge.direction
+            },
+            ..Edge::with_weight(base + edge.from, base + edge.to, edge.weight)
+        }));
+
+        base
+    }
+
     /// 始点と終点が同じ辺が存在するか
-    fn has_same_edge(is_directed: bool, edges: &[Edge], from: usize, to: usize) -> bool {
+    pub fn has_same_edge(is_directed: bool, edges: &[Edge], from: usize, to: usize) -> bool {
         edges.iter().any(|edge| {
             (edge.from, edge.to) == (from, to) || !is_directed && (edge.from, edge.to) == (to, from)
         })
     }
 
-    /// ユニークな辺を追加する．正常に追加された場合`true`を返す．
-    pub fn add_unique_edge(
+    /// 向きを反映すると一方向になる辺があるか
+    pub fn has_directed_edges(&self) -> bool {
+        self.edges.iter().any(|edge| {
+            !edge.is_deleted && edge.direction.resolve(self.is_directed) == EdgeDirection::Directed
+        })
+    }
+
+    /// 同じ頂点対を結ぶ辺が複数ある（多重辺を含む）か
+    pub fn has_parallel_edges(&self) -> bool {
+        let mut seen = HashSet::new();
+        self.edges
+            .iter()
+            .filter(|edge| !edge.is_deleted)
+            .any(|edge| {
+                let key = if self.is_directed {
+                    (edge.from, edge.to)
+                } else {
+                    (edge.from.min(edge.to), edge.from.max(edge.to))
+                };
+                !seen.insert(key)
+            })
+    }
+
+    /// 辺を追加する．多重辺を許可しない場合は，同じ辺が既にあれば追加しない．
+    /// 正常に追加された場合`true`を返す．
+    pub fn try_add_edge(
         is_directed: bool,
+        allows_multi_edges: bool,
         edges: &mut Vec<Edge>,
         from: usize,
         to: usize,
     ) -> bool {
-        if Self::has_same_edge(is_directed, edges, from, to) {
+        if !allows_multi_edges && Self::has_same_edge(is_directed, edges, from, to) {
             false
         } else {
             edges.push(Edge::new(from, to));
@@ -153,22 +277,6 @@ impl Graph {
         }
     }
 
-    /// 隣接頂点のidを列挙する
-    pub fn neighbor_vertices(&self, id: usize) -> impl Iterator<Item = &Vertex> {
-        self.edges
-            .iter()
-            .filter_map(move |v| {
-                if v.from == id {
-                    Some(v.to)
-                } else if v.to == id {
-                    Some(v.from)
-                } else {
-                    None
-                }
-            })
-            .filter_map(|id| self.vertices.iter().find(|v| v.id == id))
-    }
-
     pub fn approx_diameter_lower_bound(&self) -> usize {
         let n = self.vertices.len();
         if n <= 1 {
@@ -224,7 +332,9 @@ impl Graph {
             .collect()
     }
 
-    pub fn encode(&self, zero_indexed: bool) -> String {
+    /// 削除済みの頂点を詰めた番号で，頂点数と辺（多重辺を許可しない無向グラフでは重複を除く）を求める．
+    /// 有向グラフでは，向きのない辺と両向きの辺を逆向きの 2 本の辺に展開する．
+    pub(super) fn compact_edges(&self) -> (usize, Vec<(usize, usize, Option<EdgeWeight>)>) {
         let active_vertices: Vec<_> = self.vertices.iter().filter(|v| !v.is_deleted).collect();
         let mut id_map = HashMap::new();
 
@@ -243,22 +353,40 @@ impl Graph {
                 continue;
             };
 
-            if !self.is_directed && seen.contains(&(to, from)) {
+            if !self.is_directed && !self.allows_multi_edges && seen.contains(&(to, from)) {
                 continue;
             }
 
             seen.insert((from, to));
-            unique_edges.push((from, to));
+            unique_edges.push((from, to, edge.weight));
+
+            // 有向グラフのテキスト形式では，向きのない辺や両向きの辺を逆向きの 2 本の辺として表す
+            if self.is_directed
+                && from != to
+                && edge.direction.resolve(self.is_directed) != EdgeDirection::Directed
+            {
+                unique_edges.push((to, from, edge.weight));
+            }
         }
 
-        let mut res = format!("{} {}", active_vertices.len(), unique_edges.len());
+        (active_vertices.len(), unique_edges)
+    }
 
-        for (from, to) in unique_edges {
+    pub fn encode(&self, zero_indexed: bool) -> String {
+        let (n, unique_edges) = self.compact_edges();
+
+        let mut res = format!("{} {}", n, unique_edges.len());
+
+        for (from, to, weight) in unique_edges {
             res.push_str(&format!(
                 "\n{} {}",
                 if zero_indexed { from } else { from + 1 },
                 if zero_indexed { to } else { to + 1 }
             ));
+            if self.is_weighted {
+                // 重みが未設定の辺は 1 として出力する
+                res.push_str(&format!(" {}", weight.unwrap_or(EdgeWeight::Int(1))));
+            }
         }
 
         res
@@ -286,16 +414,40 @@ impl Graph {
                 .flat_map(move |i| (i + 1..n).map(move |j| (i, j)))
                 .filter(|&(u, v)| edge_existence[u][v])
                 .collect(),
+            weights: None,
         }
     }
 
-    /// 逆辺を張ったグラフを求める（有向グラフの場合のみ）
-    pub fn calc_reverted(&self) -> BaseGraph {
-        debug_assert!(self.is_directed);
+    /// すべての辺を逆向きにする．辺ごとの向きの指定や重みはそのまま保つ
+    pub fn revert_edges(&mut self) {
+        for edge in &mut self.edges {
+            std::mem::swap(&mut edge.from, &mut edge.to);
+        }
+    }
 
-        BaseGraph {
-            n: self.vertices.len(),
-            edges: self.edges.iter().map(|e| (e.to, e.from)).collect(),
+    /// 頂点と辺を保ったまま，頂点を配置し直す
+    pub fn relayout(
+        &mut self,
+        visualizer: &dyn Visualizer,
+        density_threshold: f32,
+        canvas_rect: egui::Rect,
+        rng: &mut dyn RngCore,
+    ) {
+        self.apply_deletions();
+        *self.affine.borrow_mut() = Affine2D::one();
+
+        let edges: Vec<_> = self.edges.iter().map(|edge| (edge.from, edge.to)).collect();
+        let positions = layout_positions(
+            visualizer,
+            density_threshold,
+            self.vertices.len(),
+            &edges,
+            canvas_rect,
+            rng,
+        );
+        for (vertex, position) in self.vertices.iter_mut().zip(positions) {
+            vertex.position = position;
+            vertex.velocity = Vec2::ZERO;
         }
     }
 
@@ -304,52 +456,79 @@ impl Graph {
         &mut self,
         visualizer: &dyn Visualizer,
         density_threshold: f32,
-        BaseGraph { n, edges }: BaseGraph,
+        BaseGraph { n, edges, weights }: BaseGraph,
         canvas_rect: egui::Rect,
+        rng: &mut dyn RngCore,
     ) -> anyhow::Result<()> {
         // グラフの初期化
         self.clear();
         *self.affine.borrow_mut() = Affine2D::one();
 
-        // 頂点座標を適切な位置に（上下左右 10% の余白をもたせる）
-        let adjust_to_window = |pos: egui::Vec2| -> egui::Pos2 {
-            let size = canvas_rect.size();
-            let margin = size * 0.1;
-            canvas_rect.min + margin + pos * size * 0.8
-        };
-
-        // グラフの構築
-        let density = if n == 0 {
-            0.0
-        } else {
-            edges.len() as f32 / (n as f32 * n as f32)
-        };
-
-        let positions = if density > density_threshold {
-            // 高密度グラフでは最適化を避けてランダム配置にする
-            visualize_methods::Naive.resolve_vertex_position(n, &edges)
-        } else {
-            visualizer.resolve_vertex_position(n, &edges)
-        };
-
-        let new_vertices = positions.into_iter().enumerate().map(|(id, pos)| Vertex {
-            id,
-            position: adjust_to_window(pos),
-            velocity: egui::Vec2::ZERO,
-            is_deleted: false,
-            affine: self.affine.clone(),
-        });
+        let positions =
+            layout_positions(visualizer, density_threshold, n, &edges, canvas_rect, rng);
+
+        let new_vertices = positions
+            .into_iter()
+            .enumerate()
+            .map(|(id, position)| Vertex {
+                id,
+                position,
+                velocity: egui::Vec2::ZERO,
+                is_pinned: false,
+                is_deleted: false,
+                affine: self.affine.clone(),
+            });
 
         self.vertices.extend(new_vertices);
 
-        let new_edges = edges.into_iter().map(|(from, to)| Edge::new(from, to));
+        let new_edges = edges.into_iter().enumerate().map(|(i, (from, to))| {
+            let weight = weights.as_ref().and_then(|weights| weights.get(i).copied());
+            Edge::with_weight(from, to, weight)
+        });
 
         self.edges.extend(new_edges);
 
+        // 入力が多重辺を含む場合は，そのまま多重グラフとして扱う
+        if self.has_parallel_edges() {
+            self.allows_multi_edges = true;
+        }
+
         Ok(())
     }
 }
 
+/// 頂点の初期配置を求める
+fn layout_positions(
+    visualizer: &dyn Visualizer,
+    density_threshold: f32,
+    n: usize,
+    edges: &[(usize, usize)],
+    canvas_rect: egui::Rect,
+    rng: &mut dyn RngCore,
+) -> Vec<egui::Pos2> {
+    // 頂点座標を適切な位置に（上下左右 10% の余白をもたせる）
+    let adjust_to_window = |pos: egui::Vec2| -> egui::Pos2 {
+        let size = canvas_rect.size();
+        let margin = size * 0.1;
+        canvas_rect.min + margin + pos * size * 0.8
+    };
+
+    let density = if n == 0 {
+        0.0
+    } else {
+        edges.len() as f32 / (n as f32 * n as f32)
+    };
+
+    let positions = if density > density_threshold {
+        // 高密度グラフでは最適化を避けてランダム配置にする
+        visualize_methods::Naive.resolve_vertex_position(n, edges, rng)
+    } else {
+        visualizer.resolve_vertex_position(n, edges, rng)
+    };
+
+    positions.into_iter().map(adjust_to_window).collect()
+}
+
 fn bfs_farthest(start: usize, adjacency: &[Vec<usize>]) -> (usize, usize, Vec<usize>) {
     let mut dist = vec![usize::MAX; adjacency.len()];
     let mut queue = VecDeque::new();
@@ -383,11 +562,14 @@ impl Default for Graph {
 
         Self {
             is_directed: false,
+            is_weighted: false,
+            allows_multi_edges: false,
             vertices: vec![
                 Vertex {
                     id: 0,
                     position: egui::pos2(400.0, 400.0),
                     velocity: egui::Vec2::ZERO,
+                    is_pinned: false,
                     is_deleted: false,
                     affine: affine.clone(),
                 },
@@ -395,15 +577,12 @@ impl Default for Graph {
                     id: 1,
                     position: egui::pos2(600.0, 400.0),
                     velocity: egui::Vec2::ZERO,
+                    is_pinned: false,
                     is_deleted: false,
                     affine: affine.clone(),
                 },
             ],
-            edges: vec![Edge {
-                from: 0,
-                to: 1,
-                is_deleted: false,
-            }],
+            edges: vec![Edge::new(0, 1)],
             affine,
         }
     }
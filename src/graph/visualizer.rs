@@ -1,22 +1,34 @@
 #![allow(clippy::needless_range_loop)]
 
+use rand::RngCore;
+
 /// 可視化を行う
 pub trait Visualizer {
     /// グラフ G = (V,E) が与えられたとき，
     /// 頂点から 2 次元平面への写像 f: V → (0,1)^2 を構成する．
-    fn resolve_vertex_position(&self, n: usize, edges: &[(usize, usize)]) -> Vec<egui::Vec2>;
+    /// 乱択を行う場合は `rng` だけを使い，同じ状態の `rng` からは同じ配置を返す．
+    fn resolve_vertex_position(
+        &self,
+        n: usize,
+        edges: &[(usize, usize)],
+        rng: &mut dyn RngCore,
+    ) -> Vec<egui::Vec2>;
 }
 
 pub mod visualize_methods {
     #![allow(dead_code)]
 
+    use rand::{Rng, RngCore};
+
     const JACOBI_EPS: f64 = 1e-10;
+    /// スペクトル配置で固有値分解を行う頂点数の上限．これを超えると幅優先順の円周配置で代用する
+    pub const MAX_SPECTRAL_VERTICES: usize = 100;
     const JACOBI_MAX_ITER_FACTOR: usize = 20;
     const AXIS_EPS: f32 = 1e-6;
 
     /// [0,1]^2 から一様ランダムにサンプリングする
-    fn sample_point() -> egui::Vec2 {
-        egui::vec2(rand::random::<f32>(), rand::random::<f32>())
+    fn sample_point(rng: &mut dyn RngCore) -> egui::Vec2 {
+        egui::vec2(rng.gen::<f32>(), rng.gen::<f32>())
     }
 
     fn circular_layout(n: usize) -> Vec<egui::Vec2> {
@@ -36,10 +48,51 @@ pub mod visualize_methods {
             .collect()
     }
 
+    /// 連結成分ごとに幅優先探索で訪れた順に頂点を円周上に並べる．
+    /// 隣接する頂点どうしが近くに置かれ，O(n + m) で求まる．
+    fn breadth_first_circular_layout(n: usize, edges: &[(usize, usize)]) -> Vec<egui::Vec2> {
+        let mut adj = vec![vec![]; n];
+        for &(u, v) in edges {
+            if u < n && v < n {
+                adj[u].push(v);
+                adj[v].push(u);
+            }
+        }
+
+        let mut order = Vec::with_capacity(n);
+        let mut visited = vec![false; n];
+        for start in 0..n {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let head = order.len();
+            order.push(start);
+            for i in head.. {
+                let Some(&u) = order.get(i) else {
+                    break;
+                };
+                for &v in &adj[u] {
+                    if !visited[v] {
+                        visited[v] = true;
+                        order.push(v);
+                    }
+                }
+            }
+        }
+
+        let mut positions = vec![egui::Vec2::ZERO; n];
+        for (&v, position) in order.iter().zip(circular_layout(n)) {
+            positions[v] = position;
+        }
+        positions
+    }
+
     fn build_laplacian(n: usize, edges: &[(usize, usize)]) -> Vec<Vec<f64>> {
         let mut laplacian = vec![vec![0.0; n]; n];
 
         for &(u, v) in edges {
+            // 自己ループは次数と隣接行列の対角成分に同じだけ寄与し，L = D - A では打ち消し合う
             if u >= n || v >= n || u == v {
                 continue;
             }
@@ -154,6 +207,9 @@ pub mod visualize_methods {
         if n <= 1 {
             return circular_layout(n);
         }
+        if n > MAX_SPECTRAL_VERTICES {
+            return breadth_first_circular_layout(n, edges);
+        }
 
         let laplacian = build_laplacian(n, edges);
         let (eigenvalues, eigenvectors) = jacobi_eigendecomposition(laplacian);
@@ -251,16 +307,27 @@ pub mod visualize_methods {
     pub struct Naive;
 
     impl super::Visualizer for Naive {
-        fn resolve_vertex_position(&self, n: usize, _edges: &[(usize, usize)]) -> Vec<egui::Vec2> {
-            (0..n).map(|_| sample_point()).collect()
+        fn resolve_vertex_position(
+            &self,
+            n: usize,
+            _edges: &[(usize, usize)],
+            rng: &mut dyn RngCore,
+        ) -> Vec<egui::Vec2> {
+            (0..n).map(|_| sample_point(rng)).collect()
         }
     }
 
     /// ラプラシアンの第2・第3固有ベクトルから初期配置を構成する．
+    /// 頂点数が`MAX_SPECTRAL_VERTICES`を超える場合は幅優先順の円周配置にする．
     pub struct Spectral;
 
     impl super::Visualizer for Spectral {
-        fn resolve_vertex_position(&self, n: usize, edges: &[(usize, usize)]) -> Vec<egui::Vec2> {
+        fn resolve_vertex_position(
+            &self,
+            n: usize,
+            edges: &[(usize, usize)],
+            _rng: &mut dyn RngCore,
+        ) -> Vec<egui::Vec2> {
             spectral_layout(n, edges)
         }
     }
@@ -270,12 +337,17 @@ pub mod visualize_methods {
     pub struct HillClimbing(pub usize);
 
     impl super::Visualizer for HillClimbing {
-        fn resolve_vertex_position(&self, n: usize, edges: &[(usize, usize)]) -> Vec<egui::Vec2> {
+        fn resolve_vertex_position(
+            &self,
+            n: usize,
+            edges: &[(usize, usize)],
+            rng: &mut dyn RngCore,
+        ) -> Vec<egui::Vec2> {
             if n == 0 {
                 return vec![];
             }
 
-            let initial_positions = (0..n).map(|_| sample_point()).collect::<Vec<_>>();
+            let initial_positions = (0..n).map(|_| sample_point(rng)).collect::<Vec<_>>();
 
             let mut best_positions = initial_positions.clone();
             let mut best_crossing = count_edge_crossing(&best_positions, edges);
@@ -284,8 +356,8 @@ pub mod visualize_methods {
                 let mut new_positions = best_positions.clone();
 
                 // 1 つの頂点をランダムに選択して座標を変更する
-                let i = rand::random::<usize>() % n;
-                new_positions[i] = sample_point();
+                let i = rng.gen_range(0..n);
+                new_positions[i] = sample_point(rng);
 
                 // 辺の重なりが減るような配置を選択する
                 let new_crossing = count_edge_crossing(&new_positions, edges);
@@ -311,16 +383,21 @@ pub mod visualize_methods {
     }
 
     impl super::Visualizer for SimulatedAnnealing {
-        fn resolve_vertex_position(&self, n: usize, edges: &[(usize, usize)]) -> Vec<egui::Vec2> {
+        fn resolve_vertex_position(
+            &self,
+            n: usize,
+            edges: &[(usize, usize)],
+            rng: &mut dyn RngCore,
+        ) -> Vec<egui::Vec2> {
             if n == 0 {
                 return vec![];
             }
 
             if self.max_iter == 0 {
-                return (0..n).map(|_| sample_point()).collect();
+                return (0..n).map(|_| sample_point(rng)).collect();
             }
 
-            let mut current_positions = (0..n).map(|_| sample_point()).collect::<Vec<_>>();
+            let mut current_positions = (0..n).map(|_| sample_point(rng)).collect::<Vec<_>>();
             let mut current_crossing = count_edge_crossing(&current_positions, edges);
 
             let mut best_positions = current_positions.clone();
@@ -333,8 +410,8 @@ pub mod visualize_methods {
                 let mut new_positions = current_positions.clone();
 
                 // 1 つの頂点をランダムに選択して座標を変更する
-                let i = rand::random::<usize>() % n;
-                new_positions[i] = sample_point();
+                let i = rng.gen_range(0..n);
+                new_positions[i] = sample_point(rng);
 
                 let new_crossing = count_edge_crossing(&new_positions, edges);
                 let delta = new_crossing as isize - current_crossing as isize;
@@ -343,7 +420,7 @@ pub mod visualize_methods {
                     true
                 } else if temperature > 0.0 {
                     let prob = (-(delta as f32) / temperature).exp();
-                    rand::random::<f32>() < prob
+                    rng.gen::<f32>() < prob
                 } else {
                     false
                 };
@@ -365,11 +442,554 @@ pub mod visualize_methods {
         }
     }
 
+    /// 無向グラフとみなしたときに森（閉路を持たない）かどうかを判定する
+    pub fn is_forest(n: usize, edges: &[(usize, usize)]) -> bool {
+        let mut parent = (0..n).collect::<Vec<_>>();
+        fn find(parent: &mut [usize], mut x: usize) -> usize {
+            while parent[x] != x {
+                parent[x] = parent[parent[x]];
+                x = parent[x];
+            }
+            x
+        }
+
+        edges.iter().all(|&(u, v)| {
+            if u >= n || v >= n {
+                return true;
+            }
+            let (ru, rv) = (find(&mut parent, u), find(&mut parent, v));
+            parent[ru] = rv;
+            ru != rv
+        })
+    }
+
+    /// 部分木の輪郭．各深さでの左端・右端を，深い方から順に持つ．
+    /// 実際の座標は `levels` の値に `shift` を足したもの．
+    struct Contour {
+        levels: Vec<(f32, f32)>,
+        shift: f32,
+    }
+
+    impl Contour {
+        fn leaf() -> Self {
+            Self {
+                levels: vec![(0.0, 0.0)],
+                shift: 0.0,
+            }
+        }
+
+        fn height(&self) -> usize {
+            self.levels.len()
+        }
+
+        /// 上から `depth` 番目の左端・右端
+        fn get(&self, depth: usize) -> (f32, f32) {
+            let (left, right) = self.levels[self.levels.len() - 1 - depth];
+            (left + self.shift, right + self.shift)
+        }
+
+        /// `other` を `offset` だけずらして重ねた輪郭
+        fn merge(self, mut other: Self, offset: f32) -> Self {
+            other.shift += offset;
+            let (mut base, top) = if self.height() >= other.height() {
+                (self, other)
+            } else {
+                (other, self)
+            };
+            for depth in 0..top.height() {
+                let (left, right) = base.get(depth);
+                let (top_left, top_right) = top.get(depth);
+                let index = base.levels.len() - 1 - depth;
+                base.levels[index] = (
+                    left.min(top_left) - base.shift,
+                    right.max(top_right) - base.shift,
+                );
+            }
+            base
+        }
+
+        /// `right` の左側に間隔 1 以上あけて並べるときの，`right` のずらし量
+        fn separation(left: &Self, right: &Self) -> f32 {
+            (0..left.height().min(right.height()))
+                .map(|depth| left.get(depth).1 - right.get(depth).0 + 1.0)
+                .fold(f32::NEG_INFINITY, f32::max)
+        }
+    }
+
+    /// 部分木を左から詰めて並べ，各頂点の親からの相対的な x 座標を求める．
+    /// `roots` の木どうしも同様に左から詰めて並べ，`roots` の x 座標を返す．
+    fn pack_forest(roots: &[usize], children: &[Vec<usize>], order: &[usize]) -> Vec<f32> {
+        let n = children.len();
+        let mut offset = vec![0.0; n];
+        let mut contours: Vec<Option<Contour>> = (0..n).map(|_| None).collect();
+
+        // 子から順に処理する
+        for &v in order.iter().rev() {
+            let mut contour: Option<Contour> = None;
+            let mut child_offsets = Vec::with_capacity(children[v].len());
+            for &child in &children[v] {
+                let child_contour = contours[child].take().expect("child is processed first");
+                let x = contour
+                    .as_ref()
+                    .map_or(0.0, |acc| Contour::separation(acc, &child_contour));
+                child_offsets.push(x);
+                contour = Some(match contour {
+                    Some(acc) => acc.merge(child_contour, x),
+                    None => child_contour,
+                });
+            }
+
+            let contour = match contour {
+                Some(mut acc) => {
+                    // 親を最初と最後の子の中央に置く
+                    let mid = (child_offsets[0] + child_offsets[child_offsets.len() - 1]) / 2.0;
+                    for (&child, x) in children[v].iter().zip(child_offsets) {
+                        offset[child] = x - mid;
+                    }
+                    acc.shift -= mid;
+                    acc.levels.push((-acc.shift, -acc.shift));
+                    acc
+                }
+                None => Contour::leaf(),
+            };
+            contours[v] = Some(contour);
+        }
+
+        let mut forest: Option<Contour> = None;
+        for &root in roots {
+            let contour = contours[root].take().expect("root is processed");
+            let x = forest
+                .as_ref()
+                .map_or(0.0, |acc| Contour::separation(acc, &contour));
+            offset[root] = x;
+            forest = Some(match forest {
+                Some(acc) => acc.merge(contour, x),
+                None => contour,
+            });
+        }
+
+        offset
+    }
+
+    /// 根付き木（森）として，深さごとの層に頂点を並べる．
+    /// 各部分木は重ならないように詰めて，親は子の中央に置く．
+    /// 左から詰めた配置と右から詰めた配置を平均し，左右対称な部分木が対称に描かれるようにする．
+    /// 森でない場合は [`Spectral`] と同じ配置にする．
+    /// - `root`: `root` を含む木の根．他の木は番号が最小の頂点を根とする．
+    pub struct Tree {
+        pub root: usize,
+    }
+
+    impl super::Visualizer for Tree {
+        fn resolve_vertex_position(
+            &self,
+            n: usize,
+            edges: &[(usize, usize)],
+            _rng: &mut dyn RngCore,
+        ) -> Vec<egui::Vec2> {
+            if n == 0 || !is_forest(n, edges) {
+                return spectral_layout(n, edges);
+            }
+
+            let mut adjacency = vec![Vec::new(); n];
+            for &(u, v) in edges {
+                if u < n && v < n {
+                    adjacency[u].push(v);
+                    adjacency[v].push(u);
+                }
+            }
+
+            // 根から BFS して，親子関係と深さを求める
+            let mut depth = vec![usize::MAX; n];
+            let mut children = vec![Vec::new(); n];
+            let mut order = Vec::with_capacity(n);
+            let mut roots = Vec::new();
+            let first_root = self.root.min(n - 1);
+            for start in std::iter::once(first_root).chain(0..n) {
+                if depth[start] != usize::MAX {
+                    continue;
+                }
+                roots.push(start);
+                depth[start] = 0;
+                let mut head = order.len();
+                order.push(start);
+                while let Some(&u) = order.get(head) {
+                    head += 1;
+                    let mut next = adjacency[u]
+                        .iter()
+                        .copied()
+                        .filter(|&v| depth[v] == usize::MAX)
+                        .collect::<Vec<_>>();
+                    next.sort_unstable();
+                    for &v in &next {
+                        depth[v] = depth[u] + 1;
+                        order.push(v);
+                    }
+                    children[u] = next;
+                }
+            }
+
+            let from_left = pack_forest(&roots, &children, &order);
+
+            // 子と木の並びを逆にして詰め，左右反転したものを右から詰めた配置とする
+            let reversed_children = children
+                .iter()
+                .map(|c| c.iter().rev().copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let reversed_roots = roots.iter().rev().copied().collect::<Vec<_>>();
+            let from_right = pack_forest(&reversed_roots, &reversed_children, &order);
+
+            // 親からの相対位置を平均してから，根から順に絶対位置へ直す
+            let mut xs = (0..n)
+                .map(|v| (from_left[v] - from_right[v]) / 2.0)
+                .collect::<Vec<_>>();
+            for &v in &order {
+                for &child in &children[v] {
+                    xs[child] += xs[v];
+                }
+            }
+
+            let min_x = xs.iter().copied().fold(f32::INFINITY, f32::min);
+            let max_x = xs.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let max_depth = depth.iter().copied().max().unwrap_or(0);
+
+            (0..n)
+                .map(|v| {
+                    let x = if max_x - min_x < AXIS_EPS {
+                        0.5
+                    } else {
+                        (xs[v] - min_x) / (max_x - min_x)
+                    };
+                    let y = if max_depth == 0 {
+                        0.5
+                    } else {
+                        depth[v] as f32 / max_depth as f32
+                    };
+                    egui::vec2(x, y)
+                })
+                .collect()
+        }
+    }
+
+    /// DFS で後退辺を見つけ，逆向きにして閉路をなくした辺の一覧を返す
+    fn remove_cycles(n: usize, edges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        let mut adjacency = vec![Vec::new(); n];
+        for &(u, v) in edges {
+            if u < n && v < n && u != v {
+                adjacency[u].push(v);
+            }
+        }
+
+        // 0: 未訪問，1: 探索中，2: 探索済み
+        let mut state = vec![0u8; n];
+        let mut back_edges = std::collections::HashSet::new();
+        for start in 0..n {
+            if state[start] != 0 {
+                continue;
+            }
+            state[start] = 1;
+            let mut stack = vec![(start, 0)];
+            while let Some((u, i)) = stack.pop() {
+                if let Some(&v) = adjacency[u].get(i) {
+                    stack.push((u, i + 1));
+                    match state[v] {
+                        0 => {
+                            state[v] = 1;
+                            stack.push((v, 0));
+                        }
+                        1 => {
+                            back_edges.insert((u, v));
+                        }
+                        _ => {}
+                    }
+                } else {
+                    state[u] = 2;
+                }
+            }
+        }
+
+        let mut dag = Vec::new();
+        for (u, neighbors) in adjacency.iter().enumerate() {
+            for &v in neighbors {
+                dag.push(if back_edges.contains(&(u, v)) {
+                    (v, u)
+                } else {
+                    (u, v)
+                });
+            }
+        }
+        dag
+    }
+
+    /// 各頂点の層を，始点からの最長路の長さとして求める
+    fn assign_layers(n: usize, dag: &[(usize, usize)]) -> Vec<usize> {
+        let mut adjacency = vec![Vec::new(); n];
+        let mut indegree = vec![0; n];
+        for &(u, v) in dag {
+            adjacency[u].push(v);
+            indegree[v] += 1;
+        }
+
+        let mut layer = vec![0; n];
+        let mut queue = (0..n)
+            .filter(|&v| indegree[v] == 0)
+            .collect::<std::collections::VecDeque<_>>();
+        while let Some(u) = queue.pop_front() {
+            for &v in &adjacency[u] {
+                layer[v] = layer[v].max(layer[u] + 1);
+                indegree[v] -= 1;
+                if indegree[v] == 0 {
+                    queue.push_back(v);
+                }
+            }
+        }
+        layer
+    }
+
+    /// 隣り合う 2 層の間の辺の交差数を数える
+    fn count_layer_crossing(position: &[usize], edges: &[(usize, usize)]) -> usize {
+        let mut count = 0;
+        for (i, &(a, b)) in edges.iter().enumerate() {
+            for &(c, d) in &edges[i + 1..] {
+                let upper = position[a].cmp(&position[c]);
+                let lower = position[b].cmp(&position[d]);
+                if upper.is_ne() && lower.is_ne() && upper != lower {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// すべての隣り合う層の間の辺の交差数を数える
+    fn count_total_crossing(
+        layers: &[Vec<usize>],
+        down: &[Vec<usize>],
+        position: &[usize],
+    ) -> usize {
+        layers
+            .iter()
+            .map(|vertices| {
+                let between = vertices
+                    .iter()
+                    .flat_map(|&u| down[u].iter().map(move |&v| (u, v)))
+                    .collect::<Vec<_>>();
+                count_layer_crossing(position, &between)
+            })
+            .sum()
+    }
+
+    /// 順序を保ち間隔を 1 以上あけつつ，各頂点をなるべく `desired` の位置に置く
+    fn place_in_order(desired: &[f32]) -> Vec<f32> {
+        let k = desired.len();
+        let mut from_left = desired.to_vec();
+        for i in 1..k {
+            from_left[i] = from_left[i].max(from_left[i - 1] + 1.0);
+        }
+        let mut from_right = desired.to_vec();
+        for i in (0..k.saturating_sub(1)).rev() {
+            from_right[i] = from_right[i].min(from_right[i + 1] - 1.0);
+        }
+        from_left
+            .into_iter()
+            .zip(from_right)
+            .map(|(l, r)| (l + r) / 2.0)
+            .collect()
+    }
+
+    /// Sugiyama 法で，辺がなるべく上から下を向くように層状に配置する．
+    /// 1. 閉路を作る辺を逆向きにする
+    /// 2. 始点からの最長路の長さで層を決め，複数の層をまたぐ辺にはダミー頂点を置く
+    /// 3. 重心法で上下に走査して，辺の交差が少ない順序を求める
+    /// 4. 隣接頂点の平均位置に近づけながら，重ならないように x 座標を決める
+    /// - `sweeps`: 交差を減らす走査の回数
+    pub struct Sugiyama {
+        pub sweeps: usize,
+    }
+
+    impl super::Visualizer for Sugiyama {
+        fn resolve_vertex_position(
+            &self,
+            n: usize,
+            edges: &[(usize, usize)],
+            _rng: &mut dyn RngCore,
+        ) -> Vec<egui::Vec2> {
+            if n == 0 {
+                return vec![];
+            }
+
+            let dag = remove_cycles(n, edges);
+            let mut layer = assign_layers(n, &dag);
+
+            // 長い辺をダミー頂点で分割し，隣り合う層の間の辺だけにする
+            let mut segments = Vec::new();
+            for &(u, v) in &dag {
+                let mut prev = u;
+                for l in layer[u] + 1..layer[v] {
+                    let dummy = layer.len();
+                    layer.push(l);
+                    segments.push((prev, dummy));
+                    prev = dummy;
+                }
+                segments.push((prev, v));
+            }
+            segments.sort_unstable();
+            segments.dedup();
+
+            let total = layer.len();
+            let layer_count = layer.iter().max().map_or(0, |&l| l + 1);
+            let mut up = vec![Vec::new(); total];
+            let mut down = vec![Vec::new(); total];
+            for &(u, v) in &segments {
+                down[u].push(v);
+                up[v].push(u);
+            }
+
+            let mut layers = vec![Vec::new(); layer_count];
+            for v in 0..total {
+                layers[layer[v]].push(v);
+            }
+
+            let mut position = vec![0; total];
+            for vertices in &layers {
+                for (i, &v) in vertices.iter().enumerate() {
+                    position[v] = i;
+                }
+            }
+
+            let mut best_layers = layers.clone();
+            let mut best_crossing = count_total_crossing(&layers, &down, &position);
+
+            for sweep in 0..self.sweeps {
+                if best_crossing == 0 {
+                    break;
+                }
+                let is_downward = sweep % 2 == 0;
+                let order: Vec<usize> = if is_downward {
+                    (1..layer_count).collect()
+                } else {
+                    (0..layer_count.saturating_sub(1)).rev().collect()
+                };
+                for l in order {
+                    let barycenter = |v: usize| {
+                        let neighbors = if is_downward { &up[v] } else { &down[v] };
+                        if neighbors.is_empty() {
+                            position[v] as f32
+                        } else {
+                            neighbors.iter().map(|&u| position[u] as f32).sum::<f32>()
+                                / neighbors.len() as f32
+                        }
+                    };
+                    let mut keyed = layers[l]
+                        .iter()
+                        .map(|&v| (barycenter(v), v))
+                        .collect::<Vec<_>>();
+                    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+                    layers[l] = keyed.into_iter().map(|(_, v)| v).collect();
+                    for (i, &v) in layers[l].iter().enumerate() {
+                        position[v] = i;
+                    }
+                }
+
+                let crossing = count_total_crossing(&layers, &down, &position);
+                if crossing < best_crossing {
+                    best_crossing = crossing;
+                    best_layers = layers.clone();
+                }
+            }
+            let layers = best_layers;
+
+            // 各層を中央揃えで並べてから，隣接頂点の平均位置に近づける
+            let mut xs = vec![0.0; total];
+            for vertices in &layers {
+                let center = (vertices.len() as f32 - 1.0) / 2.0;
+                for (i, &v) in vertices.iter().enumerate() {
+                    xs[v] = i as f32 - center;
+                }
+            }
+            for round in 0..4 {
+                let is_downward = round % 2 == 0;
+                let order: Vec<usize> = if is_downward {
+                    (1..layer_count).collect()
+                } else {
+                    (0..layer_count.saturating_sub(1)).rev().collect()
+                };
+                for l in order {
+                    let desired = layers[l]
+                        .iter()
+                        .map(|&v| {
+                            let neighbors = if is_downward { &up[v] } else { &down[v] };
+                            if neighbors.is_empty() {
+                                xs[v]
+                            } else {
+                                neighbors.iter().map(|&u| xs[u]).sum::<f32>()
+                                    / neighbors.len() as f32
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    for (&v, x) in layers[l].iter().zip(place_in_order(&desired)) {
+                        xs[v] = x;
+                    }
+                }
+            }
+
+            let min_x = xs[..n].iter().copied().fold(f32::INFINITY, f32::min);
+            let max_x = xs[..n].iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let max_layer = layer[..n].iter().copied().max().unwrap_or(0);
+
+            (0..n)
+                .map(|v| {
+                    let x = if max_x - min_x < AXIS_EPS {
+                        0.5
+                    } else {
+                        (xs[v] - min_x) / (max_x - min_x)
+                    };
+                    let y = if max_layer == 0 {
+                        0.5
+                    } else {
+                        layer[v] as f32 / max_layer as f32
+                    };
+                    egui::vec2(x, y)
+                })
+                .collect()
+        }
+    }
+
     #[cfg(test)]
     mod tests {
-        use super::{spectral_layout, Spectral};
+        use rand::{rngs::StdRng, SeedableRng};
+
+        use super::{
+            is_forest, spectral_layout, HillClimbing, Naive, SimulatedAnnealing, Spectral,
+            Sugiyama, Tree, MAX_SPECTRAL_VERTICES,
+        };
         use crate::graph::Visualizer;
 
+        fn rng() -> StdRng {
+            StdRng::seed_from_u64(0)
+        }
+
+        #[test]
+        fn random_layouts_are_reproducible_from_the_seed() {
+            let edges = [(0, 1), (1, 2), (2, 3), (3, 0), (0, 2), (4, 5)];
+            let visualizers: [Box<dyn Visualizer>; 3] = [
+                Box::new(Naive),
+                Box::new(HillClimbing(200)),
+                Box::new(SimulatedAnnealing {
+                    max_iter: 200,
+                    initial_temp: 10.0,
+                    cooling_rate: 0.99,
+                }),
+            ];
+            for visualizer in visualizers {
+                let layout = |seed: u64| {
+                    visualizer.resolve_vertex_position(6, &edges, &mut StdRng::seed_from_u64(seed))
+                };
+                assert_eq!(layout(7), layout(7));
+                assert_ne!(layout(7), layout(8));
+            }
+        }
+
         #[test]
         fn spectral_layout_stays_inside_unit_square() {
             let positions = spectral_layout(4, &[(0, 1), (1, 2), (2, 3)]);
@@ -379,13 +999,92 @@ pub mod visualize_methods {
             assert!(positions.iter().all(|p| (0.0..=1.0).contains(&p.y)));
         }
 
+        #[test]
+        fn spectral_layout_places_large_graphs_along_a_circle_in_bfs_order() {
+            // 番号を並べ替えたパス．幅優先順なら辺の両端は円周上で隣り合う
+            let n = MAX_SPECTRAL_VERTICES + 1;
+            let path = (0..n).map(|i| i * 37 % n).collect::<Vec<_>>();
+            let edges = path.windows(2).map(|w| (w[0], w[1])).collect::<Vec<_>>();
+            let positions = spectral_layout(n, &edges);
+
+            let chord = 0.8 * (std::f32::consts::PI / n as f32).sin();
+            assert_eq!(positions.len(), n);
+            assert!(edges
+                .iter()
+                .all(|&(u, v)| (positions[u] - positions[v]).length() < chord + 1e-4));
+        }
+
         #[test]
         fn spectral_visualizer_handles_disconnected_graph() {
             let visualizer = Spectral;
-            let positions = visualizer.resolve_vertex_position(4, &[(0, 1), (2, 3)]);
+            let positions = visualizer.resolve_vertex_position(4, &[(0, 1), (2, 3)], &mut rng());
 
             assert_eq!(positions.len(), 4);
             assert!(positions.windows(2).any(|w| w[0] != w[1]));
         }
+
+        #[test]
+        fn tree_layout_is_layered_symmetric_and_non_overlapping() {
+            // 根 0 の子 1, 2, 3, 4 のうち，1 と 4 は幅の広い部分木を持つ
+            let mut edges = vec![(0, 1), (0, 2), (0, 3), (0, 4)];
+            edges.extend((5..10).map(|v| (1, v)));
+            edges.extend((10..15).map(|v| (4, v)));
+            let positions = Tree { root: 0 }.resolve_vertex_position(15, &edges, &mut rng());
+
+            let depth_y = [positions[0].y, positions[1].y, positions[5].y];
+            assert!(depth_y[0] < depth_y[1] && depth_y[1] < depth_y[2]);
+            assert!((2..5).all(|v| positions[v].y == depth_y[1]));
+
+            let center = positions[0].x;
+            let mirrored = |a: usize, b: usize| {
+                ((positions[a].x - center) + (positions[b].x - center)).abs() < 1e-5
+            };
+            assert!((center - 0.5).abs() < 1e-5);
+            assert!(mirrored(1, 4) && mirrored(2, 3) && mirrored(5, 14));
+
+            let mut depth_two = (5..15).map(|v| positions[v].x).collect::<Vec<_>>();
+            depth_two.sort_by(f32::total_cmp);
+            assert!(depth_two.windows(2).all(|w| w[1] - w[0] > 1e-3));
+        }
+
+        #[test]
+        fn tree_layout_uses_the_selected_root() {
+            let edges = [(0, 1), (1, 2), (3, 4)];
+            assert!(is_forest(5, &edges));
+            assert!(!is_forest(3, &[(0, 1), (1, 2), (2, 0)]));
+
+            let positions = Tree { root: 2 }.resolve_vertex_position(5, &edges, &mut rng());
+            assert_eq!(positions[2].y, 0.0);
+            assert_eq!(positions[0].y, 1.0);
+            assert_eq!(positions[3].y, 0.0);
+            assert!(positions[2].x < positions[3].x);
+        }
+
+        #[test]
+        fn sugiyama_layout_points_edges_downward() {
+            let edges = [(0, 1), (0, 2), (1, 3), (2, 3), (0, 3), (3, 4)];
+            let positions = Sugiyama { sweeps: 8 }.resolve_vertex_position(5, &edges, &mut rng());
+            assert!(edges.iter().all(|&(u, v)| positions[u].y < positions[v].y));
+
+            // 閉路は 1 本の辺を逆向きにして層に分ける
+            let positions = Sugiyama { sweeps: 8 }.resolve_vertex_position(
+                3,
+                &[(0, 1), (1, 2), (2, 0)],
+                &mut rng(),
+            );
+            assert!(positions[0].y < positions[1].y && positions[1].y < positions[2].y);
+        }
+
+        #[test]
+        fn sugiyama_layout_removes_avoidable_crossings() {
+            let edges = [(0, 3), (1, 2), (0, 4), (1, 5)];
+            let positions = Sugiyama { sweeps: 8 }.resolve_vertex_position(6, &edges, &mut rng());
+            let x = |v: usize| positions[v].x;
+            assert_eq!(x(0) < x(1), x(3) < x(2));
+            assert_eq!(x(0) < x(1), x(4) < x(5));
+            let mut bottom = [x(2), x(3), x(4), x(5)];
+            bottom.sort_by(f32::total_cmp);
+            assert!(bottom.windows(2).all(|w| w[1] - w[0] > 1e-3));
+        }
     }
 }
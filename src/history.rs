@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+
+use crate::{
+    graph::{Edge, Graph, Vertex},
+    state::EditTarget,
+    view_state::GraphViewState,
+};
+
+/// 履歴の 1 エントリ（グラフとビュー状態のスナップショット）
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub is_directed: bool,
+    pub is_weighted: bool,
+    pub allows_multi_edges: bool,
+    pub vertices: Vec<Vertex>,
+    pub edges: Vec<Edge>,
+    pub view: GraphViewState,
+}
+
+impl HistoryEntry {
+    /// 現在のグラフとビュー状態を記録する
+    pub fn capture(graph: &Graph, view: &GraphViewState) -> Self {
+        Self {
+            is_directed: graph.is_directed,
+            is_weighted: graph.is_weighted,
+            allows_multi_edges: graph.allows_multi_edges,
+            vertices: graph.vertices.clone(),
+            edges: graph.edges.clone(),
+            view: view.clone(),
+        }
+    }
+
+    /// 記録した状態をグラフとビュー状態に書き戻す
+    fn restore(self, graph: &mut Graph, view: &mut GraphViewState) {
+        graph.is_directed = self.is_directed;
+        graph.is_weighted = self.is_weighted;
+        graph.allows_multi_edges = self.allows_multi_edges;
+        graph.vertices = self.vertices;
+        graph.edges = self.edges;
+
+        // 視点（アフィン変換）は履歴の対象外とし，現在のものを共有させる
+        for vertex in &mut graph.vertices {
+            vertex.affine = graph.affine.clone();
+            vertex.velocity = egui::Vec2::ZERO;
+        }
+
+        // アルゴリズムの結果は編集前後で意味が変わるため復元しない
+        *view = self.view;
+        view.clear_highlight();
+        for vertex in &mut view.vertices {
+            vertex.is_pressed = false;
+        }
+        for edge in &mut view.edges {
+            edge.is_pressed = false;
+        }
+    }
+}
+
+/// 連続した変更を 1 つの履歴にまとめるためのキー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoalesceKey {
+    /// エディタでの頂点・辺の編集
+    Entity(EditTarget),
+    /// 選択範囲のスタイル変更
+    SelectionStyle,
+}
+
+/// 編集履歴（Undo / Redo）
+#[derive(Debug)]
+pub struct EditHistory {
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    limit: usize,
+    /// 直前の記録をまとめる対象（エディタでの連続編集など）
+    coalesce_key: Option<CoalesceKey>,
+}
+
+impl EditHistory {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            limit,
+            coalesce_key: None,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// 変更を加える直前の状態を記録する
+    pub fn record(&mut self, graph: &Graph, view: &GraphViewState) {
+        self.push(HistoryEntry::capture(graph, view));
+    }
+
+    /// 変更前の状態を記録する．
+    /// 直前の記録と同じ対象への連続した変更は 1 つにまとめる．
+    pub fn record_coalesced(&mut self, key: CoalesceKey, entry: HistoryEntry) {
+        if self.coalesce_key == Some(key) {
+            return;
+        }
+        self.push(entry);
+        self.coalesce_key = Some(key);
+    }
+
+    /// 連続編集のまとまりを区切る
+    pub fn break_coalescing(&mut self) {
+        self.coalesce_key = None;
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        self.coalesce_key = None;
+        self.redo_stack.clear();
+
+        if self.limit == 0 {
+            return;
+        }
+        while self.undo_stack.len() >= self.limit {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(entry);
+    }
+
+    /// 1 つ前の状態に戻す．戻せた場合`true`を返す．
+    pub fn undo(&mut self, graph: &mut Graph, view: &mut GraphViewState) -> bool {
+        let Some(entry) = self.undo_stack.pop_back() else {
+            return false;
+        };
+        self.coalesce_key = None;
+        self.redo_stack.push(HistoryEntry::capture(graph, view));
+        entry.restore(graph, view);
+        true
+    }
+
+    /// 取り消した変更をやり直す．やり直せた場合`true`を返す．
+    pub fn redo(&mut self, graph: &mut Graph, view: &mut GraphViewState) -> bool {
+        let Some(entry) = self.redo_stack.pop() else {
+            return false;
+        };
+        self.coalesce_key = None;
+        self.undo_stack
+            .push_back(HistoryEntry::capture(graph, view));
+        entry.restore(graph, view);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoalesceKey, EditHistory, HistoryEntry};
+    use crate::{components::Colors, graph::Graph, state::EditTarget, view_state::GraphViewState};
+
+    #[test]
+    fn undo_and_redo_restore_vertex_deletion_with_reindexing() {
+        let mut graph = Graph::default();
+        let mut view = GraphViewState::new_for_graph(&graph);
+        let mut history = EditHistory::new(10);
+
+        history.record(&graph, &view);
+        graph.vertices[0].is_deleted = true;
+        view.apply_deletions(&graph);
+        graph.apply_deletions();
+        assert_eq!(graph.vertices.len(), 1);
+        assert!(graph.edges.is_empty());
+
+        assert!(history.undo(&mut graph, &mut view));
+        assert_eq!(graph.vertices.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(view.vertices.len(), 2);
+
+        assert!(history.redo(&mut graph, &mut view));
+        assert_eq!(graph.vertices.len(), 1);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn history_is_bounded_by_limit() {
+        let graph = Graph::default();
+        let view = GraphViewState::new_for_graph(&graph);
+        let mut history = EditHistory::new(3);
+
+        for _ in 0..5 {
+            history.record(&graph, &view);
+        }
+
+        assert_eq!(history.undo_stack.len(), 3);
+    }
+
+    #[test]
+    fn coalesced_records_for_same_target_are_merged() {
+        let mut graph = Graph::default();
+        let mut view = GraphViewState::new_for_graph(&graph);
+        let mut history = EditHistory::new(10);
+
+        for color in [Colors::Red, Colors::Blue] {
+            history.record_coalesced(
+                CoalesceKey::Entity(EditTarget::Vertex(0)),
+                HistoryEntry::capture(&graph, &view),
+            );
+            view.vertices[0].color = color;
+        }
+
+        assert!(history.undo(&mut graph, &mut view));
+        assert_eq!(view.vertices[0].color, Colors::Default);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn different_coalesce_keys_are_recorded_separately() {
+        let graph = Graph::default();
+        let view = GraphViewState::new_for_graph(&graph);
+        let mut history = EditHistory::new(10);
+
+        for key in [
+            CoalesceKey::Entity(EditTarget::Vertex(0)),
+            CoalesceKey::SelectionStyle,
+            CoalesceKey::SelectionStyle,
+        ] {
+            history.record_coalesced(key, HistoryEntry::capture(&graph, &view));
+        }
+
+        assert_eq!(history.undo_stack.len(), 2);
+    }
+
+    #[test]
+    fn new_record_clears_redo_stack() {
+        let mut graph = Graph::default();
+        let mut view = GraphViewState::new_for_graph(&graph);
+        let mut history = EditHistory::new(10);
+
+        history.record(&graph, &view);
+        graph.is_directed = true;
+        history.undo(&mut graph, &mut view);
+        assert!(history.can_redo());
+
+        history.record(&graph, &view);
+        assert!(!history.can_redo());
+    }
+}
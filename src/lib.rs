@@ -1,10 +1,13 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+mod analysis;
 mod app;
+pub mod cli;
 mod components;
 mod config;
 mod export;
 mod graph;
+mod history;
 mod math;
 mod mode;
 mod project_io;
@@ -15,7 +15,7 @@ const AFFINE2D_ZERO: [[f32; 3]; 3] = [[0.0; 3]; 3];
 const AFFINE2D_ONE: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
 
 /// アフィン変換
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Affine2D(pub [[f32; 3]; 3]);
 
 impl Affine2D {
@@ -1,5 +1,14 @@
+use std::collections::HashMap;
+
 use super::newton::newton_method;
 
+/// 自己ループの始点・終点を置く角度（ループの向きから測る）
+const SELF_LOOP_END_ANGLE: f32 = 0.45;
+/// 自己ループの制御点を置く角度（ループの向きから測る）
+const SELF_LOOP_CONTROL_ANGLE: f32 = 0.7;
+/// 同じ頂点の自己ループを重ねるとき，1 つ外側のループを大きくする割合
+const SELF_LOOP_NEST_RATIO: f32 = 0.6;
+
 pub fn calc_bezier_control_point(
     start: egui::Pos2,
     end: egui::Pos2,
@@ -12,6 +21,33 @@ pub fn calc_bezier_control_point(
     mid + dir * if is_clockwise { distance } else { -distance }
 }
 
+/// 辺の中点（曲線の場合はベジェ曲線上の中点）を求める
+pub fn edge_midpoint(
+    start: egui::Pos2,
+    end: egui::Pos2,
+    is_curved: bool,
+    distance: f32,
+) -> egui::Pos2 {
+    if is_curved {
+        let control = calc_bezier_control_point(start, end, distance, false);
+        bezier_curve(start, control, end, 0.5)
+    } else {
+        start + (end - start) / 2.0
+    }
+}
+
+/// 辺の中点から，曲線が膨らむ側へ`offset`だけずらした位置を求める
+pub fn edge_side_point(
+    start: egui::Pos2,
+    end: egui::Pos2,
+    is_curved: bool,
+    distance: f32,
+    offset: f32,
+) -> egui::Pos2 {
+    let normal = -(end - start).normalized().rot90() * if distance < 0.0 { -1.0 } else { 1.0 };
+    edge_midpoint(start, end, is_curved, distance) + normal * offset
+}
+
 /// ベジェ曲線
 pub fn bezier_curve(start: egui::Pos2, control: egui::Pos2, end: egui::Pos2, t: f32) -> egui::Pos2 {
     let x = (1.0 - t).powf(2.0) * start.x + 2.0 * t * (1.0 - t) * control.x + t.powf(2.0) * end.x;
@@ -84,3 +120,288 @@ pub fn calc_intersection_of_bezier_and_circle(
         )
     })
 }
+
+/// 同じ頂点対を結ぶ辺（多重辺）が重ならないよう，辺ごとのずらし量を求める
+///
+/// 自己ループ以外の辺には，`calc_bezier_control_point`に渡す距離の倍率を返す．0 の辺は直線で描く．
+/// 向きが逆の辺も同じ頂点対の辺として扱い，奇数本なら 1 本を直線にして，
+/// 残りを 1, -1, 2, -2, ... 倍の距離で交互に反対側へ膨らませる．
+///
+/// 自己ループには，同じ頂点の何番目のループか（0, 1, 2, ...）を返す．
+pub fn parallel_edge_offsets(edges: impl IntoIterator<Item = (usize, usize)>) -> Vec<f32> {
+    let edges = edges.into_iter().collect::<Vec<_>>();
+    let key = |(from, to): (usize, usize)| (from.min(to), from.max(to));
+
+    let mut group_sizes: HashMap<(usize, usize), usize> = HashMap::new();
+    for &edge in &edges {
+        *group_sizes.entry(key(edge)).or_insert(0) += 1;
+    }
+
+    let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+    edges
+        .iter()
+        .map(|&(from, to)| {
+            let count = counts.entry(key((from, to))).or_insert(0);
+            let index = *count;
+            *count += 1;
+            if from == to {
+                return index as f32;
+            }
+
+            // 偶数本の場合は直線の辺を作らない
+            let lane = if group_sizes[&key((from, to))] % 2 == 1 {
+                index
+            } else {
+                index + 1
+            };
+            let magnitude = lane.div_ceil(2) as f32;
+            let offset = if lane % 2 == 1 { magnitude } else { -magnitude };
+
+            // 頂点対の番号が小さい側から見た向きにそろえる
+            if from < to {
+                offset
+            } else {
+                -offset
+            }
+        })
+        .collect()
+}
+
+/// 3次ベジェ曲線
+pub fn cubic_bezier_curve(points: [egui::Pos2; 4], t: f32) -> egui::Pos2 {
+    let mt = 1.0 - t;
+    let [p0, p1, p2, p3] = points.map(|p| p.to_vec2());
+    (p0 * mt.powi(3) + p1 * 3.0 * mt.powi(2) * t + p2 * 3.0 * mt * t.powi(2) + p3 * t.powi(3))
+        .to_pos2()
+}
+
+/// 3次ベジェ曲線のパラメータ`t`における微分
+pub fn d_cubic_bezier_dt(points: [egui::Pos2; 4], t: f32) -> egui::Vec2 {
+    let mt = 1.0 - t;
+    let [p0, p1, p2, p3] = points;
+    (p1 - p0) * 3.0 * mt.powi(2) + (p2 - p1) * 6.0 * mt * t + (p3 - p2) * 3.0 * t.powi(2)
+}
+
+/// 3次ベジェ曲線のうち，パラメータ`0..=t`の部分を同じ形の3次ベジェ曲線として求める
+pub fn cubic_bezier_prefix(points: [egui::Pos2; 4], t: f32) -> [egui::Pos2; 4] {
+    let [p0, p1, p2, p3] = points;
+    let p01 = p0.lerp(p1, t);
+    let p12 = p1.lerp(p2, t);
+    let p23 = p2.lerp(p3, t);
+    let p012 = p01.lerp(p12, t);
+    let p123 = p12.lerp(p23, t);
+    [p0, p01, p012, p012.lerp(p123, t)]
+}
+
+/// 3次ベジェ曲線の終点から`length`だけ手前で曲線を切る．矢印のヘッドと線が重ならないようにするために使う
+pub fn trim_cubic_bezier_end(points: [egui::Pos2; 4], length: f32) -> [egui::Pos2; 4] {
+    let end = points[3];
+    // 自己ループは始点と終点が近いので，両端の距離ではなく曲線の中央からの距離で判定する
+    if cubic_bezier_curve(points, 0.5).distance(end) <= length {
+        return [points[0]; 4];
+    }
+
+    // 後半では終点からの距離が t について単調に減少するとみなして二分法で求める
+    let (mut low, mut high) = (0.5_f32, 1.0_f32);
+    for _ in 0..24 {
+        let mid = (low + high) / 2.0;
+        if cubic_bezier_curve(points, mid).distance(end) > length {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    cubic_bezier_prefix(points, low)
+}
+
+/// 自己ループを置く向きを求める．隣接する頂点の方向のうち，最も広く空いている角度の中央を選ぶ
+pub fn self_loop_direction(
+    center: egui::Pos2,
+    neighbors: impl IntoIterator<Item = egui::Pos2>,
+) -> egui::Vec2 {
+    let mut angles = neighbors
+        .into_iter()
+        .map(|neighbor| neighbor - center)
+        .filter(|dir| dir.length_sq() > f32::EPSILON)
+        .map(|dir| dir.angle())
+        .collect::<Vec<_>>();
+    let Some(&last) = angles.iter().max_by(|a, b| a.total_cmp(b)) else {
+        // 隣接する頂点がなければ上向きに置く
+        return -egui::Vec2::Y;
+    };
+    angles.sort_by(f32::total_cmp);
+
+    // 最後の方向から一周して最初の方向に戻る隙間も候補に含める
+    let mut widest = (angles[0] + std::f32::consts::TAU - last, last);
+    for pair in angles.windows(2) {
+        let gap = pair[1] - pair[0];
+        if gap > widest.0 {
+            widest = (gap, pair[0]);
+        }
+    }
+    egui::Vec2::angled(widest.1 + widest.0 / 2.0)
+}
+
+/// 自己ループの頂点から最も離れた点から，さらに外側へ`offset`だけずらした位置を求める
+pub fn self_loop_side_point(
+    center: egui::Pos2,
+    points: [egui::Pos2; 4],
+    offset: f32,
+) -> egui::Pos2 {
+    let apex = cubic_bezier_curve(points, 0.5);
+    apex + (apex - center).normalized() * offset
+}
+
+/// 自己ループを持つ頂点ごとに，ループを置く向きを求める
+pub fn self_loop_directions(
+    positions: &HashMap<usize, egui::Pos2>,
+    edges: impl IntoIterator<Item = (usize, usize)>,
+) -> HashMap<usize, egui::Vec2> {
+    let mut neighbors: HashMap<usize, Vec<egui::Pos2>> = HashMap::new();
+    let mut has_loop = Vec::new();
+    for (from, to) in edges {
+        if from == to {
+            has_loop.push(from);
+            continue;
+        }
+        let (Some(&from_pos), Some(&to_pos)) = (positions.get(&from), positions.get(&to)) else {
+            continue;
+        };
+        neighbors.entry(from).or_default().push(to_pos);
+        neighbors.entry(to).or_default().push(from_pos);
+    }
+
+    has_loop
+        .into_iter()
+        .filter_map(|id| {
+            let center = *positions.get(&id)?;
+            let around = neighbors.get(&id).into_iter().flatten().copied();
+            Some((id, self_loop_direction(center, around)))
+        })
+        .collect()
+}
+
+/// 同じ頂点の`index`番目の自己ループの大きさを求める．後のループほど外側に大きく描く
+pub fn nested_self_loop_size(size: f32, index: f32) -> f32 {
+    size * (1.0 + SELF_LOOP_NEST_RATIO * index)
+}
+
+/// 自己ループを3次ベジェ曲線の制御点として求める
+///
+/// ### Parameters
+/// - `center`: 頂点の中心
+/// - `radius`: 頂点の半径
+/// - `direction`: ループを置く向き
+/// - `size`: ループが頂点の円から膨らむ大きさ
+///
+/// ### Returns
+/// 始点と終点が頂点の円周上にある3次ベジェ曲線の制御点
+pub fn self_loop_points(
+    center: egui::Pos2,
+    radius: f32,
+    direction: egui::Vec2,
+    size: f32,
+) -> [egui::Pos2; 4] {
+    let dir = direction.normalized();
+    let rotated = |angle: f32| {
+        let (sin, cos) = angle.sin_cos();
+        egui::vec2(dir.x * cos - dir.y * sin, dir.x * sin + dir.y * cos)
+    };
+
+    // t = 0.5 の点が中心から radius + size の距離になるように制御点を置く
+    let reach = (8.0 * (radius + size) - 2.0 * radius * SELF_LOOP_END_ANGLE.cos())
+        / (6.0 * SELF_LOOP_CONTROL_ANGLE.cos());
+    [
+        center + rotated(SELF_LOOP_END_ANGLE) * radius,
+        center + rotated(SELF_LOOP_CONTROL_ANGLE) * reach,
+        center + rotated(-SELF_LOOP_CONTROL_ANGLE) * reach,
+        center + rotated(-SELF_LOOP_END_ANGLE) * radius,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{
+        parallel_edge_offsets, self_loop_direction, self_loop_directions, self_loop_points,
+        trim_cubic_bezier_end,
+    };
+
+    #[test]
+    fn parallel_edges_fan_out_on_alternating_sides() {
+        assert_eq!(parallel_edge_offsets([(0, 1), (1, 2)]), vec![0.0, 0.0]);
+        // 逆向きの 2 本はそれぞれ自分の左側に膨らむので，互いに反対側になる
+        assert_eq!(parallel_edge_offsets([(0, 1), (1, 0)]), vec![1.0, 1.0]);
+        assert_eq!(
+            parallel_edge_offsets([(0, 1), (0, 1), (0, 1)]),
+            vec![0.0, 1.0, -1.0]
+        );
+        assert_eq!(
+            parallel_edge_offsets([(0, 1), (1, 0), (0, 1), (0, 1)]),
+            vec![1.0, 1.0, 2.0, -2.0]
+        );
+        assert_eq!(
+            parallel_edge_offsets([(2, 2), (0, 1), (2, 2)]),
+            vec![0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn self_loop_faces_the_widest_gap_between_neighbors() {
+        let center = egui::pos2(0.0, 0.0);
+        assert_eq!(self_loop_direction(center, []), -egui::Vec2::Y);
+
+        let single = self_loop_direction(center, [egui::pos2(10.0, 0.0)]);
+        assert!(
+            (single - egui::vec2(-1.0, 0.0)).length() < 1e-5,
+            "{single:?}"
+        );
+
+        // 右と下に隣接点がある場合は左上を向く
+        let corner = self_loop_direction(center, [egui::pos2(10.0, 0.0), egui::pos2(0.0, 10.0)]);
+        let expected = egui::vec2(-1.0, -1.0).normalized();
+        assert!((corner - expected).length() < 1e-5, "{corner:?}");
+
+        let positions = HashMap::from([(0, center), (1, egui::pos2(0.0, -10.0))]);
+        let directions = self_loop_directions(&positions, [(0, 0), (0, 1), (1, 0)]);
+        assert_eq!(directions.len(), 1);
+        assert!((directions[&0] - egui::Vec2::Y).length() < 1e-5);
+    }
+
+    #[test]
+    fn self_loop_starts_and_ends_on_the_vertex_circle() {
+        let center = egui::pos2(100.0, 100.0);
+        let points = self_loop_points(center, 20.0, egui::vec2(1.0, 0.0), 40.0);
+
+        assert!((points[0].distance(center) - 20.0).abs() < 1e-3);
+        assert!((points[3].distance(center) - 20.0).abs() < 1e-3);
+        let apex = super::cubic_bezier_curve(points, 0.5);
+        assert!(
+            (apex - egui::pos2(160.0, 100.0)).length() < 1e-3,
+            "{apex:?}"
+        );
+
+        let trimmed = trim_cubic_bezier_end(points, 10.0);
+        assert_eq!(trimmed[0], points[0]);
+        assert!((trimmed[3].distance(points[3]) - 10.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn trimming_a_small_self_loop_keeps_its_shape() {
+        // 頂点が小さいと始点と終点の距離は矢印の長さより短くなる
+        let center = egui::pos2(0.0, 0.0);
+        let points = self_loop_points(center, 10.0, egui::vec2(0.0, -1.0), 48.0);
+        assert!(points[0].distance(points[3]) < 18.0);
+
+        let trimmed = trim_cubic_bezier_end(points, 18.0);
+        assert_eq!(trimmed[0], points[0]);
+        assert!((trimmed[3].distance(points[3]) - 18.0).abs() < 1e-2);
+
+        let mut both = trimmed;
+        both.reverse();
+        both = trim_cubic_bezier_end(both, 18.0);
+        assert!((both[3].distance(points[0]) - 18.0).abs() < 1e-2);
+        assert!(both[0].distance(both[3]) > 1.0, "{both:?}");
+    }
+}
@@ -0,0 +1,1045 @@
+//! Graphviz の DOT 形式の読み書き
+//!
+//! 読み込みは実用的な部分集合に対応する．ノード文・辺文・属性文と`ID = ID`を読み，
+//! サブグラフ（クラスタを含む）は平坦化する．ポート指定と`graph`属性は無視する．
+//!
+//! Graphviz の`weight`属性は配置のための値なので，辺の重みは独自の`ge_weight`属性に書き出す．
+
+use std::{cell::RefCell, collections::HashMap, fmt::Write as _, rc::Rc};
+
+use num_traits::One;
+
+use super::{
+    color_to_hex, default_vertex_positions, display_vertex_id, match_color, parse_hex_color,
+    ImportError, ImportedGraph,
+};
+use crate::{
+    components::Colors,
+    graph::{Edge, EdgeDirection, EdgeWeight, Graph, Vertex},
+    math::affine::Affine2D,
+    view_state::GraphViewState,
+};
+
+/// 辺の重みを書き出す属性
+const WEIGHT_ATTR: &str = "ge_weight";
+
+/// グラフを DOT 形式で出力する．頂点位置は y 軸を反転して`pos`属性に書き出す．
+pub fn export_graph_to_dot(graph: &Graph, view: &GraphViewState, zero_indexed: bool) -> String {
+    let (keyword, edge_op) = if graph.is_directed {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+
+    let mut dot = format!("{keyword} G {{\n");
+    dot.push_str("  node [shape=circle];\n");
+
+    let mut node_ids = HashMap::new();
+    for vertex in graph.vertices.iter().filter(|vertex| !vertex.is_deleted) {
+        let id = display_vertex_id(node_ids.len(), zero_indexed);
+        node_ids.insert(vertex.id, id);
+
+        let state = view.vertices.get(vertex.id);
+        let position = vertex.get_position();
+        let mut attrs = vec![];
+        if let Some(label) = state.and_then(|state| state.label.clone()) {
+            attrs.push(("label", label));
+        }
+        attrs.push(("pos", format!("{},{}!", position.x, -position.y)));
+        if vertex.is_pinned {
+            attrs.push(("pin", "true".to_string()));
+        }
+        if let Some(color) = state
+            .map(|state| state.color)
+            .filter(|color| *color != Colors::Default)
+        {
+            attrs.push(("style", "filled".to_string()));
+            attrs.push(("fillcolor", color_to_hex(color.vertex())));
+        }
+        if let Some(stroke_color) = state.and_then(|state| state.stroke_color) {
+            if !attrs.iter().any(|(key, _)| *key == "fillcolor") {
+                attrs.push(("fillcolor", color_to_hex(Colors::Default.vertex())));
+            }
+            attrs.push(("color", color_to_hex(stroke_color)));
+        }
+        if let Some(text_color) = state.and_then(|state| state.text_color) {
+            attrs.push(("fontcolor", color_to_hex(text_color)));
+        }
+        let _ = writeln!(dot, "  {id}{};", format_attrs(&attrs));
+    }
+
+    for (index, edge) in graph.edges.iter().enumerate() {
+        if edge.is_deleted {
+            continue;
+        }
+        let (Some(from), Some(to)) = (node_ids.get(&edge.from), node_ids.get(&edge.to)) else {
+            continue;
+        };
+
+        let state = view.edges.get(index);
+        let mut attrs = vec![];
+        if let Some(weight) = edge.weight.filter(|_| graph.is_weighted) {
+            attrs.push((WEIGHT_ATTR, weight.to_string()));
+        }
+        // グラフ全体の設定と異なる向きの辺だけ`dir`属性を付ける
+        let direction = edge.direction.resolve(graph.is_directed);
+        if direction != EdgeDirection::Inherit.resolve(graph.is_directed) {
+            let dir = match direction {
+                EdgeDirection::Directed => "forward",
+                EdgeDirection::Bidirectional => "both",
+                EdgeDirection::Undirected | EdgeDirection::Inherit => "none",
+            };
+            attrs.push(("dir", dir.to_string()));
+        }
+        if let Some(label) = state
+            .and_then(|state| state.label.clone())
+            .filter(|label| !label.is_empty())
+        {
+            attrs.push(("label", label));
+        }
+        if let Some(color) = state
+            .map(|state| state.color)
+            .filter(|color| *color != Colors::Default)
+        {
+            attrs.push(("color", color_to_hex(color.edge())));
+        }
+        if let Some(text_color) = state.and_then(|state| state.text_color) {
+            attrs.push(("fontcolor", color_to_hex(text_color)));
+        }
+        let _ = writeln!(dot, "  {from} {edge_op} {to}{};", format_attrs(&attrs));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// DOT 形式のテキストからグラフを読み込む
+pub fn import_graph_from_dot(text: &str) -> Result<ImportedGraph, ImportError> {
+    let parsed = Parser::new(tokenize(text)?).parse()?;
+    let (order, zero_indexed, is_numbered) = vertex_order(&parsed.nodes);
+
+    let n = parsed.nodes.len();
+    let mut positions = vec![None; n];
+    let mut is_pinned = vec![false; n];
+    for (node, &index) in parsed.nodes.iter().zip(&order) {
+        is_pinned[index] = node
+            .attrs
+            .get("pin")
+            .is_some_and(|(value, _)| matches!(value.as_str(), "true" | "1"));
+        if let Some((value, line)) = node.attrs.get("pos") {
+            positions[index] = Some(parse_pos(value).ok_or_else(|| ImportError::InvalidDot {
+                line: *line,
+                message: format!("invalid pos \"{value}\""),
+            })?);
+        }
+    }
+
+    let mut edges = Vec::with_capacity(parsed.edges.len());
+    for edge in &parsed.edges {
+        let weight = match edge.attrs.get(WEIGHT_ATTR) {
+            Some((value, line)) => {
+                Some(
+                    EdgeWeight::parse(value).map_err(|_| ImportError::InvalidDot {
+                        line: *line,
+                        message: format!("invalid weight \"{value}\""),
+                    })?,
+                )
+            }
+            None => None,
+        };
+        let (mut from, mut to) = (order[edge.from], order[edge.to]);
+        let direction = match edge.attrs.get("dir") {
+            Some((value, line)) => match value.as_str() {
+                "forward" => EdgeDirection::Directed,
+                "back" => {
+                    std::mem::swap(&mut from, &mut to);
+                    EdgeDirection::Directed
+                }
+                "both" => EdgeDirection::Bidirectional,
+                "none" => EdgeDirection::Undirected,
+                _ => {
+                    return Err(ImportError::InvalidDot {
+                        line: *line,
+                        message: format!("invalid dir \"{value}\""),
+                    })
+                }
+            },
+            None => EdgeDirection::Inherit,
+        };
+        // グラフ全体の設定と同じ向きは上書きしない
+        let direction = if direction == EdgeDirection::Inherit.resolve(parsed.directed) {
+            EdgeDirection::Inherit
+        } else {
+            direction
+        };
+        edges.push(Edge {
+            direction,
+            ..Edge::with_weight(from, to, weight)
+        });
+    }
+
+    let used_generated_positions = positions.iter().any(Option::is_none);
+    let default_positions = if used_generated_positions {
+        let pairs = edges
+            .iter()
+            .map(|edge| (edge.from, edge.to))
+            .collect::<Vec<_>>();
+        default_vertex_positions(n, &pairs)
+    } else {
+        vec![]
+    };
+
+    let affine = Rc::new(RefCell::new(Affine2D::one()));
+    let vertices = positions
+        .iter()
+        .enumerate()
+        .map(|(index, position)| Vertex {
+            id: index,
+            position: position.unwrap_or_else(|| default_positions[index]),
+            velocity: egui::Vec2::ZERO,
+            is_pinned: is_pinned[index],
+            is_deleted: false,
+            affine: affine.clone(),
+        })
+        .collect();
+
+    let mut graph = Graph {
+        is_directed: parsed.directed,
+        is_weighted: edges.iter().any(|edge| edge.weight.is_some()),
+        allows_multi_edges: false,
+        affine,
+        vertices,
+        edges,
+    };
+    graph.allows_multi_edges = graph.has_parallel_edges();
+
+    let mut view = GraphViewState::new_for_graph(&graph);
+    for (node, &index) in parsed.nodes.iter().zip(&order) {
+        let state = &mut view.vertices[index];
+        state.label = node
+            .attr("label")
+            .filter(|label| *label != "\\N")
+            .map(str::to_string)
+            .or_else(|| (!is_numbered).then(|| node.name.clone()));
+        state.color = node
+            .attr("fillcolor")
+            .or(node.attr("color"))
+            .map(|color| resolve_color(color, true))
+            .unwrap_or_default();
+        state.text_color = node.attr("fontcolor").and_then(parse_color);
+        // 塗りの色を指定した場合，`color`は輪郭の色になる
+        state.stroke_color = node
+            .attr("fillcolor")
+            .and(node.attr("color"))
+            .and_then(|color| {
+                parse_color(color).or_else(|| resolve_color(color, true).to_egui_color())
+            });
+    }
+    for (edge, state) in parsed.edges.iter().zip(&mut view.edges) {
+        state.label = edge
+            .attr("label")
+            .filter(|label| !label.is_empty())
+            .map(str::to_string);
+        state.color = edge
+            .attr("color")
+            .map(|color| resolve_color(color, false))
+            .unwrap_or_default();
+        state.text_color = edge.attr("fontcolor").and_then(parse_color);
+    }
+
+    Ok(ImportedGraph {
+        graph,
+        view,
+        zero_indexed,
+        used_generated_positions,
+        layout_seed: None,
+    })
+}
+
+fn format_attrs(attrs: &[(&str, String)]) -> String {
+    if attrs.is_empty() {
+        return String::new();
+    }
+    let attrs = attrs
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape_string(value)))
+        .collect::<Vec<_>>();
+    format!(" [{}]", attrs.join(", "))
+}
+
+fn escape_string(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// ノード名がすべて 0 または 1 から始まる連番であればその番号順に並べ，
+/// そうでなければ出現順に並べる．（各ノードの頂点番号，0-indexed か，連番か）を返す．
+fn vertex_order(nodes: &[DotNode]) -> (Vec<usize>, bool, bool) {
+    let numbers = nodes
+        .iter()
+        .map(|node| node.name.parse::<usize>().ok())
+        .collect::<Option<Vec<_>>>();
+    if let Some(numbers) = numbers {
+        let mut sorted = numbers.clone();
+        sorted.sort_unstable();
+        for origin in [1, 0] {
+            if sorted
+                .iter()
+                .enumerate()
+                .all(|(index, &number)| number == index + origin)
+            {
+                let order = numbers.iter().map(|number| number - origin).collect();
+                return (order, origin == 0, true);
+            }
+        }
+    }
+    ((0..nodes.len()).collect(), false, false)
+}
+
+fn parse_pos(value: &str) -> Option<egui::Pos2> {
+    let mut coords = value.trim().trim_end_matches('!').split(',');
+    let x = coords.next()?.trim().parse::<f32>().ok()?;
+    let y = coords.next()?.trim().parse::<f32>().ok()?;
+    Some(egui::pos2(x, -y))
+}
+
+/// `#rrggbb`，`#rrggbbaa`形式の色を読む
+fn parse_color(value: &str) -> Option<egui::Color32> {
+    let value = value.trim();
+    match value.len() {
+        9 if value.starts_with('#') => parse_hex_color(&value[..7]),
+        _ => parse_hex_color(value),
+    }
+}
+
+/// 色の名前またはパレットの色に一致する 16 進表記を`Colors`に変換する
+fn resolve_color(value: &str, vertex: bool) -> Colors {
+    if let Some(color) = Colors::PALETTE
+        .into_iter()
+        .find(|color| color.name().eq_ignore_ascii_case(value.trim()))
+    {
+        return color;
+    }
+    match_color(parse_color(value), vertex)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Id { text: String, quoted: bool },
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Equal,
+    Semicolon,
+    Comma,
+    Colon,
+    Plus,
+    EdgeOp { directed: bool },
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            Self::Id { text, .. } => format!("\"{text}\""),
+            Self::LBrace => "`{`".to_string(),
+            Self::RBrace => "`}`".to_string(),
+            Self::LBracket => "`[`".to_string(),
+            Self::RBracket => "`]`".to_string(),
+            Self::Equal => "`=`".to_string(),
+            Self::Semicolon => "`;`".to_string(),
+            Self::Comma => "`,`".to_string(),
+            Self::Colon => "`:`".to_string(),
+            Self::Plus => "`+`".to_string(),
+            Self::EdgeOp { directed: true } => "`->`".to_string(),
+            Self::EdgeOp { directed: false } => "`--`".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+}
+
+fn dot_error(line: usize, message: impl Into<String>) -> ImportError {
+    ImportError::InvalidDot {
+        line,
+        message: message.into(),
+    }
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii()
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ImportError> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    let mut line = 1;
+    let mut at_line_start = true;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+            at_line_start = true;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        // `#`で始まる行（C プリプロセッサの出力）は読み飛ばす
+        if c == '#' && at_line_start {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        at_line_start = false;
+
+        let start_line = line;
+        let next = chars.get(i + 1).copied();
+        let kind = match c {
+            '/' if next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if next == Some('*') => {
+                i += 2;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(dot_error(start_line, "unterminated comment")),
+                        Some('*') if chars.get(i + 1) == Some(&'/') => {
+                            i += 2;
+                            break;
+                        }
+                        Some('\n') => line += 1,
+                        Some(_) => {}
+                    }
+                    i += 1;
+                }
+                continue;
+            }
+            '{' => TokenKind::LBrace,
+            '}' => TokenKind::RBrace,
+            '[' => TokenKind::LBracket,
+            ']' => TokenKind::RBracket,
+            '=' => TokenKind::Equal,
+            ';' => TokenKind::Semicolon,
+            ',' => TokenKind::Comma,
+            ':' => TokenKind::Colon,
+            '+' => TokenKind::Plus,
+            '-' if next == Some('-') || next == Some('>') => {
+                i += 1;
+                TokenKind::EdgeOp {
+                    directed: next == Some('>'),
+                }
+            }
+            '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(dot_error(start_line, "unterminated string")),
+                        Some('"') => break,
+                        Some('\\') if matches!(chars.get(i + 1), Some('"' | '\\')) => {
+                            text.push(chars[i + 1]);
+                            i += 1;
+                        }
+                        Some('\\') if chars.get(i + 1) == Some(&'\n') => {
+                            line += 1;
+                            i += 1;
+                        }
+                        Some(&c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            text.push(c);
+                        }
+                    }
+                    i += 1;
+                }
+                TokenKind::Id { text, quoted: true }
+            }
+            '<' => {
+                let mut text = String::new();
+                let mut depth = 1;
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(dot_error(start_line, "unterminated HTML string")),
+                        Some('<') => depth += 1,
+                        Some('>') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        Some('\n') => line += 1,
+                        Some(_) => {}
+                    }
+                    text.push(chars[i]);
+                    i += 1;
+                }
+                TokenKind::Id { text, quoted: true }
+            }
+            c if c.is_ascii_digit() || c == '.' || c == '-' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text = chars[start..i].iter().collect::<String>();
+                if text == "-" || text == "." {
+                    return Err(dot_error(start_line, format!("unexpected `{text}`")));
+                }
+                tokens.push(Token {
+                    kind: TokenKind::Id {
+                        text,
+                        quoted: false,
+                    },
+                    line: start_line,
+                });
+                continue;
+            }
+            c if is_id_char(c) => {
+                let start = i;
+                while i < chars.len() && is_id_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token {
+                    kind: TokenKind::Id {
+                        text: chars[start..i].iter().collect(),
+                        quoted: false,
+                    },
+                    line: start_line,
+                });
+                continue;
+            }
+            c => return Err(dot_error(start_line, format!("unexpected character `{c}`"))),
+        };
+        tokens.push(Token {
+            kind,
+            line: start_line,
+        });
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+/// 属性名と（値，行番号）
+type Attrs = HashMap<String, (String, usize)>;
+
+#[derive(Debug)]
+struct DotNode {
+    name: String,
+    attrs: Attrs,
+}
+
+impl DotNode {
+    fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs.get(key).map(|(value, _)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct DotEdge {
+    from: usize,
+    to: usize,
+    attrs: Attrs,
+}
+
+impl DotEdge {
+    fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs.get(key).map(|(value, _)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct DotGraph {
+    directed: bool,
+    nodes: Vec<DotNode>,
+    edges: Vec<DotEdge>,
+}
+
+/// `strict`なグラフでは同じ頂点対の辺を 1 本にまとめ，後から指定した属性で上書きする
+fn merge_duplicate_edges(edges: Vec<DotEdge>, directed: bool) -> Vec<DotEdge> {
+    let mut index: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut merged: Vec<DotEdge> = Vec::with_capacity(edges.len());
+    for edge in edges {
+        let key = if directed {
+            (edge.from, edge.to)
+        } else {
+            (edge.from.min(edge.to), edge.from.max(edge.to))
+        };
+        match index.get(&key) {
+            Some(&i) => merged[i].attrs.extend(edge.attrs),
+            None => {
+                index.insert(key, merged.len());
+                merged.push(edge);
+            }
+        }
+    }
+    merged
+}
+
+/// `node [...]`，`edge [...]`で指定された既定の属性．サブグラフの中だけで有効．
+#[derive(Debug, Clone, Default)]
+struct Defaults {
+    node: Attrs,
+    edge: Attrs,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    directed: bool,
+    nodes: Vec<DotNode>,
+    node_index: HashMap<String, usize>,
+    edges: Vec<DotEdge>,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            directed: false,
+            nodes: vec![],
+            node_index: HashMap::new(),
+            edges: vec![],
+        }
+    }
+
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.pos).map(|token| &token.kind)
+    }
+
+    fn peek_is(&self, kind: &TokenKind) -> bool {
+        self.peek() == Some(kind)
+    }
+
+    /// 現在のトークンの行番号．末尾に達している場合は最後のトークンの行番号．
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |token| token.line)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ImportError {
+        dot_error(self.line(), message)
+    }
+
+    fn unexpected(&self, expected: &str) -> ImportError {
+        match self.peek() {
+            Some(kind) => self.error(format!("expected {expected}, found {}", kind.describe())),
+            None => self.error(format!("expected {expected}, found end of input")),
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), ImportError> {
+        if self.peek_is(&kind) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected(&kind.describe()))
+        }
+    }
+
+    /// 引用符で囲まれていないキーワード（大文字小文字を区別しない）か
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(
+            self.peek(),
+            Some(TokenKind::Id { text, quoted: false }) if text.eq_ignore_ascii_case(keyword)
+        )
+    }
+
+    fn parse_id(&mut self) -> Result<String, ImportError> {
+        let Some(TokenKind::Id { text, quoted }) = self.peek().cloned() else {
+            return Err(self.unexpected("an identifier"));
+        };
+        self.pos += 1;
+
+        let mut text = text;
+        if quoted {
+            while self.peek_is(&TokenKind::Plus) {
+                self.pos += 1;
+                match self.peek().cloned() {
+                    Some(TokenKind::Id {
+                        text: rest,
+                        quoted: true,
+                    }) => {
+                        self.pos += 1;
+                        text.push_str(&rest);
+                    }
+                    _ => return Err(self.unexpected("a quoted string after `+`")),
+                }
+            }
+        }
+        Ok(text)
+    }
+
+    fn parse(mut self) -> Result<DotGraph, ImportError> {
+        let strict = self.is_keyword("strict");
+        if strict {
+            self.pos += 1;
+        }
+        self.directed = if self.is_keyword("digraph") {
+            true
+        } else if self.is_keyword("graph") {
+            false
+        } else {
+            return Err(self.unexpected("`graph` or `digraph`"));
+        };
+        self.pos += 1;
+
+        if matches!(self.peek(), Some(TokenKind::Id { .. })) {
+            self.parse_id()?;
+        }
+        self.expect(TokenKind::LBrace)?;
+        self.parse_stmt_list(&mut Defaults::default())?;
+        self.expect(TokenKind::RBrace)?;
+
+        if self.peek().is_some() {
+            return Err(self.error("unexpected content after the graph"));
+        }
+
+        let edges = if strict {
+            merge_duplicate_edges(self.edges, self.directed)
+        } else {
+            self.edges
+        };
+        Ok(DotGraph {
+            directed: self.directed,
+            nodes: self.nodes,
+            edges,
+        })
+    }
+
+    /// `}`の手前まで文を読み，現れたノードを返す
+    fn parse_stmt_list(&mut self, defaults: &mut Defaults) -> Result<Vec<usize>, ImportError> {
+        let mut members = vec![];
+        loop {
+            match self.peek() {
+                None => return Err(self.unexpected("`}`")),
+                Some(TokenKind::RBrace) => return Ok(members),
+                Some(TokenKind::Semicolon) => self.pos += 1,
+                _ => self.parse_stmt(defaults, &mut members)?,
+            }
+        }
+    }
+
+    fn parse_stmt(
+        &mut self,
+        defaults: &mut Defaults,
+        members: &mut Vec<usize>,
+    ) -> Result<(), ImportError> {
+        for keyword in ["graph", "node", "edge"] {
+            if self.is_keyword(keyword) {
+                self.pos += 1;
+                if !self.peek_is(&TokenKind::LBracket) {
+                    return Err(self.unexpected("`[`"));
+                }
+                let attrs = self.parse_attr_lists()?;
+                match keyword {
+                    "node" => defaults.node.extend(attrs),
+                    "edge" => defaults.edge.extend(attrs),
+                    _ => {}
+                }
+                return Ok(());
+            }
+        }
+
+        let first = if self.peek_is(&TokenKind::LBrace) || self.is_keyword("subgraph") {
+            self.parse_subgraph(defaults)?
+        } else {
+            let name = self.parse_id()?;
+            if self.peek_is(&TokenKind::Equal) {
+                // グラフの属性は使わない
+                self.pos += 1;
+                self.parse_id()?;
+                return Ok(());
+            }
+            self.skip_port()?;
+            let node = self.ensure_node(name, defaults);
+            if !matches!(self.peek(), Some(TokenKind::EdgeOp { .. })) {
+                let attrs = self.parse_attr_lists()?;
+                self.nodes[node].attrs.extend(attrs);
+                members.push(node);
+                return Ok(());
+            }
+            vec![node]
+        };
+
+        let mut operands = vec![first];
+        while let Some(&TokenKind::EdgeOp { directed }) = self.peek() {
+            if directed != self.directed {
+                return Err(self.error(if directed {
+                    "`->` in an undirected graph"
+                } else {
+                    "`--` in a directed graph"
+                }));
+            }
+            self.pos += 1;
+            let operand = if self.peek_is(&TokenKind::LBrace) || self.is_keyword("subgraph") {
+                self.parse_subgraph(defaults)?
+            } else {
+                let name = self.parse_id()?;
+                self.skip_port()?;
+                vec![self.ensure_node(name, defaults)]
+            };
+            operands.push(operand);
+        }
+
+        let mut attrs = defaults.edge.clone();
+        attrs.extend(self.parse_attr_lists()?);
+        if operands.len() == 1 {
+            // 辺につながらないサブグラフ
+            members.extend(&operands[0]);
+            return Ok(());
+        }
+        for pair in operands.windows(2) {
+            for &from in &pair[0] {
+                for &to in &pair[1] {
+                    self.edges.push(DotEdge {
+                        from,
+                        to,
+                        attrs: attrs.clone(),
+                    });
+                }
+            }
+        }
+        members.extend(operands.into_iter().flatten());
+        Ok(())
+    }
+
+    fn parse_subgraph(&mut self, defaults: &Defaults) -> Result<Vec<usize>, ImportError> {
+        if self.is_keyword("subgraph") {
+            self.pos += 1;
+            if matches!(self.peek(), Some(TokenKind::Id { .. })) {
+                self.parse_id()?;
+            }
+        }
+        self.expect(TokenKind::LBrace)?;
+        let mut inner = defaults.clone();
+        let members = self.parse_stmt_list(&mut inner)?;
+        self.expect(TokenKind::RBrace)?;
+        Ok(members)
+    }
+
+    /// `node:port:compass`のポート指定を読み飛ばす
+    fn skip_port(&mut self) -> Result<(), ImportError> {
+        for _ in 0..2 {
+            if !self.peek_is(&TokenKind::Colon) {
+                break;
+            }
+            self.pos += 1;
+            self.parse_id()?;
+        }
+        Ok(())
+    }
+
+    /// `[key=value, ...]`を 0 個以上読む
+    fn parse_attr_lists(&mut self) -> Result<Attrs, ImportError> {
+        let mut attrs = Attrs::new();
+        while self.peek_is(&TokenKind::LBracket) {
+            self.pos += 1;
+            while !self.peek_is(&TokenKind::RBracket) {
+                let line = self.line();
+                let key = self.parse_id()?;
+                if !self.peek_is(&TokenKind::Equal) {
+                    return Err(self.unexpected(&format!("`=` after attribute \"{key}\"")));
+                }
+                self.pos += 1;
+                let value = self.parse_id()?;
+                attrs.insert(key, (value, line));
+                if self.peek_is(&TokenKind::Comma) || self.peek_is(&TokenKind::Semicolon) {
+                    self.pos += 1;
+                }
+            }
+            self.pos += 1;
+        }
+        Ok(attrs)
+    }
+
+    fn ensure_node(&mut self, name: String, defaults: &Defaults) -> usize {
+        if let Some(&index) = self.node_index.get(&name) {
+            return index;
+        }
+        let index = self.nodes.len();
+        self.node_index.insert(name.clone(), index);
+        self.nodes.push(DotNode {
+            name,
+            attrs: defaults.node.clone(),
+        });
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_graph_to_dot, import_graph_from_dot};
+    use crate::{
+        components::Colors,
+        graph::{EdgeDirection, EdgeWeight},
+        project_io::ImportError,
+    };
+
+    #[test]
+    fn round_trips_structure_positions_colors_and_labels() {
+        let dot = r##"digraph G {
+  1 [pos="10,-20!", fillcolor="red", label="start"];
+  2 [pos="30,-40!"];
+  1 -> 2 [ge_weight=5, label="a \"b\"", color="#4646ff"];
+}"##;
+        let imported = import_graph_from_dot(dot).unwrap();
+        assert!(imported.graph.is_directed);
+        assert!(imported.graph.is_weighted);
+        assert!(!imported.zero_indexed);
+        assert!(!imported.used_generated_positions);
+        assert_eq!(imported.graph.vertices[0].position, egui::pos2(10.0, 20.0));
+        assert_eq!(imported.graph.edges[0].weight, Some(EdgeWeight::Int(5)));
+        assert_eq!(imported.view.vertices[0].color, Colors::Red);
+        assert_eq!(imported.view.vertices[0].label.as_deref(), Some("start"));
+        assert_eq!(imported.view.edges[0].color, Colors::Blue);
+        assert_eq!(imported.view.edges[0].label.as_deref(), Some("a \"b\""));
+
+        let exported = export_graph_to_dot(&imported.graph, &imported.view, false);
+        let again = import_graph_from_dot(&exported).unwrap();
+        assert_eq!(again.graph.edges[0].from, 0);
+        assert_eq!(again.graph.edges[0].to, 1);
+        assert_eq!(again.graph.vertices[1].position, egui::pos2(30.0, 40.0));
+        assert_eq!(again.view.vertices[0].label.as_deref(), Some("start"));
+        assert_eq!(again.view.edges[0].label.as_deref(), Some("a \"b\""));
+        assert_eq!(again.view.edges[0].color, Colors::Blue);
+    }
+
+    #[test]
+    fn flattens_subgraphs_and_applies_default_attributes() {
+        let dot = "strict graph {
+  // comment
+  subgraph cluster_0 { node [color=green]; a -- b }
+  b -- { c d } [label=x]
+  /* block
+     comment */
+  e
+}";
+        let imported = import_graph_from_dot(dot).unwrap();
+        assert!(!imported.graph.is_directed);
+        assert!(imported.used_generated_positions);
+        assert_eq!(imported.graph.vertices.len(), 5);
+        assert_eq!(imported.graph.edges.len(), 3);
+        assert_eq!(imported.view.vertices[0].label.as_deref(), Some("a"));
+        assert_eq!(imported.view.vertices[1].color, Colors::Green);
+        assert_eq!(imported.view.vertices[2].color, Colors::Default);
+        assert_eq!(imported.view.edges[2].label.as_deref(), Some("x"));
+    }
+
+    #[test]
+    fn weights_use_their_own_attribute() {
+        // Graphviz の配置用の`weight`は辺の重みとして読まない
+        let imported = import_graph_from_dot("digraph { a -> b [weight=3] }").unwrap();
+        assert!(!imported.graph.is_weighted);
+        assert_eq!(imported.graph.edges[0].weight, None);
+
+        let imported =
+            import_graph_from_dot("digraph { a -> b [ge_weight=-1.5]; b -> c [ge_weight=2] }")
+                .unwrap();
+        let exported = export_graph_to_dot(&imported.graph, &imported.view, false);
+        assert!(exported.contains("[ge_weight=\"-1.5\"]"), "{exported}");
+        assert!(!exported.contains(" weight="));
+        let again = import_graph_from_dot(&exported).unwrap();
+        assert_eq!(again.graph.edges[0].weight, Some(EdgeWeight::Float(-1.5)));
+        assert_eq!(again.graph.edges[1].weight, Some(EdgeWeight::Int(2)));
+    }
+
+    #[test]
+    fn strict_graphs_merge_duplicate_edges() {
+        let imported =
+            import_graph_from_dot("strict digraph { a -> b; a -> b [label=x]; b -> a }").unwrap();
+        assert_eq!(imported.graph.edges.len(), 2);
+        assert!(!imported.graph.allows_multi_edges);
+        assert_eq!(imported.view.edges[0].label.as_deref(), Some("x"));
+
+        let imported = import_graph_from_dot("strict graph { a -- b; b -- a }").unwrap();
+        assert_eq!(imported.graph.edges.len(), 1);
+
+        let imported = import_graph_from_dot("digraph { a -> b; a -> b }").unwrap();
+        assert_eq!(imported.graph.edges.len(), 2);
+    }
+
+    #[test]
+    fn numeric_node_names_keep_their_order() {
+        let imported = import_graph_from_dot("graph { 2 -- 0; 1 }").unwrap();
+        assert!(imported.zero_indexed);
+        assert_eq!(imported.graph.edges[0].from, 2);
+        assert_eq!(imported.graph.edges[0].to, 0);
+        assert_eq!(imported.view.vertices[0].label, None);
+    }
+
+    #[test]
+    fn round_trips_edge_directions_through_the_dir_attribute() {
+        let dot = "digraph { a -> b; b -> c [dir=none]; c -> d [dir=both]; a -> d [dir=back] }";
+        let imported = import_graph_from_dot(dot).unwrap();
+        let directions = |graph: &crate::graph::Graph| {
+            graph
+                .edges
+                .iter()
+                .map(|edge| (edge.from, edge.to, edge.direction))
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![
+            (0, 1, EdgeDirection::Inherit),
+            (1, 2, EdgeDirection::Undirected),
+            (2, 3, EdgeDirection::Bidirectional),
+            (3, 0, EdgeDirection::Inherit),
+        ];
+        assert_eq!(directions(&imported.graph), expected);
+
+        let exported = export_graph_to_dot(&imported.graph, &imported.view, false);
+        assert!(exported.contains("[dir=\"none\"]"));
+        assert!(!exported.contains("dir=forward"));
+        let again = import_graph_from_dot(&exported).unwrap();
+        assert_eq!(directions(&again.graph), expected);
+    }
+
+    #[test]
+    fn keeps_colors_outside_the_palette() {
+        let dot = r##"graph { a [fillcolor="#123456", color="#abcdef"]; a -- b [color="#fedcba", fontcolor="#010203"] }"##;
+        let imported = import_graph_from_dot(dot).unwrap();
+        let rgb = |hex: &str| egui::Color32::from_hex(hex).unwrap();
+        let check = |imported: &crate::project_io::ImportedGraph| {
+            let vertex = &imported.view.vertices[0];
+            assert_eq!(vertex.color, Colors::Custom(rgb("#123456")));
+            assert_eq!(vertex.stroke_color, Some(rgb("#abcdef")));
+            assert_eq!(imported.view.vertices[1].stroke_color, None);
+            let edge = &imported.view.edges[0];
+            assert_eq!(edge.color, Colors::Custom(rgb("#fedcba")));
+            assert_eq!(edge.text_color, Some(rgb("#010203")));
+        };
+        check(&imported);
+
+        let exported = export_graph_to_dot(&imported.graph, &imported.view, false);
+        check(&import_graph_from_dot(&exported).unwrap());
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        let line_of = |dot: &str| match import_graph_from_dot(dot).unwrap_err() {
+            ImportError::InvalidDot { line, .. } => line,
+            err => panic!("unexpected error: {err}"),
+        };
+        assert_eq!(line_of("graph {\n  a -- b\n  a -> c\n}"), 3);
+        assert_eq!(line_of("digraph {\n  a [label=\"x]\n}"), 2);
+        assert_eq!(line_of("digraph {\n\n  a [pos=\"oops\"]\n}"), 3);
+        assert_eq!(line_of("digraph {\n  a -> b\n"), 2);
+    }
+}
@@ -6,16 +6,22 @@ use std::{
 };
 
 use num_traits::One;
+use rand::{rngs::StdRng, SeedableRng};
+
+pub mod dot;
 
 use crate::{
     components::Colors,
-    graph::{visualize_methods, Edge, Graph, Vertex, Visualizer},
+    graph::{visualize_methods, Edge, EdgeDirection, EdgeWeight, Graph, Vertex, Visualizer},
     math::affine::Affine2D,
     view_state::GraphViewState,
 };
 
-const GRAPH_FILE_FORMAT: &str = "graph-editor";
-const GRAPH_FILE_VERSION: u32 = 1;
+pub const GRAPH_FILE_FORMAT: &str = "graph-editor";
+/// バージョン 2 から，頂点の`stroke`は輪郭の色，辺の`text`は文字色を表し，
+/// 既定の色（`Colors::Default`）の`fill`・`stroke`は書き出さない．
+/// バージョン 1 ではどちらも塗りの色（辺の色）の複製で，既定の色も書き出していた．
+const GRAPH_FILE_VERSION: u32 = 2;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GraphFile {
@@ -27,9 +33,17 @@ pub struct GraphFile {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GraphData {
     pub directed: bool,
+    #[serde(default)]
+    pub weighted: bool,
+    /// 多重辺を許可するか
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multigraph: bool,
     pub index_origin: u8,
     #[serde(default)]
     pub features: GraphFeatures,
+    /// 乱択を行う配置の乱数の種
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout_seed: Option<u64>,
     pub vertices: Vec<VertexData>,
     pub edges: Vec<EdgeData>,
 }
@@ -53,6 +67,10 @@ pub struct VertexData {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub style: Option<VertexStyleData>,
+
+    /// シミュレーションで動かさない頂点
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -61,6 +79,13 @@ pub struct EdgeData {
     pub from: usize,
     pub to: usize,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<EdgeWeight>,
+
+    /// グラフ全体の設定を上書きする辺の向き
+    #[serde(default, skip_serializing_if = "EdgeDirection::is_inherit")]
+    pub direction: EdgeDirection,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
 
@@ -109,6 +134,8 @@ pub struct SaveOptions {
     pub include_vertex_position: bool,
     pub include_vertex_style: bool,
     pub include_edge_style: bool,
+    /// ファイルに記録する配置の乱数の種
+    pub layout_seed: Option<u64>,
 }
 
 impl Default for SaveOptions {
@@ -117,6 +144,7 @@ impl Default for SaveOptions {
             include_vertex_position: true,
             include_vertex_style: true,
             include_edge_style: true,
+            layout_seed: None,
         }
     }
 }
@@ -130,6 +158,7 @@ pub enum ImportError {
     DuplicateVertexId(usize),
     DuplicateEdgeId(usize),
     MissingVertex { edge_id: usize, vertex_id: usize },
+    InvalidDot { line: usize, message: String },
 }
 
 impl fmt::Display for ImportError {
@@ -146,6 +175,7 @@ impl fmt::Display for ImportError {
             Self::MissingVertex { edge_id, vertex_id } => {
                 write!(f, "Edge {edge_id} references missing vertex {vertex_id}")
             }
+            Self::InvalidDot { line, message } => write!(f, "Invalid DOT (line {line}): {message}"),
         }
     }
 }
@@ -173,6 +203,8 @@ pub struct ImportedGraph {
     pub view: GraphViewState,
     pub zero_indexed: bool,
     pub used_generated_positions: bool,
+    /// ファイルに記録されていた配置の乱数の種
+    pub layout_seed: Option<u64>,
 }
 
 pub fn export_graph_to_file(
@@ -181,10 +213,49 @@ pub fn export_graph_to_file(
     zero_indexed: bool,
     options: SaveOptions,
 ) -> GraphFile {
+    export_vertices_to_file(graph, view, zero_indexed, options, None)
+}
+
+/// 選択中の頂点が誘導する部分グラフを出力する．
+/// 貼り付け先で番号が振り直されるため，ラベルは明示的に設定されたもののみ出力する．
+pub fn export_subgraph_to_file(
+    graph: &Graph,
+    view: &GraphViewState,
+    zero_indexed: bool,
+    selected: &[bool],
+) -> GraphFile {
+    export_vertices_to_file(
+        graph,
+        view,
+        zero_indexed,
+        SaveOptions::default(),
+        Some(selected),
+    )
+}
+
+pub fn export_subgraph_to_json(
+    graph: &Graph,
+    view: &GraphViewState,
+    zero_indexed: bool,
+    selected: &[bool],
+) -> Result<String, ExportError> {
+    let file = export_subgraph_to_file(graph, view, zero_indexed, selected);
+    serde_json::to_string_pretty(&file).map_err(|err| ExportError::SerializeFailed(err.to_string()))
+}
+
+fn export_vertices_to_file(
+    graph: &Graph,
+    view: &GraphViewState,
+    zero_indexed: bool,
+    options: SaveOptions,
+    selected: Option<&[bool]>,
+) -> GraphFile {
+    let is_included =
+        |id: usize| selected.is_none_or(|selected| selected.get(id).copied().unwrap_or(false));
     let active_vertices: Vec<_> = graph
         .vertices
         .iter()
-        .filter(|vertex| !vertex.is_deleted)
+        .filter(|vertex| !vertex.is_deleted && is_included(vertex.id))
         .collect();
     let mut vertex_id_map = HashMap::new();
     for (index, vertex) in active_vertices.iter().enumerate() {
@@ -196,12 +267,15 @@ pub fn export_graph_to_file(
         .enumerate()
         .map(|(index, vertex)| VertexData {
             id: index,
-            label: Some(
-                view.vertices
-                    .get(vertex.id)
-                    .and_then(|state| state.label.clone())
-                    .unwrap_or_else(|| display_vertex_id(index, zero_indexed).to_string()),
-            ),
+            label: view
+                .vertices
+                .get(vertex.id)
+                .and_then(|state| state.label.clone())
+                .or_else(|| {
+                    selected
+                        .is_none()
+                        .then(|| display_vertex_id(index, zero_indexed).to_string())
+                }),
             position: options.include_vertex_position.then(|| PositionData {
                 x: vertex.get_position().x,
                 y: vertex.get_position().y,
@@ -211,13 +285,13 @@ pub fn export_graph_to_file(
                 let vertex_state = view.vertices.get(vertex.id);
                 let color = vertex_state.map(|state| state.color).unwrap_or_default();
                 VertexStyleData {
-                    fill: Some(color_to_hex(color.vertex())),
-                    stroke: Some(color_to_hex(color.vertex())),
-                    text: Some(color_to_hex(
-                        vertex_state
-                            .and_then(|state| state.text_color)
-                            .unwrap_or(egui::Color32::BLACK),
-                    )),
+                    fill: color.to_egui_color().map(color_to_hex),
+                    stroke: vertex_state
+                        .and_then(|state| state.stroke_color)
+                        .map(color_to_hex),
+                    text: vertex_state
+                        .and_then(|state| state.text_color)
+                        .map(color_to_hex),
                     radius: vertex_state
                         .and_then(|state| state.radius)
                         .filter(|radius| (*radius - defaults.vertex_radius).abs() > f32::EPSILON),
@@ -226,6 +300,7 @@ pub fn export_graph_to_file(
                         .filter(|width| (*width - defaults.vertex_stroke).abs() > f32::EPSILON),
                 }
             }),
+            pinned: vertex.is_pinned,
         })
         .collect();
 
@@ -241,7 +316,13 @@ pub fn export_graph_to_file(
                 id: edge_index,
                 from,
                 to,
-                label: Some(String::new()),
+                weight: edge.weight,
+                direction: edge.direction,
+                label: view
+                    .edges
+                    .get(edge_index)
+                    .and_then(|state| state.label.clone())
+                    .filter(|label| !label.is_empty()),
                 style: options.include_edge_style.then(|| {
                     let defaults = crate::config::AppConfig::default();
                     let color = view
@@ -250,8 +331,12 @@ pub fn export_graph_to_file(
                         .map(|state| state.color)
                         .unwrap_or_default();
                     EdgeStyleData {
-                        stroke: Some(color_to_hex(color.edge())),
-                        text: Some(color_to_hex(color.edge())),
+                        stroke: color.to_egui_color().map(color_to_hex),
+                        text: view
+                            .edges
+                            .get(edge_index)
+                            .and_then(|state| state.text_color)
+                            .map(color_to_hex),
                         stroke_width: view
                             .edges
                             .get(edge_index)
@@ -268,12 +353,15 @@ pub fn export_graph_to_file(
         version: GRAPH_FILE_VERSION,
         graph: GraphData {
             directed: graph.is_directed,
+            weighted: graph.is_weighted,
+            multigraph: graph.allows_multi_edges,
             index_origin: if zero_indexed { 0 } else { 1 },
             features: GraphFeatures {
                 vertex_position: options.include_vertex_position,
                 vertex_style: options.include_vertex_style,
                 edge_style: options.include_edge_style,
             },
+            layout_seed: options.layout_seed,
             vertices,
             edges,
         },
@@ -294,7 +382,7 @@ pub fn import_graph_from_file(file: GraphFile) -> Result<ImportedGraph, ImportEr
     if file.format != GRAPH_FILE_FORMAT {
         return Err(ImportError::InvalidFormat(file.format));
     }
-    if file.version != GRAPH_FILE_VERSION {
+    if !(1..=GRAPH_FILE_VERSION).contains(&file.version) {
         return Err(ImportError::UnsupportedVersion(file.version));
     }
     if file.graph.index_origin != 0 && file.graph.index_origin != 1 {
@@ -365,6 +453,7 @@ pub fn import_graph_from_file(file: GraphFile) -> Result<ImportedGraph, ImportEr
                 id: index,
                 position,
                 velocity: egui::Vec2::ZERO,
+                is_pinned: vertex.pinned,
                 is_deleted: false,
                 affine: affine.clone(),
             }
@@ -373,29 +462,46 @@ pub fn import_graph_from_file(file: GraphFile) -> Result<ImportedGraph, ImportEr
 
     let graph_edges = edge_pairs
         .iter()
-        .map(|&(from, to)| Edge::new(from, to))
+        .zip(&edges)
+        .map(|(&(from, to), edge)| Edge {
+            direction: edge.direction,
+            ..Edge::with_weight(from, to, edge.weight)
+        })
         .collect::<Vec<_>>();
 
-    let graph = Graph {
+    let mut graph = Graph {
         is_directed: file.graph.directed,
+        is_weighted: file.graph.weighted,
+        allows_multi_edges: file.graph.multigraph,
         affine,
         vertices: graph_vertices,
         edges: graph_edges,
     };
+    graph.allows_multi_edges |= graph.has_parallel_edges();
 
     let mut view = GraphViewState::new_for_graph(&graph);
     for (index, vertex) in vertices.iter().enumerate() {
         view.vertices[index].label = vertex.label.clone();
         if let Some(style) = &vertex.style {
-            view.vertices[index].color = color_from_vertex_style(style);
+            view.vertices[index].color =
+                color_from_style(style.fill.as_deref(), true, file.version);
             view.vertices[index].text_color = style.text.as_deref().and_then(parse_hex_color);
+            if file.version >= 2 {
+                view.vertices[index].stroke_color =
+                    style.stroke.as_deref().and_then(parse_hex_color);
+            }
             view.vertices[index].radius = style.radius;
             view.vertices[index].stroke_width = style.stroke_width;
         }
     }
     for (index, edge) in edges.iter().enumerate() {
+        view.edges[index].label = edge.label.clone().filter(|label| !label.is_empty());
         if let Some(style) = &edge.style {
-            view.edges[index].color = color_from_edge_style(style);
+            view.edges[index].color =
+                color_from_style(style.stroke.as_deref(), false, file.version);
+            if file.version >= 2 {
+                view.edges[index].text_color = style.text.as_deref().and_then(parse_hex_color);
+            }
             view.edges[index].stroke_width = style.stroke_width;
         }
     }
@@ -405,6 +511,7 @@ pub fn import_graph_from_file(file: GraphFile) -> Result<ImportedGraph, ImportEr
         view,
         zero_indexed: file.graph.index_origin == 0,
         used_generated_positions,
+        layout_seed: file.graph.layout_seed,
     })
 }
 
@@ -426,7 +533,12 @@ fn default_vertex_positions(n: usize, edges: &[(usize, usize)]) -> Vec<egui::Pos
     let size = egui::vec2(720.0, 720.0);
     let margin = size * 0.1;
     let base = egui::pos2(0.0, 0.0);
-    let positions = visualize_methods::Spectral.resolve_vertex_position(n, edges);
+    // スペクトル配置は乱数を使わないため，種は何でもよい
+    let positions = visualize_methods::Spectral.resolve_vertex_position(
+        n,
+        edges,
+        &mut StdRng::seed_from_u64(0),
+    );
     positions
         .into_iter()
         .map(|pos| base + margin + pos * size * 0.8)
@@ -449,55 +561,34 @@ fn parse_hex_color(hex: &str) -> Option<egui::Color32> {
     Some(egui::Color32::from_rgb(r, g, b))
 }
 
-fn color_from_vertex_style(style: &VertexStyleData) -> Colors {
-    let color = style
-        .fill
-        .as_deref()
-        .or(style.stroke.as_deref())
-        .or(style.text.as_deref())
-        .and_then(parse_hex_color);
-    match_color(color, true)
-}
-
-fn color_from_edge_style(style: &EdgeStyleData) -> Colors {
-    let color = style
-        .stroke
-        .as_deref()
-        .or(style.text.as_deref())
-        .and_then(parse_hex_color);
-    match_color(color, false)
+/// 頂点の`fill`・辺の`stroke`から色を読み取る．バージョン 2 からは`Colors::Default`を
+/// 省略して書き出すため，書かれた色はすべてそのまま読み込む．
+/// バージョン 1 では既定の色も書き出していたため，既定の色と一致すれば`Colors::Default`とみなす
+fn color_from_style(hex: Option<&str>, vertex: bool, version: u32) -> Colors {
+    let color = hex.and_then(parse_hex_color);
+    if version >= 2 {
+        color.map(Colors::from_rgb).unwrap_or_default()
+    } else {
+        match_color(color, vertex)
+    }
 }
 
+/// 既定の色と一致すれば`Colors::Default`，パレットの色と一致すればその色，
+/// それ以外は任意の色として読み込む
 fn match_color(color: Option<egui::Color32>, vertex: bool) -> Colors {
     let Some(color) = color else {
         return Colors::Default;
     };
 
-    for candidate in [
-        Colors::Default,
-        Colors::Red,
-        Colors::Green,
-        Colors::Blue,
-        Colors::Yellow,
-        Colors::Orange,
-        Colors::Violet,
-        Colors::Pink,
-        Colors::Brown,
-        Colors::Cyan,
-        Colors::Indigo,
-        Colors::Gray,
-    ] {
-        let expected = if vertex {
-            candidate.vertex()
-        } else {
-            candidate.edge()
-        };
-        if expected == color {
-            return candidate;
-        }
+    let default = if vertex {
+        Colors::Default.vertex()
+    } else {
+        Colors::Default.edge()
+    };
+    if color == default {
+        return Colors::Default;
     }
-
-    Colors::Default
+    Colors::from_rgb(color)
 }
 
 #[cfg(test)]
@@ -508,13 +599,14 @@ mod tests {
 
     use crate::{
         components::Colors,
-        graph::{Edge, Graph, Vertex},
+        graph::{Edge, EdgeDirection, EdgeWeight, Graph, Vertex},
         math::affine::Affine2D,
         view_state::GraphViewState,
     };
 
     use super::{
-        export_graph_to_json, import_graph_from_json, GraphData, GraphFeatures, GraphFile,
+        export_graph_to_file, export_graph_to_json, export_subgraph_to_json,
+        import_graph_from_file, import_graph_from_json, GraphData, GraphFeatures, GraphFile,
         ImportError, SaveOptions, VertexData,
     };
 
@@ -522,12 +614,15 @@ mod tests {
         let affine = Rc::new(RefCell::new(Affine2D::one()));
         let graph = Graph {
             is_directed: true,
+            is_weighted: false,
+            allows_multi_edges: false,
             affine: affine.clone(),
             vertices: vec![
                 Vertex {
                     id: 0,
                     position: egui::pos2(120.0, 80.0),
                     velocity: egui::Vec2::ZERO,
+                    is_pinned: false,
                     is_deleted: false,
                     affine: affine.clone(),
                 },
@@ -535,6 +630,7 @@ mod tests {
                     id: 1,
                     position: egui::pos2(260.0, 140.0),
                     velocity: egui::Vec2::ZERO,
+                    is_pinned: true,
                     is_deleted: false,
                     affine: affine.clone(),
                 },
@@ -558,6 +654,7 @@ mod tests {
                 include_vertex_position: false,
                 include_vertex_style: false,
                 include_edge_style: false,
+                layout_seed: None,
             },
         )
         .unwrap();
@@ -577,6 +674,77 @@ mod tests {
         assert!(!json.contains("\"stroke_width\""));
     }
 
+    #[test]
+    fn round_trips_custom_colors_losslessly() {
+        let (graph, mut view) = sample_graph();
+        let rgb = |hex: &str| egui::Color32::from_hex(hex).unwrap();
+        view.vertices[0].color = Colors::Custom(rgb("#123456"));
+        view.vertices[0].stroke_color = Some(rgb("#abcdef"));
+        view.vertices[1].color = Colors::Red;
+        view.vertices[1].text_color = Some(rgb("#010203"));
+        view.edges[0].color = Colors::Custom(rgb("#fedcba"));
+        view.edges[0].text_color = Some(rgb("#0a0b0c"));
+
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        assert!(json.contains("\"fill\": \"#123456\""));
+        let imported = import_graph_from_json(&json).unwrap();
+        assert_eq!(imported.view.vertices[0].color, view.vertices[0].color);
+        assert_eq!(
+            imported.view.vertices[0].stroke_color,
+            view.vertices[0].stroke_color
+        );
+        assert_eq!(imported.view.vertices[0].text_color, None);
+        assert_eq!(imported.view.vertices[1].color, Colors::Red);
+        assert_eq!(imported.view.vertices[1].stroke_color, None);
+        assert_eq!(
+            imported.view.vertices[1].text_color,
+            view.vertices[1].text_color
+        );
+        assert_eq!(imported.view.edges[0].color, view.edges[0].color);
+        assert_eq!(imported.view.edges[0].text_color, view.edges[0].text_color);
+    }
+
+    #[test]
+    fn custom_colors_equal_to_the_defaults_stay_custom() {
+        let (graph, mut view) = sample_graph();
+        let vertex_default = Colors::Default.vertex();
+        let edge_default = Colors::Default.edge();
+        view.vertices[0].color = Colors::Custom(vertex_default);
+        view.edges[0].color = Colors::Custom(edge_default);
+
+        let mut file = export_graph_to_file(&graph, &view, true, SaveOptions::default());
+        assert_eq!(file.graph.vertices[1].style.as_ref().unwrap().fill, None);
+        let imported = import_graph_from_file(file.clone()).unwrap();
+        assert_eq!(imported.view.vertices[0].color, view.vertices[0].color);
+        assert_eq!(imported.view.vertices[1].color, Colors::Default);
+        assert_eq!(imported.view.edges[0].color, view.edges[0].color);
+
+        // バージョン 1 では既定の色と一致すれば既定の色とみなす
+        file.version = 1;
+        let imported = import_graph_from_file(file).unwrap();
+        assert_eq!(imported.view.vertices[0].color, Colors::Default);
+        assert_eq!(imported.view.edges[0].color, Colors::Default);
+    }
+
+    #[test]
+    fn version_1_files_ignore_the_mirrored_stroke_and_text_colors() {
+        let (graph, mut view) = sample_graph();
+        view.vertices[0].color = Colors::Blue;
+        view.edges[0].color = Colors::Green;
+        let mut file = export_graph_to_file(&graph, &view, true, SaveOptions::default());
+        file.version = 1;
+        let vertex_style = file.graph.vertices[0].style.as_mut().unwrap();
+        vertex_style.stroke = vertex_style.fill.clone();
+        let edge_style = file.graph.edges[0].style.as_mut().unwrap();
+        edge_style.text = edge_style.stroke.clone();
+
+        let imported = import_graph_from_file(file).unwrap();
+        assert_eq!(imported.view.vertices[0].color, Colors::Blue);
+        assert_eq!(imported.view.vertices[0].stroke_color, None);
+        assert_eq!(imported.view.edges[0].color, Colors::Green);
+        assert_eq!(imported.view.edges[0].text_color, None);
+    }
+
     #[test]
     fn imports_preserve_edge_connections() {
         let (graph, view) = sample_graph();
@@ -595,18 +763,24 @@ mod tests {
             version: 1,
             graph: GraphData {
                 directed: false,
+                weighted: false,
+                multigraph: false,
                 index_origin: 0,
                 features: GraphFeatures::default(),
+                layout_seed: None,
                 vertices: vec![VertexData {
                     id: 0,
                     label: None,
                     position: None,
                     style: None,
+                    pinned: false,
                 }],
                 edges: vec![super::EdgeData {
                     id: 0,
                     from: 0,
                     to: 1,
+                    weight: None,
+                    direction: EdgeDirection::Inherit,
                     label: None,
                     style: None,
                 }],
@@ -631,20 +805,25 @@ mod tests {
             version: 1,
             graph: GraphData {
                 directed: false,
+                weighted: false,
+                multigraph: false,
                 index_origin: 0,
                 features: GraphFeatures::default(),
+                layout_seed: None,
                 vertices: vec![
                     VertexData {
                         id: 0,
                         label: None,
                         position: None,
                         style: None,
+                        pinned: false,
                     },
                     VertexData {
                         id: 0,
                         label: None,
                         position: None,
                         style: None,
+                        pinned: false,
                     },
                 ],
                 edges: vec![],
@@ -663,8 +842,11 @@ mod tests {
             version: 999,
             graph: GraphData {
                 directed: false,
+                weighted: false,
+                multigraph: false,
                 index_origin: 0,
                 features: GraphFeatures::default(),
+                layout_seed: None,
                 vertices: vec![],
                 edges: vec![],
             },
@@ -682,8 +864,11 @@ mod tests {
             version: 1,
             graph: GraphData {
                 directed: false,
+                weighted: false,
+                multigraph: false,
                 index_origin: 2,
                 features: GraphFeatures::default(),
+                layout_seed: None,
                 vertices: vec![],
                 edges: vec![],
             },
@@ -693,4 +878,135 @@ mod tests {
         let err = import_graph_from_json(&json).unwrap_err();
         assert!(matches!(err, ImportError::InvalidIndexOrigin(2)));
     }
+
+    #[test]
+    fn round_trips_edge_weights() {
+        let (mut graph, view) = sample_graph();
+        graph.is_weighted = true;
+        graph.edges[0].weight = Some(EdgeWeight::Float(2.5));
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        let imported = import_graph_from_json(&json).unwrap();
+
+        assert!(imported.graph.is_weighted);
+        assert_eq!(imported.graph.edges[0].weight, Some(EdgeWeight::Float(2.5)));
+    }
+
+    #[test]
+    fn round_trips_edge_labels() {
+        let (graph, mut view) = sample_graph();
+        view.edges[0].label = Some("bridge".to_string());
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        let imported = import_graph_from_json(&json).unwrap();
+
+        assert_eq!(imported.view.edges[0].label.as_deref(), Some("bridge"));
+    }
+
+    #[test]
+    fn round_trips_pinned_vertices() {
+        let (graph, view) = sample_graph();
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        assert_eq!(json.matches("\"pinned\"").count(), 1);
+
+        let imported = import_graph_from_json(&json).unwrap();
+        assert!(!imported.graph.vertices[0].is_pinned);
+        assert!(imported.graph.vertices[1].is_pinned);
+    }
+
+    #[test]
+    fn round_trips_the_multigraph_setting() {
+        let (mut graph, view) = sample_graph();
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        assert!(!json.contains("\"multigraph\""));
+        assert!(
+            !import_graph_from_json(&json)
+                .unwrap()
+                .graph
+                .allows_multi_edges
+        );
+
+        graph.allows_multi_edges = true;
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        assert!(
+            import_graph_from_json(&json)
+                .unwrap()
+                .graph
+                .allows_multi_edges
+        );
+
+        // 多重辺を含むファイルは設定がなくても多重グラフとして読み込む
+        graph.allows_multi_edges = false;
+        graph.edges.push(graph.edges[0].clone());
+        let mut view = view;
+        view.add_edge();
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        let imported = import_graph_from_json(&json).unwrap();
+        assert!(imported.graph.allows_multi_edges);
+        assert_eq!(imported.graph.edges.len(), graph.edges.len());
+    }
+
+    #[test]
+    fn round_trips_edge_direction_overrides() {
+        let (mut graph, view) = sample_graph();
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        assert!(!json.contains("\"direction\""));
+
+        graph.edges[0].direction = EdgeDirection::Bidirectional;
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        assert!(json.contains("\"direction\": \"bidirectional\""));
+        let imported = import_graph_from_json(&json).unwrap();
+        assert_eq!(
+            imported.graph.edges[0].direction,
+            EdgeDirection::Bidirectional
+        );
+    }
+
+    #[test]
+    fn omits_empty_edge_labels() {
+        let (graph, view) = sample_graph();
+        let json = export_graph_to_json(&graph, &view, true, SaveOptions::default()).unwrap();
+        let file: GraphFile = serde_json::from_str(&json).unwrap();
+
+        assert!(file.graph.edges[0].label.is_none());
+    }
+
+    #[test]
+    fn subgraph_export_keeps_only_induced_edges_and_custom_labels() {
+        let (mut graph, mut view) = sample_graph();
+        graph.add_vertex(egui::pos2(300.0, 300.0));
+        view.add_vertex(2);
+        graph.edges.push(Edge::new(1, 2));
+        view.add_edge();
+        view.vertices[2].label = Some("c".to_string());
+
+        let json = export_subgraph_to_json(&graph, &view, true, &[false, true, true]).unwrap();
+        let imported = import_graph_from_json(&json).unwrap();
+
+        assert_eq!(imported.graph.vertices.len(), 2);
+        assert_eq!(imported.graph.edges.len(), 1);
+        assert_eq!(
+            (imported.graph.edges[0].from, imported.graph.edges[0].to),
+            (0, 1)
+        );
+        assert_eq!(imported.view.vertices[0].label, None);
+        assert_eq!(imported.view.vertices[1].label.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn pasted_subgraph_is_appended_with_fresh_ids() {
+        let (mut graph, mut view) = sample_graph();
+        let json = export_subgraph_to_json(&graph, &view, true, &[true, true]).unwrap();
+        let imported = import_graph_from_json(&json).unwrap();
+
+        let base = graph.append(&imported.graph, egui::vec2(10.0, 0.0));
+        view.append(imported.view, 2);
+
+        assert_eq!(base, 2);
+        assert_eq!(graph.vertices.len(), 4);
+        assert_eq!(view.vertices.len(), 4);
+        assert_eq!((graph.edges[1].from, graph.edges[1].to), (2, 3));
+        assert_eq!(graph.vertices[2].get_position(), egui::pos2(130.0, 80.0));
+        assert_eq!(view.vertices[2].color, Colors::Red);
+        assert!(view.vertices[3].is_selected && view.edges[1].is_selected);
+        assert!(!view.vertices[0].is_selected);
+    }
 }
@@ -1,15 +1,20 @@
+use std::{cell::RefCell, rc::Rc};
+
 use eframe::egui;
 
+use crate::analysis::{player::TracePlayer, AlgorithmKind};
 use crate::components::{Colors, CursorHoverState, InspectorTab};
-use crate::graph::Graph;
+use crate::graph::{simulation_methods::GraphDistances, EdgeListOptions, Graph, GraphTextFormat};
+use crate::history::EditHistory;
 use crate::mode::EditMode;
 use crate::view_state::GraphViewState;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum IoFormat {
     #[default]
-    EdgeList,
+    Text,
     Json,
+    Dot,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,8 +26,11 @@ pub enum EditTarget {
 pub struct AppState {
     pub graph: Graph,
     pub graph_view: GraphViewState,
+    pub history: EditHistory,
     pub is_animated: bool,
     pub simulation_edge_length: f32,
+    /// シミュレーションで使うグラフ上の距離（フレームをまたいで使い回す）
+    pub graph_distances: Rc<RefCell<GraphDistances>>,
     pub last_mouse_pos: Option<egui::Pos2>,
     pub next_z_index: u32,
     pub edit_mode: EditMode,
@@ -37,11 +45,18 @@ pub struct UiState {
     pub input_text: String,
     pub input_synced_text: String,
     pub io_format: IoFormat,
+    pub text_format: GraphTextFormat,
+    pub edge_list_options: EdgeListOptions,
+    /// 現在のグラフを選択中のテキスト形式で表せない場合の理由
+    pub input_encode_error: Option<String>,
     pub json_text: String,
     pub json_synced_text: String,
+    pub dot_text: String,
+    pub dot_synced_text: String,
     pub input_has_focus: bool,
     pub input_is_dirty: bool,
     pub json_is_dirty: bool,
+    pub dot_is_dirty: bool,
     pub save_vertex_position: bool,
     pub save_vertex_style: bool,
     pub save_edge_style: bool,
@@ -51,4 +66,15 @@ pub struct UiState {
     pub inspector_tab: InspectorTab,
     pub edit_target: Option<EditTarget>,
     pub edit_window_pos: Option<egui::Pos2>,
+    /// 範囲選択の開始位置（画面座標）
+    pub marquee_start: Option<egui::Pos2>,
+    pub algorithm_kind: AlgorithmKind,
+    /// アルゴリズムの始点
+    pub algorithm_source: usize,
+    /// 直前に実行したアルゴリズムの結果の要約
+    pub algorithm_summary: Option<String>,
+    /// アルゴリズムの実行過程の再生
+    pub algorithm_player: Option<TracePlayer>,
+    /// 再生速度（イベント / 秒）
+    pub algorithm_speed: f32,
 }
@@ -1,7 +1,12 @@
 use crate::GraphEditorApp;
 
 pub fn request_repaint(app: &mut GraphEditorApp, ctx: &egui::Context) {
-    if app.state.is_animated {
+    let is_playing = app
+        .ui
+        .algorithm_player
+        .as_ref()
+        .is_some_and(|player| player.is_playing);
+    if app.state.is_animated || is_playing {
         ctx.request_repaint();
     }
 }
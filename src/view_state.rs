@@ -2,9 +2,13 @@ use std::collections::HashSet;
 
 use num_traits::One;
 
-use crate::{components::Colors, graph::Graph, math::affine::Affine2D};
+use crate::{
+    components::Colors,
+    graph::{EdgeDirection, EdgeWeight, Graph},
+    math::affine::Affine2D,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct VertexViewState {
     pub is_pressed: bool,
     pub is_selected: bool,
@@ -13,8 +17,14 @@ pub struct VertexViewState {
     pub color: Colors,
     pub label: Option<String>,
     pub text_color: Option<egui::Color32>,
+    /// 輪郭の色．`None`の場合は設定の色を使う
+    pub stroke_color: Option<egui::Color32>,
     pub radius: Option<f32>,
     pub stroke_width: Option<f32>,
+    /// アルゴリズムの結果として一時的に表示する色（ユーザーの色より優先）
+    pub highlight: Option<Colors>,
+    /// アルゴリズムの結果として一時的に表示するラベル
+    pub highlight_label: Option<String>,
 }
 
 impl Default for VertexViewState {
@@ -27,17 +37,26 @@ impl Default for VertexViewState {
             color: Colors::default(),
             label: None,
             text_color: None,
+            stroke_color: None,
             radius: None,
             stroke_width: None,
+            highlight: None,
+            highlight_label: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct EdgeViewState {
     pub is_pressed: bool,
+    pub is_selected: bool,
     pub color: Colors,
+    pub label: Option<String>,
+    /// 重みとラベルの文字色．`None`の場合は設定の色を使う
+    pub text_color: Option<egui::Color32>,
     pub stroke_width: Option<f32>,
+    /// アルゴリズムの結果として一時的に表示する色（ユーザーの色より優先）
+    pub highlight: Option<Colors>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,6 +101,29 @@ impl GraphViewState {
         self.edges.push(EdgeViewState::default());
     }
 
+    /// 別のグラフのビュー状態を末尾に追加し，追加した頂点・辺を選択状態にする
+    pub fn append(&mut self, other: GraphViewState, z_index_start: u32) {
+        self.vertices
+            .extend(
+                other
+                    .vertices
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, vertex)| VertexViewState {
+                        z_index: z_index_start + i as u32,
+                        is_selected: true,
+                        is_pressed: false,
+                        ..vertex
+                    }),
+            );
+        self.edges
+            .extend(other.edges.into_iter().map(|edge| EdgeViewState {
+                is_selected: true,
+                is_pressed: false,
+                ..edge
+            }));
+    }
+
     pub fn apply_deletions(&mut self, graph: &Graph) {
         let deleted_vertices: HashSet<usize> = graph
             .vertices
@@ -115,6 +157,40 @@ impl GraphViewState {
         self.edges = next_edges;
     }
 
+    /// 選択中の頂点・辺が存在するか
+    pub fn has_selection(&self) -> bool {
+        self.vertices.iter().any(|vertex| vertex.is_selected)
+            || self.edges.iter().any(|edge| edge.is_selected)
+    }
+
+    pub fn clear_selection(&mut self) {
+        for vertex in &mut self.vertices {
+            vertex.is_selected = false;
+        }
+        for edge in &mut self.edges {
+            edge.is_selected = false;
+        }
+    }
+
+    /// アルゴリズムの結果が表示されているか
+    pub fn has_highlight(&self) -> bool {
+        self.vertices
+            .iter()
+            .any(|vertex| vertex.highlight.is_some() || vertex.highlight_label.is_some())
+            || self.edges.iter().any(|edge| edge.highlight.is_some())
+    }
+
+    /// アルゴリズムの結果の表示を消す（ユーザーが設定した色・ラベルは残る）
+    pub fn clear_highlight(&mut self) {
+        for vertex in &mut self.vertices {
+            vertex.highlight = None;
+            vertex.highlight_label = None;
+        }
+        for edge in &mut self.edges {
+            edge.highlight = None;
+        }
+    }
+
     pub fn remove_color(&mut self) {
         for vertex in &mut self.vertices {
             vertex.color = Colors::default();
@@ -128,6 +204,9 @@ impl GraphViewState {
         for vertex in &mut self.vertices {
             vertex.label = Some(String::default());
         }
+        for edge in &mut self.edges {
+            edge.label = None;
+        }
     }
 
     pub fn snapshot(&self, graph: &Graph) -> GraphSnapshot {
@@ -143,10 +222,12 @@ impl GraphViewState {
                     position: v.get_position(),
                     is_pressed: view.is_pressed,
                     is_selected: view.is_selected,
+                    is_pinned: v.is_pinned,
                     z_index: view.z_index,
-                    color: view.color,
-                    label: view.label.clone(),
+                    color: view.highlight.unwrap_or(view.color),
+                    label: view.highlight_label.clone().or_else(|| view.label.clone()),
                     text_color: view.text_color,
+                    stroke_color: view.stroke_color,
                     radius: view.radius,
                     stroke_width: view.stroke_width,
                 })
@@ -168,8 +249,13 @@ impl GraphViewState {
                 Some(EdgeSnapshot {
                     from: e.from,
                     to: e.to,
+                    weight: e.weight,
+                    direction: e.direction.resolve(graph.is_directed),
                     is_pressed: view.is_pressed,
-                    color: view.color,
+                    is_selected: view.is_selected,
+                    color: view.highlight.unwrap_or(view.color),
+                    label: view.label.clone(),
+                    text_color: view.text_color,
                     stroke_width: view.stroke_width,
                 })
             })
@@ -177,6 +263,7 @@ impl GraphViewState {
 
         GraphSnapshot {
             is_directed: graph.is_directed,
+            is_weighted: graph.is_weighted,
             vertices,
             edges,
         }
@@ -189,10 +276,12 @@ pub struct VertexSnapshot {
     pub position: egui::Pos2,
     pub is_pressed: bool,
     pub is_selected: bool,
+    pub is_pinned: bool,
     pub z_index: u32,
     pub color: Colors,
     pub label: Option<String>,
     pub text_color: Option<egui::Color32>,
+    pub stroke_color: Option<egui::Color32>,
     pub radius: Option<f32>,
     pub stroke_width: Option<f32>,
 }
@@ -201,14 +290,21 @@ pub struct VertexSnapshot {
 pub struct EdgeSnapshot {
     pub from: usize,
     pub to: usize,
+    pub weight: Option<EdgeWeight>,
+    /// グラフ全体の設定を反映した向き（`Inherit`にはならない）
+    pub direction: EdgeDirection,
     pub is_pressed: bool,
+    pub is_selected: bool,
     pub color: Colors,
+    pub label: Option<String>,
+    pub text_color: Option<egui::Color32>,
     pub stroke_width: Option<f32>,
 }
 
 #[derive(Debug, Clone)]
 pub struct GraphSnapshot {
     pub is_directed: bool,
+    pub is_weighted: bool,
     pub vertices: Vec<VertexSnapshot>,
     pub edges: Vec<EdgeSnapshot>,
 }